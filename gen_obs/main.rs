@@ -9,24 +9,16 @@ extern crate nalgebra as na;
 use kiss3d::window::Window;
 use kiss3d::light::Light;
 
-extern crate serde;
-use serde::{Serialize,Deserialize};
-
 use std::env;
 
-#[derive(Serialize, Deserialize)]
-struct ObsData(pub [f32; 4]);
+extern crate sample_planning;
+use sample_planning::map_loader::{Obs,ObsData};
 
 use na::{Vector3, UnitQuaternion, Translation3, Point3, U3};
 
 extern crate clap;
 use clap::{Arg, App, SubCommand};
 
-#[derive(Serialize, Deserialize)]
-struct Obs {
-    obs: Vec<ObsData>,
-}
-
 fn generate_obstacles( num_obs: u32 ) -> Vec<ObsData> {
 
     use rand::Rng;
@@ -5,12 +5,12 @@ max=200
 
 # for (( i=1; i <= $max; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance" "--" "-p" "obs3" "-i" "2000000" "-b" "5000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "-p" "obs3" "-i" "2000000" "-b" "5000"
 # done
 
 # for (( i=1; i <= $max; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance,motion_primitives" "--" "-p" "obs3" "-i" "2000000" "-b" "5000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "--motion_primitives" "-p" "obs3" "-i" "2000000" "-b" "5000"
 # done
 
 # for (( i=1; i <= $max; ++i ))
@@ -20,12 +20,12 @@ max=200
 
 # for (( i=1; i <= $left; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance" "--" "-p" "obs3" "-i" "2000000" "-b" "5000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "-p" "obs3" "-i" "2000000" "-b" "5000"
 # done
 
 # for (( i=1; i <= $max; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance" "--" "-p" "obs3" "-i" "2000000" "-b" "20000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "-p" "obs3" "-i" "2000000" "-b" "20000"
 # done
 
 # for (( i=1; i <= $max; ++i ))
@@ -35,25 +35,42 @@ max=200
 
 # for (( i=1; i <= $max; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "motion_primitives" "--" "-p" "obs3" "-i" "2000000" "-b" "20000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--motion_primitives" "-p" "obs3" "-i" "2000000" "-b" "20000"
 # done
 
 # for (( i=1; i <= $max; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance" "--" "-p" "obs_sparse" "-i" "4000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "-p" "obs_sparse" "-i" "4000"
 # done
 
 # for (( i=1; i <= $max; ++i ))
 # do
-#     cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance,state_propagate_sample" "--" "-p" "obs_sparse" "-i" "4000"
+#     cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "--state_propagate_sample" "-p" "obs_sparse" "-i" "4000"
+# done
+
+# baselines, compare against sst runs on the same problem instance
+
+# for (( i=1; i <= $max; ++i ))
+# do
+#     cargo  "run" "--release" "--bin" "planner" "--" "--planner" "rrt" "--headless" "--output" "rrt_$i.json" "-p" "obs_sparse" "-i" "4000"
+# done
+
+# for (( i=1; i <= $max; ++i ))
+# do
+#     cargo  "run" "--release" "--bin" "planner" "--" "--planner" "rrt_star" "--headless" "--output" "rrt_star_$i.json" "-p" "obs_sparse" "-i" "4000"
+# done
+
+# for (( i=1; i <= $max; ++i ))
+# do
+#     cargo  "run" "--release" "--bin" "planner" "--" "--planner" "kpiece" "--headless" "--output" "kpiece_$i.json" "-p" "ht_0_hightown" "-i" "2000000"
 # done
 
 for (( i=1; i <= $max; ++i ))
 do
-    cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance,batch_propagate_sample" "--" "-p" "obs_sparse" "-i" "4000"
+    cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "--batch_propagate_sample" "-p" "obs_sparse" "-i" "4000"
 done
 
 for (( i=1; i <= $max; ++i ))
 do
-    cargo  "run" "--release" "--bin" "planner" "--features" "disable_witness_disturbance,state_propagate_sample,batch_propagate_sample" "--" "-p" "obs_sparse" "-i" "4000"
+    cargo  "run" "--release" "--bin" "planner" "--" "--disable_witness_disturbance" "--state_propagate_sample" "--batch_propagate_sample" "-p" "obs_sparse" "-i" "4000"
 done
@@ -0,0 +1,265 @@
+//! Collision checking of propagated motions
+//!
+//! A propagation is integrated into sub-states at a given duration resolution and every segment between
+//! consecutive sub-states is checked against the obstacles, so that curved motions do not cut through thin obstacles.
+//! The same check is used to validate stored solutions.
+
+use zpatial::implement::bvh_median::Bvh;
+use zpatial::interface::i_spatial_accel::ISpatialAccel;
+use zpatial::mazth::i_shape::IShape;
+use zpatial::mazth::line::Line3;
+
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::{Param,ParamObstacles,ObsVariant};
+use crate::system_model::SystemModel;
+use crate::solution::Solution;
+
+///propagates ``state`` with ``control`` for ``duration`` in steps of at most ``resolution``,
+///returns sub-states after ``state``, the last one being the propagated state, a single step if ``resolution`` is None
+pub fn integrate<TS,TC,TObs>( model: &dyn SystemModel<TS,TC,TObs>,
+                              state: TS,
+                              control: TC,
+                              duration: f32,
+                              resolution: Option<f32> ) -> Vec<TS> where TS: States, TC: Control, TObs: States {
+
+    let steps = match resolution {
+        Some(r) if r > 0. => { ( duration / r ).ceil().max( 1. ) as usize },
+        _ => { 1 },
+    };
+
+    let dt = duration / steps as f32;
+
+    let mut s = state;
+
+    (0..steps).map(|_| {
+        s = model.dynamics( s.clone(), control.clone(), dt );
+        s.clone()
+    }).collect()
+}
+
+///propagates ``state`` through consecutive (control, duration) ``edges``, returns the edges with sub-states as by integrate
+pub fn integrate_edges<TS,TC,TObs>( model: &dyn SystemModel<TS,TC,TObs>,
+                                    state: TS,
+                                    edges: Vec<(TC,f32)>,
+                                    resolution: Option<f32> ) -> Vec<(TC,f32,Vec<TS>)> where TS: States, TC: Control, TObs: States {
+
+    let mut s = state;
+
+    edges.into_iter().map(|(u,t)| {
+        let sub = integrate( model, s.clone(), u.clone(), t, resolution );
+        if let Some(x) = sub.last() {
+            s = x.clone();
+        }
+        (u, t, sub)
+    }).collect()
+}
+
+pub struct CollisionChecker<TObs> where TObs: States {
+
+    ///bvh contain indices to obstacles in obstacles_actual
+    pub obstacles: Bvh<usize>,
+
+    pub obstacles_actual: ParamObstacles<TObs>,
+
+    ///maximum propagation duration between checked sub-states, only the chord between end points is checked if None,
+    ///see PlannerConfig::collision_step
+    pub resolution: Option<f32>,
+}
+
+impl<TObs> CollisionChecker<TObs> where TObs: States {
+
+    pub fn init( obstacles: ParamObstacles<TObs>, resolution: Option<f32> ) -> Self {
+
+        let mut obs_tree = Bvh::init(10);
+
+        //get bounds as [(idx,aabb_bound)]
+        let bounds = match obstacles.obstacles {
+            ObsVariant::RBOX(ref x) => {
+                x.iter()
+                    .enumerate()
+                    .map(|x| (x.0, x.1.get_bound()) )
+                    .collect::<Vec<_>>()
+            },
+            ObsVariant::TRIPRISM(ref x) => {
+                x.iter()
+                    .enumerate()
+                    .map(|x| (x.0, x.1.get_bound()) )
+                    .collect::<Vec<_>>()
+            },
+        };
+
+        obs_tree.build_all( &bounds[..] ).expect("obstacle bounds not axis aligned");
+
+        CollisionChecker {
+            obstacles: obs_tree,
+            obstacles_actual: obstacles,
+            resolution: resolution,
+        }
+    }
+
+    ///return true if there is a collision on segment between ``a`` and ``b``
+    pub fn segment_collision( & self, a: &TObs, b: &TObs ) -> bool {
+
+        let v0 = a.get_vals_3();
+        let v1 = b.get_vals_3();
+
+        let query_line = Line3::init( &[v0[0] as _, v0[1] as _, v0[2] as _],
+                                      &[v1[0] as _, v1[1] as _, v1[2] as _] );
+
+        let num_obstacles = match self.obstacles_actual.obstacles {
+            ObsVariant::RBOX(ref x) => { x.len() },
+            ObsVariant::TRIPRISM(ref x) => { x.len() },
+        };
+
+        //bvh of no obstacles keeps an unbounded root, which intersects everything
+        if num_obstacles == 0 {
+            return false
+        }
+
+        let candidate_collisions = self.obstacles.query_intersect( &query_line._bound ).unwrap();
+
+        let mut candidates = candidate_collisions.iter().filter(|idx| **idx < num_obstacles );
+
+        match self.obstacles_actual.obstacles {
+            ObsVariant::TRIPRISM(ref x) => {
+                //narrow stage collision test for tri prisms
+                candidates.any(|idx| x[*idx].get_intersect( &query_line ).0 )
+            },
+            _ => { candidates.next().is_some() }, //box same as aabb box
+        }
+    }
+
+    ///return true if any segment between consecutive points of ``path`` collides
+    pub fn path_collision( & self, path: &[TObs] ) -> bool {
+        path.windows(2).any(|x| self.segment_collision( &x[0], &x[1] ) )
+    }
+
+    ///return true if any segment from ``before`` through config space projections of ``substates`` collides
+    pub fn propagation_collision<TS,TC>( & self,
+                                         model: &dyn SystemModel<TS,TC,TObs>,
+                                         before: &TObs,
+                                         substates: &[TS] ) -> bool where TS: States, TC: Control {
+        
+        let path = std::iter::once( before.clone() )
+            .chain( substates.iter().map(|x| model.project_state_to_config( x.clone() ) ) )
+            .collect::<Vec<_>>();
+
+        self.path_collision( &path )
+    }
+
+    ///re-integrates each edge of ``solution`` at the checker's resolution or sim_delta of ``param`` if finer,
+    ///also checks the stored sub-states of the edge if present, returns index of the first edge in collision
+    pub fn check_solution<TS,TC>( & self,
+                                  param: &Param<TS,TC,TObs>,
+                                  solution: &Solution<TS,TC> ) -> Option<usize> where TS: States, TC: Control {
+
+        let model = &*param.model;
+        
+        let resolution = Some( self.resolution.map_or( param.sim_delta, |r| r.min( param.sim_delta ) ) );
+
+        solution.states.iter()
+            .zip( solution.controls.iter().zip( solution.durations.iter() ) )
+            .enumerate()
+            .position(|(i,(s,(u,t)))| {
+                
+                let before = model.project_state_to_config( s.clone() );
+                
+                let substates = integrate( model, s.clone(), u.clone(), *t, resolution );
+                
+                let stored_collision = solution.substates.get(i).map_or( false, |x| {
+                    let path = x.iter().chain( solution.states.get(i+1) ).cloned().collect::<Vec<_>>();
+                    self.propagation_collision( model, &before, &path )
+                });
+                
+                stored_collision || self.propagation_collision( model, &before, &substates )
+            })
+    }
+}
+
+#[test]
+fn test_integrate_resolution(){
+
+    use std::marker::PhantomData;
+    use crate::states::States3D;
+    use crate::control::Control1D;
+    use crate::dynamics_dubins::DubinsCar;
+
+    let model = DubinsCar::default();
+    let s = States3D([0.2, 0.2, 0.]);
+    let u = Control1D([0.5]);
+
+    let sub = integrate( &model, s.clone(), u.clone(), 1., Some(0.3) );
+    assert_eq!( sub.len(), 4 );
+    assert_eq!( sub[0].0, model.dynamics( s.clone(), u.clone(), 0.25 ).0 );
+    assert_eq!( sub[3].0, model.dynamics( sub[2].clone(), u.clone(), 0.25 ).0 );
+
+    let sub = integrate( &model, s.clone(), u.clone(), 1., None );
+    assert_eq!( sub.len(), 1 );
+    assert_eq!( sub[0].0, model.dynamics( s.clone(), u.clone(), 1. ).0 );
+
+    let param = model.into_param();
+    let obs = ParamObstacles { obstacles: ObsVariant::RBOX(vec![]), states_info: PhantomData };
+    let checker = CollisionChecker::init( obs, Some(0.1) );
+    let sol = Solution { states: vec![ s.clone(), sub[0].clone() ], controls: vec![ u ], durations: vec![ 1. ], substates: vec![], cost: 1. };
+    assert_eq!( checker.check_solution( &param, &sol ), None );
+}
+
+#[test]
+fn test_no_obstacles(){
+
+    use std::marker::PhantomData;
+    use crate::states::States3D;
+
+    let a = States3D([0.1, 0.1, 0.]);
+    let b = States3D([0.9, 0.9, 0.]);
+
+    let boxes = CollisionChecker::init( ParamObstacles { obstacles: ObsVariant::RBOX(vec![]), states_info: PhantomData }, None );
+    assert!( !boxes.segment_collision( &a, &b ) );
+
+    let prisms = CollisionChecker::init( ParamObstacles { obstacles: ObsVariant::TRIPRISM(vec![]), states_info: PhantomData }, None );
+    assert!( !prisms.segment_collision( &a, &b ) );
+}
+
+#[test]
+fn test_thin_obstacle(){
+
+    use std::marker::PhantomData;
+    use std::f32::consts::PI;
+    use zpatial::mazth::rbox::RecBox;
+    use crate::states::States3D;
+    use crate::control::Control1D;
+    use crate::dynamics_dubins::DubinsCar;
+
+    //half turn of radius 0.5 from (0.2,0.1) to (0.2,1.1), passing (0.7,0.6), the chord stays at x=0.2
+    let param = DubinsCar::default().into_param();
+    let model = &*param.model;
+    let s = States3D([0.2, 0.1, 0.]);
+    let u = Control1D([2.]);
+    let t = PI / 2.;
+    
+    let thin_box = || ParamObstacles { obstacles: ObsVariant::RBOX(vec![ RecBox::init( &[0.7, 0.6, 0.], 0.05 ) ]), states_info: PhantomData };
+    let checker = CollisionChecker::init( thin_box(), Some(0.05) );
+    let checker_chord = CollisionChecker::init( thin_box(), None );
+
+    let sub = integrate( model, s.clone(), u.clone(), t, checker.resolution );
+    let end = sub.last().unwrap().clone();
+    assert!( ( end.0[0] - 0.2 ).abs() < 0.05 && ( end.0[1] - 1.1 ).abs() < 0.05 );
+
+    let before = model.project_state_to_config( s.clone() );
+    assert!( checker.propagation_collision( model, &before, &sub ) );
+    assert!( !checker_chord.propagation_collision( model, &before, std::slice::from_ref( &end ) ) );
+
+    //solutions are re-integrated at sim_delta at least, regardless of the checker's resolution
+    let sol = Solution { states: vec![ s.clone(), end.clone() ], controls: vec![ u ], durations: vec![ t ], substates: vec![], cost: t };
+    assert_eq!( checker.check_solution( &param, &sol ), Some(0) );
+    assert_eq!( checker_chord.check_solution( &param, &sol ), Some(0) );
+
+    //stored sub-states are checked too
+    let a = States3D([0.1, 0.6, 0.]);
+    let b = States3D([0.3, 0.6, 0.]);
+    let sol = Solution { states: vec![ a.clone(), b.clone() ], controls: vec![ Control1D([0.]) ], durations: vec![ 0.2 ], substates: vec![], cost: 0.2 };
+    assert_eq!( checker.check_solution( &param, &sol ), None );
+    let sol = Solution { substates: vec![ vec![ States3D([0.7, 0.6, 0.]) ] ], ..sol };
+    assert_eq!( checker.check_solution( &param, &sol ), Some(0) );
+}
@@ -1,11 +1,14 @@
 use std::fmt::Debug;
+
+use serde::{Serialize,Deserialize};
+use serde::de::DeserializeOwned;
     
-pub trait Control : Clone + Debug {
+pub trait Control : Clone + Debug + Serialize + DeserializeOwned + Send + Sync + 'static {
     fn get_num_dims(&self) -> i32;
     fn get_vals(&self) -> Vec<f32>;
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Control1D(pub [f32;1]);
 
 impl Control for Control1D {
@@ -17,7 +20,7 @@ impl Control for Control1D {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Control2D(pub[f32;2]);
 
 impl Control for Control2D {
@@ -29,7 +32,7 @@ impl Control for Control2D {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Control3D(pub[f32;3]);
 
 impl Control for Control3D {
@@ -41,7 +44,7 @@ impl Control for Control3D {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Control4D(pub[f32;4]);
 
 impl Control for Control4D {
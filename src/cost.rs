@@ -0,0 +1,244 @@
+//! Edge cost functionals
+//!
+//! Node cost is the sum of edge costs from the root of the propagation tree, used for witness replacement,
+//! pruning, goal selection and importance sampling fitness.
+//!
+//! Scenario files may specify a weighted blend of terms, eg:
+//! "cost": [ { "duration": 1.0 }, { "turning": 0.2 }, { "clearance": { "weight": 0.5, "radius": 0.05 } } ]
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::f32::consts::PI;
+
+use serde::{Serialize,Deserialize};
+
+use zpatial::mazth::i_shape::IShape;
+
+use crate::states::States;
+use crate::control::Control;
+use crate::system_model::SystemModel;
+use crate::planner_param::{ParamObstacles,ObsVariant};
+use crate::collision;
+
+pub trait CostFunction<TS,TC,TObs>: Debug + Send + Sync where TS: States, TC: Control, TObs: States {
+
+    ///cost of propagating ``start`` with ``control`` for ``duration`` reaching ``end``, expected to be non-negative
+    fn edge_cost( &self, model: &dyn SystemModel<TS,TC,TObs>, start: &TS, control: &TC, duration: f32, end: &TS ) -> f32;
+}
+
+///time of propagation, default
+#[derive(Clone,Debug,Default)]
+pub struct Duration;
+
+///positional distance between start and end states
+#[derive(Clone,Debug,Default)]
+pub struct PathLength;
+
+///squared magnitude of control integrated over duration
+#[derive(Clone,Debug,Default)]
+pub struct ControlEffort;
+
+///absolute heading change between start and end states, zero for models without heading
+#[derive(Clone,Debug,Default)]
+pub struct Turning;
+
+///penalty for passing within ``radius`` of an obstacle bound, growing linearly to ``duration`` at contact,
+///averaged over states of the propagation about half ``radius`` apart
+#[derive(Clone,Debug)]
+pub struct Clearance {
+    pub radius: f32,
+    ///axis aligned bounds of obstacles as (lower, upper)
+    pub bounds: Vec<([f32;3],[f32;3])>,
+}
+
+///sum of weighted costs
+#[derive(Clone,Debug)]
+pub struct Weighted<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+    pub terms: Vec<(f32, Arc<dyn CostFunction<TS,TC,TObs>>)>,
+}
+
+impl<TS,TC,TObs> CostFunction<TS,TC,TObs> for Duration where TS: States, TC: Control, TObs: States {
+    fn edge_cost( &self, _model: &dyn SystemModel<TS,TC,TObs>, _start: &TS, _control: &TC, duration: f32, _end: &TS ) -> f32 {
+        duration
+    }
+}
+
+impl<TS,TC,TObs> CostFunction<TS,TC,TObs> for PathLength where TS: States, TC: Control, TObs: States {
+    fn edge_cost( &self, model: &dyn SystemModel<TS,TC,TObs>, start: &TS, _control: &TC, _duration: f32, end: &TS ) -> f32 {
+        start.get_vals().iter().zip( end.get_vals().iter() )
+            .take( model.position_dims() )
+            .fold( 0., |acc, (x,y)| acc + (x-y)*(x-y) )
+            .sqrt()
+    }
+}
+
+impl<TS,TC,TObs> CostFunction<TS,TC,TObs> for ControlEffort where TS: States, TC: Control, TObs: States {
+    fn edge_cost( &self, _model: &dyn SystemModel<TS,TC,TObs>, _start: &TS, control: &TC, duration: f32, _end: &TS ) -> f32 {
+        control.get_vals().iter().fold( 0., |acc, x| acc + x*x ) * duration
+    }
+}
+
+impl<TS,TC,TObs> CostFunction<TS,TC,TObs> for Turning where TS: States, TC: Control, TObs: States {
+    fn edge_cost( &self, model: &dyn SystemModel<TS,TC,TObs>, start: &TS, _control: &TC, _duration: f32, end: &TS ) -> f32 {
+        match model.heading_dim() {
+            Some(i) => {
+                let d = ( start.get_vals()[i] - end.get_vals()[i] ).abs() % ( 2. * PI );
+                if d > PI { 2. * PI - d } else { d }
+            },
+            _ => { 0. },
+        }
+    }
+}
+
+impl Clearance {
+
+    pub fn init<TObs>( obstacles: &ParamObstacles<TObs>, radius: f32 ) -> Clearance where TObs: States {
+
+        //bound data holds lower corner then upper corner
+        let to_bounds = |d: [f64;32]| ( [ d[0] as f32, d[1] as f32, d[2] as f32 ],
+                                        [ d[3] as f32, d[4] as f32, d[5] as f32 ] );
+
+        let bounds = match obstacles.obstacles {
+            ObsVariant::RBOX(ref x) => {
+                x.iter().map(|o| to_bounds( o.get_bound().get_bound_data() ) ).collect()
+            },
+            ObsVariant::TRIPRISM(ref x) => {
+                x.iter().map(|o| to_bounds( o.get_bound().get_bound_data() ) ).collect()
+            },
+        };
+
+        Clearance {
+            radius: radius,
+            bounds: bounds,
+        }
+    }
+
+    ///distance of ``p`` to the nearest obstacle bound, infinite if there are no obstacles
+    pub fn distance( & self, p: [f32;3] ) -> f32 {
+        self.bounds.iter()
+            .map(|(lo,hi)| {
+                (0..3).map(|i| ( lo[i] - p[i] ).max( p[i] - hi[i] ).max( 0. ) )
+                    .fold( 0., |acc, x| acc + x*x )
+                    .sqrt()
+            })
+            .fold( std::f32::INFINITY, f32::min )
+    }
+}
+
+impl<TS,TC,TObs> CostFunction<TS,TC,TObs> for Clearance where TS: States, TC: Control, TObs: States {
+    fn edge_cost( &self, model: &dyn SystemModel<TS,TC,TObs>, start: &TS, control: &TC, duration: f32, end: &TS ) -> f32 {
+
+        if !( self.radius > 0. ) || self.bounds.is_empty() {
+            return 0.
+        }
+
+        let position = |s: &TS| {
+            let v = model.project_state_to_config( s.clone() ).get_vals_3();
+            [ v[0], v[1], v[2] ]
+        };
+
+        let penalty = |s: &TS| {
+            let d = self.distance( position( s ) );
+            if d < self.radius { ( self.radius - d ) / self.radius } else { 0. }
+        };
+
+        //sample count from the chord, the given end state is the last sample
+        let ( a, b ) = ( position( start ), position( end ) );
+        let chord = (0..3).fold( 0., |acc, i| acc + ( a[i] - b[i] ).powi(2) ).sqrt();
+        let steps = ( 2. * chord / self.radius ).ceil().max( 1. );
+
+        let substates = collision::integrate( model, start.clone(), control.clone(), duration, Some( duration / steps ) );
+
+        let total = substates[..substates.len()-1].iter()
+            .chain( std::iter::once( end ) )
+            .map( penalty )
+            .sum::<f32>();
+
+        duration * total / substates.len() as f32
+    }
+}
+
+impl<TS,TC,TObs> CostFunction<TS,TC,TObs> for Weighted<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+    fn edge_cost( &self, model: &dyn SystemModel<TS,TC,TObs>, start: &TS, control: &TC, duration: f32, end: &TS ) -> f32 {
+        self.terms.iter()
+            .map(|(w,c)| w * c.edge_cost( model, start, control, duration, end ) )
+            .sum()
+    }
+}
+
+///weighted term of a cost blend, as specified in scenario files
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostTerm {
+    Duration(f32),
+    PathLength(f32),
+    ControlEffort(f32),
+    Turning(f32),
+    Clearance { weight: f32, radius: f32 },
+}
+
+///weighted blend of ``terms``, obstacles are used by clearance terms
+pub fn from_terms<TS,TC,TObs>( terms: &[CostTerm], obstacles: &ParamObstacles<TObs> ) -> Arc<dyn CostFunction<TS,TC,TObs>> where TS: States, TC: Control, TObs: States {
+
+    let terms = terms.iter()
+        .map(|t| {
+            let c : (f32, Arc<dyn CostFunction<TS,TC,TObs>>) = match t {
+                CostTerm::Duration(w) => { ( *w, Arc::new( Duration ) ) },
+                CostTerm::PathLength(w) => { ( *w, Arc::new( PathLength ) ) },
+                CostTerm::ControlEffort(w) => { ( *w, Arc::new( ControlEffort ) ) },
+                CostTerm::Turning(w) => { ( *w, Arc::new( Turning ) ) },
+                CostTerm::Clearance { weight, radius } => { ( *weight, Arc::new( Clearance::init( obstacles, *radius ) ) ) },
+            };
+            c
+        })
+        .collect();
+
+    Arc::new( Weighted { terms: terms } )
+}
+
+#[test]
+fn test_cost_terms(){
+
+    use std::marker::PhantomData;
+    use crate::states::States3D;
+    use crate::control::Control1D;
+    use crate::dynamics_dubins::DubinsCar;
+    use zpatial::mazth::rbox::RecBox;
+
+    let model = DubinsCar::default();
+    let a = States3D([0., 0., 0.1]);
+    let b = States3D([0.3, 0.4, 2. * PI - 0.1]);
+    let u = Control1D([2.]);
+
+    let obs = ParamObstacles { obstacles: ObsVariant::RBOX(vec![]), states_info: PhantomData };
+
+    let c = from_terms( &[ CostTerm::Duration(1.), CostTerm::PathLength(2.), CostTerm::ControlEffort(0.5), CostTerm::Turning(1.) ], &obs );
+    let expect = 0.5 + 2. * 0.5 + 0.5 * 4. * 0.5 + 0.2;
+    assert!( ( c.edge_cost( &model, &a, &u, 0.5, &b ) - expect ).abs() < 1e-5 );
+
+    let clearance = Clearance { radius: 0.1, bounds: vec![ ( [0.5, 0.5, -1.], [0.6, 0.6, 1.] ) ] };
+    assert!( ( clearance.distance( [0.45, 0.55, 0.] ) - 0.05 ).abs() < 1e-5 );
+    assert_eq!( clearance.distance( [0.55, 0.55, 0.] ), 0. );
+
+    let clearance_cost = |start: [f32;3], duration: f32| {
+        let start = States3D(start);
+        let straight = Control1D([0.]);
+        let end = model.dynamics( start.clone(), straight.clone(), duration );
+        CostFunction::<States3D,Control1D,States3D>::edge_cost( &clearance, &model, &start, &straight, duration, &end )
+    };
+
+    //straight at unit speed, ending 0.05 from the bound after closing in from 0.25
+    let c = clearance_cost( [0.2, 0.55, 0.], 0.25 );
+    assert!( c > 0. && c < 0.25 * 0.5 );
+    
+    //passing by the bound is penalised even though the end is clear
+    let c = clearance_cost( [0.4, 0.45, 0.], 0.3 );
+    assert!( c > 0. );
+    assert_eq!( clearance_cost( [0.4, 0.3, 0.], 0.3 ), 0. );
+
+    //bounds of obstacle shapes
+    let boxes = ParamObstacles::<States3D> { obstacles: ObsVariant::RBOX( vec![ RecBox::init( &[0.55, 0.55, 0.], 0.05 ) ] ), states_info: PhantomData };
+    let clearance = Clearance::init( &boxes, 0.1 );
+    assert_eq!( clearance.bounds.len(), 1 );
+    assert!( ( clearance.bounds[0].0[0] - 0.5 ).abs() < 1e-6 && ( clearance.bounds[0].1[1] - 0.6 ).abs() < 1e-6 );
+}
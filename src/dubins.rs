@@ -0,0 +1,265 @@
+//! Dubins curves
+//!
+//! Shortest paths of a forward moving car with bounded turning radius between two states [ x, y, theta ],
+//! composed of left turn (L), straight (S) and right turn (R) segments: LSL, RSR, LSR, RSL, RLR, LRL.
+//! Based on Shkel and Lumelsky, Classification of the Dubins set.
+
+use std::f32::consts::PI;
+
+use serde::{Serialize,Deserialize};
+
+use crate::states::States3D;
+use crate::control::Control1D;
+
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+pub enum Word {
+    LSL,
+    RSR,
+    LSR,
+    RSL,
+    RLR,
+    LRL,
+}
+
+pub const WORDS: [Word;6] = [ Word::LSL, Word::RSR, Word::LSR, Word::RSL, Word::RLR, Word::LRL ];
+
+///turning direction of a segment
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum Turn {
+    L,
+    S,
+    R,
+}
+
+impl Word {
+    pub fn turns( & self ) -> [Turn;3] {
+        match self {
+            Word::LSL => { [ Turn::L, Turn::S, Turn::L ] },
+            Word::RSR => { [ Turn::R, Turn::S, Turn::R ] },
+            Word::LSR => { [ Turn::L, Turn::S, Turn::R ] },
+            Word::RSL => { [ Turn::R, Turn::S, Turn::L ] },
+            Word::RLR => { [ Turn::R, Turn::L, Turn::R ] },
+            Word::LRL => { [ Turn::L, Turn::R, Turn::L ] },
+        }
+    }
+}
+
+#[derive(Clone,Debug)]
+pub struct DubinsPath {
+    pub start: States3D,
+    pub word: Word,
+    pub radius: f32,
+    ///segment lengths normalized by radius, ie: turning angles of turn segments
+    pub segments: [f32;3],
+}
+
+///angle in [0,2*PI), angles within rounding error of 2*PI map to 0 so that no-op turns have zero length
+fn mod2pi( x: f32 ) -> f32 {
+    let y = x % ( 2. * PI );
+    let y = if y < 0. { y + 2. * PI } else { y };
+    if y > 2. * PI - 1e-5 { 0. } else { y }
+}
+
+///state after moving ``s`` (normalized by radius, negative when reversing) along ``turn`` from ``q``, position in normalized units
+pub(crate) fn advance( q: [f32;3], turn: Turn, s: f32 ) -> [f32;3] {
+    let [x,y,t] = q;
+    match turn {
+        Turn::L => { [ x + ( t + s ).sin() - t.sin(), y - ( t + s ).cos() + t.cos(), t + s ] },
+        Turn::R => { [ x - ( t - s ).sin() + t.sin(), y + ( t - s ).cos() - t.cos(), t - s ] },
+        Turn::S => { [ x + s * t.cos(), y + s * t.sin(), t ] },
+    }
+}
+
+///normalized segment lengths of ``word`` for normalized distance ``d`` and headings ``a``, ``b`` relative to the start-end line
+fn segments( word: Word, d: f32, a: f32, b: f32 ) -> Option<[f32;3]> {
+
+    let (sa, sb, ca, cb) = ( a.sin(), b.sin(), a.cos(), b.cos() );
+    let c_ab = ( a - b ).cos();
+
+    match word {
+        Word::LSL => {
+            let p2 = 2. + d*d - 2.*c_ab + 2.*d*( sa - sb );
+            if p2 < 0. { return None }
+            let tmp = ( cb - ca ).atan2( d + sa - sb );
+            Some([ mod2pi( -a + tmp ), p2.sqrt(), mod2pi( b - tmp ) ])
+        },
+        Word::RSR => {
+            let p2 = 2. + d*d - 2.*c_ab + 2.*d*( sb - sa );
+            if p2 < 0. { return None }
+            let tmp = ( ca - cb ).atan2( d - sa + sb );
+            Some([ mod2pi( a - tmp ), p2.sqrt(), mod2pi( -b + tmp ) ])
+        },
+        Word::LSR => {
+            let p2 = -2. + d*d + 2.*c_ab + 2.*d*( sa + sb );
+            if p2 < 0. { return None }
+            let p = p2.sqrt();
+            let tmp = ( -ca - cb ).atan2( d + sa + sb ) - (-2f32).atan2( p );
+            Some([ mod2pi( -a + tmp ), p, mod2pi( -b + tmp ) ])
+        },
+        Word::RSL => {
+            let p2 = -2. + d*d + 2.*c_ab - 2.*d*( sa + sb );
+            if p2 < 0. { return None }
+            let p = p2.sqrt();
+            let tmp = ( ca + cb ).atan2( d - sa - sb ) - 2f32.atan2( p );
+            Some([ mod2pi( a - tmp ), p, mod2pi( b - tmp ) ])
+        },
+        Word::RLR => {
+            let tmp = ( 6. - d*d + 2.*c_ab + 2.*d*( sa - sb ) ) / 8.;
+            if tmp.abs() > 1. { return None }
+            let p = mod2pi( 2.*PI - tmp.acos() );
+            let t = mod2pi( a - ( ca - cb ).atan2( d - sa + sb ) + p / 2. );
+            Some([ t, p, mod2pi( a - b - t + p ) ])
+        },
+        Word::LRL => {
+            let tmp = ( 6. - d*d + 2.*c_ab + 2.*d*( sb - sa ) ) / 8.;
+            if tmp.abs() > 1. { return None }
+            let p = mod2pi( 2.*PI - tmp.acos() );
+            let t = mod2pi( -a - ( ca - cb ).atan2( d + sa - sb ) + p / 2. );
+            Some([ t, p, mod2pi( b - a - t + p ) ])
+        },
+    }
+}
+
+///path of ``word`` from ``start`` to ``end`` with turning ``radius``, None if the word does not connect them
+pub fn path( start: &States3D, end: &States3D, radius: f32, word: Word ) -> Option<DubinsPath> {
+
+    if !( radius > 0. ) {
+        return None
+    }
+
+    let dx = end.0[0] - start.0[0];
+    let dy = end.0[1] - start.0[1];
+    let d = ( dx*dx + dy*dy ).sqrt() / radius;
+    let theta = if d > 0. { dy.atan2( dx ) } else { 0. };
+
+    let a = mod2pi( start.0[2] - theta );
+    let b = mod2pi( end.0[2] - theta );
+
+    segments( word, d, a, b ).map(|x| DubinsPath { start: start.clone(), word: word, radius: radius, segments: x } )
+}
+
+///shortest of the six words from ``start`` to ``end`` with turning ``radius``, None if radius is not positive
+pub fn shortest_path( start: &States3D, end: &States3D, radius: f32 ) -> Option<DubinsPath> {
+    WORDS.iter()
+        .filter_map(|w| path( start, end, radius, *w ) )
+        .min_by(|a,b| a.length().partial_cmp( &b.length() ).unwrap_or( std::cmp::Ordering::Equal ) )
+}
+
+impl DubinsPath {
+
+    pub fn length( & self ) -> f32 {
+        self.segments.iter().sum::<f32>() * self.radius
+    }
+
+    ///state at distance ``s`` along the path, clamped to the path
+    pub fn state_at( & self, s: f32 ) -> States3D {
+
+        let r = self.radius;
+
+        let mut remain = s.max( 0. ) / r;
+        let mut q = [ self.start.0[0] / r, self.start.0[1] / r, self.start.0[2] ];
+
+        for (turn, len) in self.word.turns().iter().zip( self.segments.iter() ) {
+            let step = remain.min( *len );
+            q = advance( q, *turn, step );
+            remain -= step;
+        }
+
+        States3D([ q[0] * r, q[1] * r, mod2pi( q[2] ) ])
+    }
+
+    pub fn end( & self ) -> States3D {
+        self.state_at( self.length() )
+    }
+
+    ///states along the path at most ``step`` apart in distance, after start and ending at the path end
+    pub fn sample( & self, step: f32 ) -> Vec<States3D> {
+        let l = self.length();
+        let n = if step > 0. { ( l / step ).ceil().max( 1. ) as usize } else { 1 };
+        (1..=n).map(|i| self.state_at( l * i as f32 / n as f32 ) ).collect()
+    }
+
+    ///edges of the path for a car of ``speed``, as (heading rate control, duration, states along the edge),
+    ///states are at most ``resolution`` apart in duration, only the end of each edge if ``resolution`` is None
+    pub fn edges( & self, speed: f32, resolution: Option<f32> ) -> Vec<(Control1D,f32,Vec<States3D>)> {
+
+        let mut offset = 0.;
+        let mut out = vec![];
+
+        for (turn, len) in self.word.turns().iter().zip( self.segments.iter() ) {
+
+            let l = len * self.radius;
+
+            if l <= 0. {
+                continue;
+            }
+
+            let duration = l / speed;
+
+            let u = match turn {
+                Turn::L => { speed / self.radius },
+                Turn::R => { -speed / self.radius },
+                Turn::S => { 0. },
+            };
+
+            let n = match resolution {
+                Some(r) if r > 0. => { ( duration / r ).ceil().max( 1. ) as usize },
+                _ => { 1 },
+            };
+
+            let states = (1..=n).map(|i| self.state_at( offset + l * i as f32 / n as f32 ) ).collect();
+
+            out.push( ( Control1D([ u ]), duration, states ) );
+
+            offset += l;
+        }
+
+        out
+    }
+}
+
+#[test]
+fn test_dubins_paths(){
+
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    let mut rng = StdRng::seed_from_u64( 3 );
+
+    let close = |a: &States3D, b: &States3D| {
+        let dh = mod2pi( a.0[2] - b.0[2] );
+        ( a.0[0] - b.0[0] ).abs() < 1e-3 && ( a.0[1] - b.0[1] ).abs() < 1e-3 && dh.min( 2.*PI - dh ) < 1e-3
+    };
+
+    let mut words_found = vec![];
+
+    for _ in 0..300 {
+
+        let a = States3D([ rng.gen_range(0., 1.), rng.gen_range(0., 1.), rng.gen_range(0., 2.*PI) ]);
+        let b = States3D([ rng.gen_range(0., 1.), rng.gen_range(0., 1.), rng.gen_range(0., 2.*PI) ]);
+        let radius = rng.gen_range(0.05, 0.4);
+
+        for w in WORDS.iter() {
+            if let Some(p) = path( &a, &b, radius, *w ) {
+                assert!( close( &p.end(), &b ), "{:?} from {:?} ends at {:?}, expected {:?}", w, a, p.end(), b );
+                if !words_found.contains( w ) {
+                    words_found.push( *w );
+                }
+            }
+        }
+
+        let p = shortest_path( &a, &b, radius ).expect("no path");
+        let d = ( ( a.0[0] - b.0[0] ).powi(2) + ( a.0[1] - b.0[1] ).powi(2) ).sqrt();
+        assert!( p.length() >= d - 1e-4 );
+
+        let edges = p.edges( 1., Some(0.05) );
+        assert!( ( edges.iter().map(|x| x.1 ).sum::<f32>() - p.length() ).abs() < 1e-4 );
+        assert!( close( edges.last().unwrap().2.last().unwrap(), &b ) );
+    }
+
+    assert_eq!( words_found.len(), 6 );
+
+    //straight ahead
+    let p = shortest_path( &States3D([0.1, 0.1, 0.]), &States3D([0.6, 0.1, 0.]), 0.1 ).unwrap();
+    assert!( ( p.length() - 0.5 ).abs() < 1e-5 );
+}
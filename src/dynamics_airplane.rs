@@ -6,46 +6,139 @@
 //! y' = Vsin(theta)
 //! z' = v
 //! theta' = u
-//! u range: [-40,40] degrees
-//! z range: [-0.5,0.5]
-//! V = 1
+//! u range: [-40,40] degrees, default
+//! v range: [-0.5,0.5], default
+//! x, y, z range: [0,1], default
+//! V = 1, default
 
 use crate::states::*;
 use crate::control::*;
-use crate::planner_param::Param;
-use rand::Rng;
+use crate::planner_param::{Param,Integrator,StopCondition};
+use crate::goal::GoalRegion;
+use crate::cost;
+use crate::validity;
+use crate::system_model::SystemModel;
+use rand::{Rng,RngCore};
+
+use std::sync::Arc;
 
 extern crate mazth;
 use mazth::mat;
 
 use std::f32::consts::PI;
 
-///load model info to the caller
-pub fn load_model() -> Param<States4D, Control2D, States3D> { //state space 4D, control space 2D, config space 3D
-    Param {
-        states_init: States4D([0.5, 0.1, 0., 0.]), //default, override by prob_instances.rs file
-        states_goal: States4D([0.8, 0.1, 0.5, 0.]), //default, override by prob_instances.rs file
-        dynamics: dynamics,
-        stop_cond: stop_cond,
-        cs_metric: config_space_distance,
-        project_state_to_config: project_state_space_to_config_space,
-        param_sampler: sampler_parameter_space,
-        ss_sampler: sampler_state_space,
-        ss_metric: statespace_distance,
-        sim_delta: 0.05f32, //default, optinal override by prob_instances.rs file
-        iterations_bound: 300_000, //override via commandline and prob_instances.rs file
-
-        ///motion primitive transform functions
-        motion_primitive_xform: Some(motion_primitive_xform),
-
-        ///not actually used
-        motion_primitive_xform_inv: Some(motion_primitive_xform_inv),
-
-        ss_add: ss_add,
-        ss_mul: ss_mul,
+///parameters of the dubins airplane
+#[derive(Clone,Debug)]
+pub struct DubinsAirplane {
+    ///forward speed V
+    pub speed: f32,
+    ///maximum heading change of a sampled control over a propagation, in radians
+    pub steer_max: f32,
+    ///maximum magnitude of sampled vertical speed
+    pub climb_rate_max: f32,
+    ///workspace range of x, y and z used for state sampling and state validity
+    pub bounds: [(f32,f32);3],
+    ///per axis positional distance to goal under which goal is reached, used for the default goal region
+    pub goal_tolerance: f32,
+    pub integrator: Integrator,
+}
+
+impl Default for DubinsAirplane {
+    fn default() -> Self {
+        DubinsAirplane {
+            speed: 1.,
+            steer_max: 40./180.*PI,
+            climb_rate_max: 0.5,
+            bounds: [ (0.,1.), (0.,1.), (0.,1.) ], //assumes environment position coordinates are normalized to be within [0,1]
+            goal_tolerance: 0.04,
+            integrator: Integrator::Euler,
+        }
+    }
+}
+
+impl DubinsAirplane {
+    pub fn into_param( self ) -> Param<States4D, Control2D, States3D> { //state space 4D, control space 2D, config space 3D
+        Param {
+            states_init: States4D([0.5, 0.1, 0., 0.]), //default, override by scenario file
+            states_goal: States4D([0.8, 0.1, 0.5, 0.]), //default, override by scenario file
+            sim_delta: 0.05f32, //default, optinal override by scenario file
+            stop_condition: StopCondition::Iterations( 300_000 ), //override via commandline and scenario file
+            goal_region: GoalRegion::Box( vec![ self.goal_tolerance; 3 ] ), //default, optional override by scenario file
+            cost: Arc::new( cost::Duration ), //default, optional override by scenario file
+            constraints: vec![], //optional addition by scenario file
+            model: Arc::new( self ),
+        }
     }
 }
 
+impl SystemModel<States4D, Control2D, States3D> for DubinsAirplane {
+
+    fn dynamics( &self, states: States4D, control: Control2D, delta: f32 ) -> States4D {
+        match self.integrator {
+            Integrator::Euler => dynamics( states, control, delta, self.speed ),
+            Integrator::RungeKutta4 => dynamics_rk4( states, control, delta, self.speed ),
+        }
+    }
+
+    fn project_state_to_config( &self, states: States4D ) -> States3D {
+        project_state_space_to_config_space( states )
+    }
+
+    fn sample_control( &self, delta: f32, rng: &mut dyn RngCore ) -> Control2D {
+        sampler_parameter_space( delta, self.steer_max, self.climb_rate_max, rng )
+    }
+
+    fn sample_state( &self, rng: &mut dyn RngCore ) -> States4D {
+        sampler_state_space( &self.bounds, rng )
+    }
+
+    fn sample_goal_region( &self, goal: States4D, radius: f32, ignore_heading: bool, rng: &mut dyn RngCore ) -> States4D {
+        sampler_goal_region( goal, radius, ignore_heading, rng )
+    }
+
+    fn ss_metric( &self, a: States4D, b: States4D ) -> f32 {
+        statespace_distance( a, b )
+    }
+
+    fn cs_metric( &self, a: States3D, b: States3D ) -> f32 {
+        config_space_distance( a, b )
+    }
+
+    fn position_dims( &self ) -> usize {
+        3
+    }
+
+    fn heading_dim( &self ) -> Option<usize> {
+        Some( 3 )
+    }
+
+    fn state_valid( &self, state: &States4D ) -> bool {
+        validity::within_bounds( &state.0[..3], &self.bounds )
+    }
+
+    fn ss_add( &self, a: States4D, b: States4D ) -> States4D {
+        a + b
+    }
+
+    fn ss_mul( &self, a: States4D, b: f32 ) -> States4D {
+        a * b
+    }
+
+    fn motion_primitive_xform( &self, q_start: States4D, q_end: States4D ) -> Option<States4D> {
+        Some( motion_primitive_xform( q_start, q_end ) )
+    }
+
+    ///not actually used
+    fn motion_primitive_xform_inv( &self, q_start: States4D, qq_end: States4D ) -> Option<States4D> {
+        Some( motion_primitive_xform_inv( q_start, qq_end ) )
+    }
+}
+
+///load model info to the caller using default model parameters
+pub fn load_model( integrator: Integrator ) -> Param<States4D, Control2D, States3D> {
+    DubinsAirplane { integrator: integrator, ..DubinsAirplane::default() }.into_param()
+}
+
 use std::ops::{Add,Mul};
 
 impl Add for States4D {
@@ -70,8 +163,6 @@ impl Mul<f32> for States4D {
     
     fn mul( self, other: f32 ) -> States4D {
         
-        use std::f32::consts::PI;
-        
         States4D(
             [ self.0[0] * other,
               self.0[1] * other,
@@ -81,22 +172,12 @@ impl Mul<f32> for States4D {
     }
 }
 
-fn ss_add( a: States4D, b: States4D ) -> States4D {
-    a + b
-}
-
-fn ss_mul( a: States4D, b: f32 ) -> States4D {
-    a * b
-}
-
 ///calculate change
-fn dyn_change( states: States4D, control: Control2D, delta: f32 )-> States4D {
+fn dyn_change( states: States4D, control: Control2D, _delta: f32, speed: f32 )-> States4D {
     
-    use std::f32::consts::PI;
-
     //control = [u,v]
-    let x_dot = states.0[3].cos();
-    let y_dot = states.0[3].sin();
+    let x_dot = speed * states.0[3].cos();
+    let y_dot = speed * states.0[3].sin();
     let theta_dot = control.0[0];
     let z_dot = control.0[1];
     States4D( [ x_dot,
@@ -106,35 +187,35 @@ fn dyn_change( states: States4D, control: Control2D, delta: f32 )-> States4D {
 }
 
 ///4th order runge-kutta
-fn runge_kutta_4( states: States4D, control: Control2D, delta: f32 )-> States4D {
+fn runge_kutta_4( states: States4D, control: Control2D, delta: f32, speed: f32 )-> States4D {
 
     use std::f32::consts::PI;
     
     let k1 = {
         
-        let mut temp = dyn_change( states.clone(), control.clone(), 0.  );
+        let temp = dyn_change( states.clone(), control.clone(), 0., speed );
         temp * delta
     };
     
     let k2 = {
         
-        let mut k1_copy = k1.clone() * (1./2.);
+        let k1_copy = k1.clone() * (1./2.);
         
-        let mut temp = dyn_change( states.clone() + k1_copy, control.clone(), delta/2. );
+        let temp = dyn_change( states.clone() + k1_copy, control.clone(), delta/2., speed );
         temp * delta
     };
 
     let k3 = {
         
-        let mut k2_copy = k2.clone() * (1./2.);
+        let k2_copy = k2.clone() * (1./2.);
 
-        let mut temp = dyn_change( states.clone() + k2_copy, control.clone(), delta/2. );
+        let temp = dyn_change( states.clone() + k2_copy, control.clone(), delta/2., speed );
         temp * delta
     };
 
     let k4 = {
         
-        let mut temp = dyn_change( states.clone() + k3, control.clone(), delta );
+        let temp = dyn_change( states.clone() + k3, control.clone(), delta, speed );
         temp * delta
     };
     
@@ -144,25 +225,23 @@ fn runge_kutta_4( states: States4D, control: Control2D, delta: f32 )-> States4D
                 ( states.0[2] + ( 1./6.*( k1.0[2] + 2.*k2.0[2] + 2.*k3.0[2] + k4.0[2] ) ) + 2.*PI ) % ( 2.*PI ) ] )
 }
 
-pub fn dynamics( states: States4D, control: Control2D, delta: f32 )-> States4D {
+///1st order Euler propagation
+pub fn dynamics( states: States4D, control: Control2D, delta: f32, speed: f32 )-> States4D {
     
     use std::f32::consts::PI;
 
-    #[cfg(not(feature="runge_kutta"))]
-    {
-        // 1st order
-        let temp = dyn_change( states, control, delta ) * delta;
-        
-        States4D( [ states.0[0] + temp.0[0],
-                    states.0[1] + temp.0[1],
-                    states.0[2] + temp.0[2],
-                    ( (states.0[3] + temp.0[3] ) + 2.*PI ) % (2.*PI) ] )
-    }
-    #[cfg(feature="runge_kutta")]
-    {
-        //4th order
-        runge_kutta_4( states, control, delta )
-    }
+    // 1st order
+    let temp = dyn_change( states, control, delta, speed ) * delta;
+    
+    States4D( [ states.0[0] + temp.0[0],
+                states.0[1] + temp.0[1],
+                states.0[2] + temp.0[2],
+                ( (states.0[3] + temp.0[3] ) + 2.*PI ) % (2.*PI) ] )
+}
+
+///4th order runge-kutta propagation
+pub fn dynamics_rk4( states: States4D, control: Control2D, delta: f32, speed: f32 )-> States4D {
+    runge_kutta_4( states, control, delta, speed )
 }
 
 ///project x and y
@@ -170,49 +249,48 @@ pub fn project_state_space_to_config_space( states: States4D ) -> States3D {
     States3D( [states.0[0], states.0[1], states.0[2]] )
 }
 
-pub fn sampler_parameter_space( delta: f32 ) -> Control2D {
+///heading change within [-steer_max,steer_max] over ``delta`` duration, vertical speed within [-climb_rate_max,climb_rate_max]
+pub fn sampler_parameter_space( delta: f32, steer_max: f32, climb_rate_max: f32, rng: &mut dyn RngCore ) -> Control2D {
     
-    use std::f32::consts::PI;
-
     use rand::prelude::*;
     use rand::distributions::Standard;
 
-    let val: f32 = SmallRng::from_entropy().sample(Standard);
-
-    let mut rng = rand::thread_rng();
+    let val: f32 = rng.sample(Standard);
     
-    Control2D( [ ( 2. * (val-0.5) * 40./180.* PI )/delta, rng.gen_range(-0.5, 0.5) ] )
+    Control2D( [ ( 2. * (val-0.5) * steer_max )/delta, rng.gen_range(-climb_rate_max, climb_rate_max) ] )
 }
 
-pub fn sampler_state_space() -> States4D {
+pub fn sampler_state_space( bounds: &[(f32,f32);3], rng: &mut dyn RngCore ) -> States4D {
     
     use std::f32::consts::PI;
     
-    let mut rng = rand::thread_rng();
-
-    States4D( [ rng.gen_range(0., 1.), //[0,1] range for x
-                rng.gen_range(0., 1.), //[0,1] range for y
-                rng.gen_range(0., 1.), //[0,1] range for z
+    States4D( [ rng.gen_range(bounds[0].0, bounds[0].1), //x
+                rng.gen_range(bounds[1].0, bounds[1].1), //y
+                rng.gen_range(bounds[2].0, bounds[2].1), //z
                 rng.gen_range(0., 2. * PI) ] ) //[0,2*PI] for theta
 }
 
-///aritrary goal condition
-pub fn stop_cond( system_states: States4D, states_config: States3D, states_goal: States4D )-> bool {
-
-    let pairs = system_states.0.iter()
-        .zip( states_goal.0.iter() );
-
-    let pairs_copy = pairs.clone();
+///sampling in a ball of ``radius`` around (x,y,z) of ``goal``, uniform theta if ``ignore_heading`` is set
+pub fn sampler_goal_region( goal: States4D, radius: f32, ignore_heading: bool, rng: &mut dyn RngCore ) -> States4D {
     
-    let pos_good = pairs.take(3).all( |x| ((x.0)-(x.1)).abs() < 0.04 );
+    use std::f32::consts::PI;
 
-    pos_good
+    //rejection sampling in unit cube
+    let d = loop {
+        let d = [ rng.gen_range(-1f32, 1.), rng.gen_range(-1f32, 1.), rng.gen_range(-1f32, 1.) ];
+        if d.iter().map(|x| x*x ).sum::<f32>() <= 1. {
+            break d;
+        }
+    };
+    
+    States4D( [ goal.0[0] + radius * d[0],
+                goal.0[1] + radius * d[1],
+                goal.0[2] + radius * d[2],
+                if ignore_heading { rng.gen_range(0., 2. * PI) } else { goal.0[3] } ] )
 }
 
 ///estimate of closeness to goal condition in configuration space
 pub fn config_space_distance( states_config: States3D, states_config_goal: States3D )-> f32 {
-
-    use std::f32::consts::PI;
     
     let va = states_config.get_vals();
     let vb = states_config_goal.get_vals();
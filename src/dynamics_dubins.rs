@@ -5,13 +5,21 @@
 //! x' = Vcos(theta)
 //! y' = Vsin(theta)
 //! theta' = u
-//! u range: [-40,40] degrees
-//! V = 1
+//! u range: [-40,40] degrees, default
+//! V = 1, default
 
 use crate::states::*;
 use crate::control::*;
-use crate::planner_param::Param;
-use rand::Rng;
+use crate::planner_param::{Param,Integrator,StopCondition};
+use crate::goal::GoalRegion;
+use crate::cost;
+use crate::validity;
+use crate::system_model::SystemModel;
+use crate::dubins;
+use crate::collision;
+use rand::{Rng,RngCore};
+
+use std::sync::Arc;
 
 extern crate mazth;
 use mazth::mat;
@@ -40,8 +48,6 @@ impl Mul<f32> for States3D {
     
     fn mul( self, other: f32 ) -> States3D {
         
-        use std::f32::consts::PI;
-        
         States3D(
             [ self.0[0] * other,
               self.0[1] * other,
@@ -50,47 +56,146 @@ impl Mul<f32> for States3D {
     }
 }
 
-fn ss_add( a: States3D, b: States3D ) -> States3D {
-    a + b
+///parameters of the dubins car
+#[derive(Clone,Debug)]
+pub struct DubinsCar {
+    ///forward speed V
+    pub speed: f32,
+    ///maximum heading change of a sampled control over a propagation, in radians
+    pub steer_max: f32,
+    ///workspace range of x and y used for state sampling and state validity
+    pub bounds: [(f32,f32);2],
+    ///positional distance to goal under which goal is reached, used for the default goal region
+    pub goal_tolerance: f32,
+    pub integrator: Integrator,
+    ///minimum turning radius of dubins curves used for steering
+    pub turning_radius: f32,
+    ///maximum heading rate of steering controls, steering is unavailable if speed / turning_radius exceeds it
+    pub turn_rate_max: f32,
+    ///use length of the shortest dubins curve as state space metric instead of weighted position and heading difference
+    pub dubins_metric: bool,
 }
 
-fn ss_mul( a: States3D, b: f32 ) -> States3D {
-    a * b
+impl Default for DubinsCar {
+    fn default() -> Self {
+        DubinsCar {
+            speed: 1.,
+            steer_max: 40./180.*PI,
+            bounds: [ (0.,1.), (0.,1.) ], //assumes environment position coordinates are normalized to be within [0,1]
+            goal_tolerance: 0.01,
+            integrator: Integrator::Euler,
+            turning_radius: 0.1,
+            turn_rate_max: 40./180.*PI / 0.05, //steer_max over the default sim_delta
+            dubins_metric: false,
+        }
+    }
 }
 
-///load model info to the caller
-pub fn load_model() -> Param<States3D, Control1D, States3D> { //state space and configuration space both are 3 dimensional in this case
-    Param {
-        states_init: States3D([0.5, 0.1, 0.]), //default, override by prob_instances.rs file
-        states_goal: States3D([0.8,0.1,0.]), //default, override by prob_instances.rs file
-        dynamics: dynamics,
-        stop_cond: stop_cond,
-        cs_metric: config_space_distance,
-        project_state_to_config: project_state_space_to_config_space,
-        param_sampler: sampler_parameter_space,
-        ss_sampler: sampler_state_space,
-        ss_metric: statespace_distance,
-        sim_delta: 0.05f32, //default, optinal override by prob_instances.rs file
-        iterations_bound: 300_000, //override via commandline and prob_instances.rs file
-
-        ///motion primitive transform functions
-        motion_primitive_xform: Some(motion_primitive_xform),
-
-        ///not actually used
-        motion_primitive_xform_inv: Some(motion_primitive_xform_inv),
-
-        ss_add: ss_add,
-        ss_mul: ss_mul,
+impl DubinsCar {
+    pub fn into_param( self ) -> Param<States3D, Control1D, States3D> { //state space and configuration space both are 3 dimensional in this case
+        Param {
+            states_init: States3D([0.5, 0.1, 0.]), //default, override by scenario file
+            states_goal: States3D([0.8,0.1,0.]), //default, override by scenario file
+            sim_delta: 0.05f32, //default, optinal override by scenario file
+            stop_condition: StopCondition::Iterations( 300_000 ), //override via commandline and scenario file
+            goal_region: GoalRegion::Tolerance { position: self.goal_tolerance, heading: None }, //default, optional override by scenario file
+            cost: Arc::new( cost::Duration ), //default, optional override by scenario file
+            constraints: vec![], //optional addition by scenario file
+            model: Arc::new( self ),
+        }
+    }
+}
+
+impl SystemModel<States3D, Control1D, States3D> for DubinsCar {
+
+    fn dynamics( &self, states: States3D, control: Control1D, delta: f32 ) -> States3D {
+        match self.integrator {
+            Integrator::Euler => dynamics( states, control, delta, self.speed ),
+            Integrator::RungeKutta4 => dynamics_rk4( states, control, delta, self.speed ),
+        }
+    }
+
+    fn project_state_to_config( &self, states: States3D ) -> States3D {
+        project_state_space_to_config_space( states )
+    }
+
+    fn sample_control( &self, delta: f32, rng: &mut dyn RngCore ) -> Control1D {
+        sampler_parameter_space( delta, self.steer_max, rng )
+    }
+
+    fn sample_state( &self, rng: &mut dyn RngCore ) -> States3D {
+        sampler_state_space( &self.bounds, rng )
+    }
+
+    fn sample_goal_region( &self, goal: States3D, radius: f32, ignore_heading: bool, rng: &mut dyn RngCore ) -> States3D {
+        sampler_goal_region( goal, radius, ignore_heading, rng )
+    }
+
+    fn ss_metric( &self, a: States3D, b: States3D ) -> f32 {
+        if self.dubins_metric {
+            dubins::shortest_path( &a, &b, self.turning_radius ).map_or( std::f32::INFINITY, |x| x.length() )
+        } else {
+            statespace_distance( a, b )
+        }
+    }
+
+    fn cs_metric( &self, a: States3D, b: States3D ) -> f32 {
+        config_space_distance( a, b )
+    }
+
+    fn position_dims( &self ) -> usize {
+        2
+    }
+
+    fn heading_dim( &self ) -> Option<usize> {
+        Some( 2 )
+    }
+
+    fn state_valid( &self, state: &States3D ) -> bool {
+        validity::within_bounds( &state.0[..2], &self.bounds )
+    }
+
+    fn ss_add( &self, a: States3D, b: States3D ) -> States3D {
+        a + b
+    }
+
+    fn ss_mul( &self, a: States3D, b: f32 ) -> States3D {
+        a * b
+    }
+
+    ///controls of the shortest dubins curve with turning_radius, heading rate control is +/- speed / turning_radius on turns
+    fn steer( &self, from: &States3D, to: &States3D, resolution: Option<f32> ) -> Option<Vec<(Control1D,f32,Vec<States3D>)>> {
+
+        if !( self.speed / self.turning_radius <= self.turn_rate_max ) {
+            return None
+        }
+        
+        let path = dubins::shortest_path( from, to, self.turning_radius )?;
+        let controls = path.edges( self.speed, None ).into_iter().map(|(u,t,_)| (u,t) ).collect();
+        
+        Some( collision::integrate_edges( self, from.clone(), controls, resolution ) )
+    }
+
+    fn motion_primitive_xform( &self, q_start: States3D, q_end: States3D ) -> Option<States3D> {
+        Some( motion_primitive_xform( q_start, q_end ) )
+    }
+
+    ///not actually used
+    fn motion_primitive_xform_inv( &self, q_start: States3D, qq_end: States3D ) -> Option<States3D> {
+        Some( motion_primitive_xform_inv( q_start, qq_end ) )
     }
 }
 
+///load model info to the caller using default model parameters
+pub fn load_model( integrator: Integrator ) -> Param<States3D, Control1D, States3D> {
+    DubinsCar { integrator: integrator, ..DubinsCar::default() }.into_param()
+}
+
 ///calculate change
-fn dyn_change( states: States3D, control: Control1D, delta: f32 )-> States3D {
-    
-    use std::f32::consts::PI;
+fn dyn_change( states: States3D, control: Control1D, _delta: f32, speed: f32 )-> States3D {
     
-    let x_dot = states.0[2].cos();
-    let y_dot = states.0[2].sin();
+    let x_dot = speed * states.0[2].cos();
+    let y_dot = speed * states.0[2].sin();
     let theta_dot = control.0[0];
     States3D( [ x_dot,
                 y_dot,
@@ -98,35 +203,35 @@ fn dyn_change( states: States3D, control: Control1D, delta: f32 )-> States3D {
 }
 
 ///4th order runge-kutta
-fn runge_kutta_4( states: States3D, control: Control1D, delta: f32 )-> States3D {
+fn runge_kutta_4( states: States3D, control: Control1D, delta: f32, speed: f32 )-> States3D {
 
     use std::f32::consts::PI;
     
     let k1 = {
         
-        let mut temp = dyn_change( states.clone(), control.clone(), 0.  );
+        let temp = dyn_change( states.clone(), control.clone(), 0., speed );
         temp * delta
     };
     
     let k2 = {
         
-        let mut k1_copy = k1.clone() * (1./2.);
+        let k1_copy = k1.clone() * (1./2.);
         
-        let mut temp = dyn_change( states.clone() + k1_copy, control.clone(), delta/2. );
+        let temp = dyn_change( states.clone() + k1_copy, control.clone(), delta/2., speed );
         temp * delta
     };
 
     let k3 = {
         
-        let mut k2_copy = k2.clone() * (1./2.);
+        let k2_copy = k2.clone() * (1./2.);
 
-        let mut temp = dyn_change( states.clone() + k2_copy, control.clone(), delta/2. );
+        let temp = dyn_change( states.clone() + k2_copy, control.clone(), delta/2., speed );
         temp * delta
     };
 
     let k4 = {
         
-        let mut temp = dyn_change( states.clone() + k3, control.clone(), delta );
+        let temp = dyn_change( states.clone() + k3, control.clone(), delta, speed );
         temp * delta
     };
     
@@ -135,24 +240,22 @@ fn runge_kutta_4( states: States3D, control: Control1D, delta: f32 )-> States3D
                 ( states.0[2] + ( 1./6.*( k1.0[2] + 2.*k2.0[2] + 2.*k3.0[2] + k4.0[2] ) ) + 2.*PI ) % ( 2.*PI ) ] )
 }
 
-pub fn dynamics( states: States3D, control: Control1D, delta: f32 )-> States3D {
+///1st order Euler propagation
+pub fn dynamics( states: States3D, control: Control1D, delta: f32, speed: f32 )-> States3D {
     
     use std::f32::consts::PI;
 
-    #[cfg(not(feature="runge_kutta"))]
-    {
-        // 1st order
-        let temp = dyn_change( states, control, delta ) * delta;
-        
-        States3D( [ states.0[0] + temp.0[0],
-                    states.0[1] + temp.0[1],
-                    ( (states.0[2] + temp.0[2] ) + 2.*PI ) % (2.*PI) ] )
-    }
-    #[cfg(feature="runge_kutta")]
-    {
-        //4th order
-        runge_kutta_4( states, control, delta )
-    }
+    // 1st order
+    let temp = dyn_change( states, control, delta, speed ) * delta;
+    
+    States3D( [ states.0[0] + temp.0[0],
+                states.0[1] + temp.0[1],
+                ( (states.0[2] + temp.0[2] ) + 2.*PI ) % (2.*PI) ] )
+}
+
+///4th order runge-kutta propagation
+pub fn dynamics_rk4( states: States3D, control: Control1D, delta: f32, speed: f32 )-> States3D {
+    runge_kutta_4( states, control, delta, speed )
 }
 
 ///project x and y
@@ -160,43 +263,42 @@ pub fn project_state_space_to_config_space( states: States3D ) -> States3D {
     States3D( [states.0[0], states.0[1], 0.] )
 }
 
-pub fn sampler_parameter_space( delta: f32 ) -> Control1D {
+///heading change within [-steer_max,steer_max] over ``delta`` duration
+pub fn sampler_parameter_space( delta: f32, steer_max: f32, rng: &mut dyn RngCore ) -> Control1D {
     
-    use std::f32::consts::PI;
-
     use rand::prelude::*;
     use rand::distributions::Standard;
 
-    let val: f32 = SmallRng::from_entropy().sample(Standard);
+    let val: f32 = rng.sample(Standard);
     
-    Control1D( [ ( 2. * (val-0.5) * 40./180.* PI )/delta ] )
+    Control1D( [ ( 2. * (val-0.5) * steer_max )/delta ] )
 }
 
-pub fn sampler_state_space() -> States3D {
+pub fn sampler_state_space( bounds: &[(f32,f32);2], rng: &mut dyn RngCore ) -> States3D {
     
     use std::f32::consts::PI;
     
-    let mut rng = rand::thread_rng();
-
-    States3D( [ rng.gen_range(0., 1.), //[0,1] range for x
-                rng.gen_range(0., 1.), //[0,1] range for y
+    States3D( [ rng.gen_range(bounds[0].0, bounds[0].1), //x
+                rng.gen_range(bounds[1].0, bounds[1].1), //y
                 rng.gen_range(0., 2. * PI) ] ) //[0,2*PI] for theta
 }
 
-///aritrary goal condition
-pub fn stop_cond( mut system_states: States3D, mut states_config: States3D, mut states_goal: States3D )-> bool {
+///sampling in a disc of ``radius`` around (x,y) of ``goal``, uniform theta if ``ignore_heading`` is set
+pub fn sampler_goal_region( goal: States3D, radius: f32, ignore_heading: bool, rng: &mut dyn RngCore ) -> States3D {
+    
+    use std::f32::consts::PI;
 
-    system_states.0[2] = 0.;
-    states_goal.0[2] = 0.;
+    let r = radius * rng.gen_range(0f32, 1.).sqrt();
+    let a = rng.gen_range(0., 2. * PI);
     
-    statespace_distance( system_states, states_goal ) < 0.01
+    States3D( [ goal.0[0] + r * a.cos(),
+                goal.0[1] + r * a.sin(),
+                if ignore_heading { rng.gen_range(0., 2. * PI) } else { goal.0[2] } ] )
 }
 
 ///estimate of closeness to goal condition in configuration space
 pub fn config_space_distance( states_config: States3D, states_config_goal: States3D )-> f32 {
     
-    use std::f32::consts::PI;
-    
     let va = states_config.get_vals();
     let vb = states_config_goal.get_vals();
 
@@ -230,7 +332,7 @@ pub fn statespace_distance( a: States3D, b: States3D ) -> f32 {
     let angle_1 = ((va[2] + 2.*PI)%(2.*PI))/(2.*PI);
     let angle_2 = ((vb[2] + 2.*PI)%(2.*PI))/(2.*PI);
     
-    ret += ((angle_1-angle_2)*(angle_1-angle_2));
+    ret += (angle_1-angle_2)*(angle_1-angle_2);
 
     ret.sqrt()
 }
@@ -384,3 +486,30 @@ fn test_motion_primitive_xform_inv(){
     assert!( q_end.0[1] > 5. - eps && q_end.0[1] < 5. + eps );
     assert!( q_end.0[2] > 0.*PI - eps && q_end.0[2] < 0.*PI + eps );
 }
+
+#[test]
+fn test_dubins_car_speed(){
+
+    let car = DubinsCar { speed: 2., ..DubinsCar::default() };
+    
+    let q = car.dynamics( States3D([0., 0., 0.]), Control1D([0.]), 0.5 );
+
+    let eps = 1e-6;
+    assert!( q.0[0] > 1. - eps && q.0[0] < 1. + eps );
+    assert!( q.0[1] > 0. - eps && q.0[1] < 0. + eps );
+}
+
+#[test]
+fn test_sampler_goal_region(){
+
+    use rand::SeedableRng;
+    
+    let mut rng = rand::rngs::StdRng::seed_from_u64( 0 );
+    let goal = States3D([0.5, 0.5, 1.]);
+    
+    for _ in 0..100 {
+        let q = sampler_goal_region( goal, 0.1, false, &mut rng );
+        assert!( config_space_distance( States3D([q.0[0], q.0[1], 0.]), States3D([0.5, 0.5, 0.]) ) <= 0.1 + 1e-6 );
+        assert_eq!( q.0[2], 1. );
+    }
+}
@@ -0,0 +1,242 @@
+//! dynamics, constraints for reeds-shepp car, a car that drives forward and backward
+//!
+//! states := [ x,y,theta ]
+//! control := [ v, u ]
+//! x' = v cos(theta)
+//! y' = v sin(theta)
+//! theta' = u
+//! v: +V or -V, u range: [-40,40] degrees, default
+//! V = 1, default
+
+use crate::states::*;
+use crate::control::*;
+use crate::planner_param::{Param,Integrator,StopCondition};
+use crate::goal::GoalRegion;
+use crate::cost;
+use crate::validity;
+use crate::system_model::SystemModel;
+use crate::reeds_shepp;
+use crate::collision;
+use crate::dynamics_dubins::{project_state_space_to_config_space,
+                             sampler_state_space,
+                             sampler_goal_region,
+                             config_space_distance,
+                             statespace_distance};
+use rand::{Rng,RngCore};
+
+use std::sync::Arc;
+
+use std::f32::consts::PI;
+
+///parameters of the reeds-shepp car
+#[derive(Clone,Debug)]
+pub struct ReedsSheppCar {
+    ///magnitude of forward and backward speed V
+    pub speed: f32,
+    ///maximum heading change of a sampled control over a propagation, in radians
+    pub steer_max: f32,
+    ///workspace range of x and y used for state sampling and state validity
+    pub bounds: [(f32,f32);2],
+    ///positional distance to goal under which goal is reached, used for the default goal region
+    pub goal_tolerance: f32,
+    pub integrator: Integrator,
+    ///minimum turning radius of reeds-shepp curves used for steering and the metric
+    pub turning_radius: f32,
+    ///maximum heading rate of steering controls, steering is unavailable if speed / turning_radius exceeds it
+    pub turn_rate_max: f32,
+    ///use length of the shortest reeds-shepp curve as state space metric instead of weighted position and heading difference,
+    ///so that states reachable by reversing through a cusp are close
+    pub reeds_shepp_metric: bool,
+}
+
+impl Default for ReedsSheppCar {
+    fn default() -> Self {
+        ReedsSheppCar {
+            speed: 1.,
+            steer_max: 40./180.*PI,
+            bounds: [ (0.,1.), (0.,1.) ], //assumes environment position coordinates are normalized to be within [0,1]
+            goal_tolerance: 0.01,
+            integrator: Integrator::Euler,
+            turning_radius: 0.1,
+            turn_rate_max: 40./180.*PI / 0.05, //steer_max over the default sim_delta
+            reeds_shepp_metric: true,
+        }
+    }
+}
+
+impl ReedsSheppCar {
+    pub fn into_param( self ) -> Param<States3D, Control2D, States3D> { //state space and configuration space both are 3 dimensional in this case
+        Param {
+            states_init: States3D([0.5, 0.1, 0.]), //default, override by scenario file
+            states_goal: States3D([0.8,0.1,0.]), //default, override by scenario file
+            sim_delta: 0.05f32, //default, optinal override by scenario file
+            stop_condition: StopCondition::Iterations( 300_000 ), //override via commandline and scenario file
+            goal_region: GoalRegion::Tolerance { position: self.goal_tolerance, heading: None }, //default, optional override by scenario file
+            cost: Arc::new( cost::Duration ), //default, optional override by scenario file
+            constraints: vec![], //optional addition by scenario file
+            model: Arc::new( self ),
+        }
+    }
+}
+
+impl SystemModel<States3D, Control2D, States3D> for ReedsSheppCar {
+
+    fn dynamics( &self, states: States3D, control: Control2D, delta: f32 ) -> States3D {
+        match self.integrator {
+            Integrator::Euler => dynamics( states, control, delta ),
+            Integrator::RungeKutta4 => dynamics_rk4( states, control, delta ),
+        }
+    }
+
+    fn project_state_to_config( &self, states: States3D ) -> States3D {
+        project_state_space_to_config_space( states )
+    }
+
+    fn sample_control( &self, delta: f32, rng: &mut dyn RngCore ) -> Control2D {
+        sampler_parameter_space( delta, self.speed, self.steer_max, rng )
+    }
+
+    fn sample_state( &self, rng: &mut dyn RngCore ) -> States3D {
+        sampler_state_space( &self.bounds, rng )
+    }
+
+    fn sample_goal_region( &self, goal: States3D, radius: f32, ignore_heading: bool, rng: &mut dyn RngCore ) -> States3D {
+        sampler_goal_region( goal, radius, ignore_heading, rng )
+    }
+
+    fn ss_metric( &self, a: States3D, b: States3D ) -> f32 {
+        if self.reeds_shepp_metric {
+            match reeds_shepp::shortest_path( &a, &b, self.turning_radius ) {
+                Some(x) => { x.length() },
+                _ => { statespace_distance( a, b ) },
+            }
+        } else {
+            statespace_distance( a, b )
+        }
+    }
+
+    fn cs_metric( &self, a: States3D, b: States3D ) -> f32 {
+        config_space_distance( a, b )
+    }
+
+    fn position_dims( &self ) -> usize {
+        2
+    }
+
+    fn heading_dim( &self ) -> Option<usize> {
+        Some( 2 )
+    }
+
+    fn state_valid( &self, state: &States3D ) -> bool {
+        validity::within_bounds( &state.0[..2], &self.bounds )
+    }
+
+    fn ss_add( &self, a: States3D, b: States3D ) -> States3D {
+        a + b
+    }
+
+    fn ss_mul( &self, a: States3D, b: f32 ) -> States3D {
+        a * b
+    }
+
+    ///controls of the shortest reeds-shepp curve with turning_radius, signed speed is +/- speed and heading rate is +/- speed / turning_radius on turns
+    fn steer( &self, from: &States3D, to: &States3D, resolution: Option<f32> ) -> Option<Vec<(Control2D,f32,Vec<States3D>)>> {
+
+        if !( self.speed / self.turning_radius <= self.turn_rate_max ) {
+            return None
+        }
+        
+        let path = reeds_shepp::shortest_path( from, to, self.turning_radius )?;
+        let controls = path.edges( self.speed, None ).into_iter().map(|(u,t,_)| (u,t) ).collect();
+        
+        Some( collision::integrate_edges( self, from.clone(), controls, resolution ) )
+    }
+}
+
+///load model info to the caller using default model parameters
+pub fn load_model( integrator: Integrator ) -> Param<States3D, Control2D, States3D> {
+    ReedsSheppCar { integrator: integrator, ..ReedsSheppCar::default() }.into_param()
+}
+
+///calculate change
+fn dyn_change( states: States3D, control: Control2D ) -> States3D {
+    States3D( [ control.0[0] * states.0[2].cos(),
+                control.0[0] * states.0[2].sin(),
+                control.0[1] ] )
+}
+
+///1st order Euler propagation
+pub fn dynamics( states: States3D, control: Control2D, delta: f32 ) -> States3D {
+    
+    let temp = dyn_change( states, control ) * delta;
+    
+    States3D( [ states.0[0] + temp.0[0],
+                states.0[1] + temp.0[1],
+                ( ( states.0[2] + temp.0[2] ) + 2.*PI ) % ( 2.*PI ) ] )
+}
+
+///4th order runge-kutta propagation
+pub fn dynamics_rk4( states: States3D, control: Control2D, delta: f32 ) -> States3D {
+
+    let k1 = dyn_change( states, control ) * delta;
+    let k2 = dyn_change( states + k1 * 0.5, control ) * delta;
+    let k3 = dyn_change( states + k2 * 0.5, control ) * delta;
+    let k4 = dyn_change( states + k3, control ) * delta;
+
+    States3D( [ states.0[0] + 1./6.*( k1.0[0] + 2.*k2.0[0] + 2.*k3.0[0] + k4.0[0] ),
+                states.0[1] + 1./6.*( k1.0[1] + 2.*k2.0[1] + 2.*k3.0[1] + k4.0[1] ),
+                ( states.0[2] + ( 1./6.*( k1.0[2] + 2.*k2.0[2] + 2.*k3.0[2] + k4.0[2] ) ) + 2.*PI ) % ( 2.*PI ) ] )
+}
+
+///forward or backward at ``speed`` with equal probability, heading change within [-steer_max,steer_max] over ``delta`` duration
+pub fn sampler_parameter_space( delta: f32, speed: f32, steer_max: f32, rng: &mut dyn RngCore ) -> Control2D {
+
+    let v = if rng.gen_bool( 0.5 ) { speed } else { -speed };
+    let val: f32 = rng.gen_range( -1., 1. );
+
+    Control2D( [ v, val * steer_max / delta ] )
+}
+
+#[test]
+fn test_reeds_shepp_car(){
+
+    use rand::SeedableRng;
+
+    let car = ReedsSheppCar { speed: 2., ..ReedsSheppCar::default() };
+
+    //reversing
+    let q = car.dynamics( States3D([1., 0., 0.]), Control2D([-2., 0.]), 0.5 );
+
+    let eps = 1e-6;
+    assert!( q.0[0] > 0. - eps && q.0[0] < 0. + eps );
+    assert!( q.0[1] > 0. - eps && q.0[1] < 0. + eps );
+
+    //heading stays within [0,2PI) turning either way
+    let q = car.dynamics( States3D([0.5, 0.5, 0.1]), Control2D([1., -1.]), 0.5 );
+    assert!( ( q.0[2] - ( 2.*PI - 0.4 ) ).abs() < 1e-5 );
+    let q = car.dynamics( States3D([0.5, 0.5, 6.2]), Control2D([1., 1.]), 0.5 );
+    assert!( ( q.0[2] - ( 6.7 - 2.*PI ) ).abs() < 1e-5 );
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64( 0 );
+    let controls = (0..100).map(|_| car.sample_control( 0.1, &mut rng ) ).collect::<Vec<_>>();
+    assert!( controls.iter().any(|x| x.0[0] < 0. ) && controls.iter().any(|x| x.0[0] > 0. ) );
+    assert!( controls.iter().all(|x| x.0[0].abs() == 2. && x.0[1].abs() <= car.steer_max / 0.1 ) );
+
+    //state straight behind is close under the reeds-shepp metric, as it is reached by reversing
+    let a = States3D([0.5, 0.5, 0.]);
+    assert!( ( car.ss_metric( a, States3D([0.3, 0.5, 0.]) ) - 0.2 ).abs() < 1e-5 );
+
+    //turns at speed 2 with the default turning radius exceed the heading rate bound
+    let b = States3D([0.3, 0.6, 1.]);
+    assert!( car.steer( &a, &b, Some(0.001) ).is_none() );
+
+    //steered edges follow the dynamics
+    let car = ReedsSheppCar { turn_rate_max: 20., ..car };
+    let edges = car.steer( &a, &b, Some(0.001) ).unwrap();
+    let mut s = a;
+    for (u, t, sub) in edges.iter() {
+        s = crate::collision::integrate( &car, s, *u, *t, Some(0.001) ).pop().unwrap();
+        assert_eq!( sub.last().unwrap().0, s.0 );
+    }
+    assert!( ( s.0[0] - b.0[0] ).abs() < 1e-2 && ( s.0[1] - b.0[1] ).abs() < 1e-2 );
+}
@@ -0,0 +1,61 @@
+//! Errors of map, obstacle, scenario and config loading and of planner construction
+
+use std::fmt;
+
+#[derive(Debug)]
+pub enum PlannerError {
+    ///file cannot be opened, read or written
+    Io { path: String, source: std::io::Error },
+    ///malformed text file, ``line`` starts from 1
+    Parse { path: String, line: usize, msg: String },
+    ///malformed json file, line and column are available from ``source``
+    Json { path: String, source: serde_json::Error },
+    ///no scenario of given name for the selected model
+    InstanceNotFound(String),
+    ///no model of given name in model_registry
+    ModelNotFound(String),
+    ///planner cannot be constructed with given parameters
+    InvalidParam(String),
+}
+
+pub type Result<T> = std::result::Result<T,PlannerError>;
+
+impl PlannerError {
+
+    pub fn io( path: &str, source: std::io::Error ) -> PlannerError {
+        PlannerError::Io { path: path.to_string(), source: source }
+    }
+
+    pub fn parse<S>( path: &str, line: usize, msg: S ) -> PlannerError where S: Into<String> {
+        PlannerError::Parse { path: path.to_string(), line: line, msg: msg.into() }
+    }
+
+    pub fn json( path: &str, source: serde_json::Error ) -> PlannerError {
+        PlannerError::Json { path: path.to_string(), source: source }
+    }
+}
+
+impl fmt::Display for PlannerError {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlannerError::Io { path, source } => { write!( f, "{}: {}", path, source ) },
+            PlannerError::Parse { path, line, msg } => { write!( f, "{}:{}: {}", path, line, msg ) },
+            PlannerError::Json { path, source } => { write!( f, "{}:{}:{}: {}", path, source.line(), source.column(), source ) },
+            PlannerError::InstanceNotFound(x) => { write!( f, "problem instance {} not found", x ) },
+            PlannerError::ModelNotFound(x) => { write!( f, "model {} not found", x ) },
+            PlannerError::InvalidParam(x) => { write!( f, "invalid planner parameter: {}", x ) },
+        }
+    }
+}
+
+impl std::error::Error for PlannerError {
+
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PlannerError::Io { source, .. } => { Some(source) },
+            PlannerError::Json { source, .. } => { Some(source) },
+            _ => { None },
+        }
+    }
+}
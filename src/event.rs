@@ -0,0 +1,12 @@
+//! Planner events
+//!
+//! Collected while iterating and drained by the caller, see Planner::take_events.
+
+use serde::{Serialize,Deserialize};
+
+#[derive(Clone,Debug,PartialEq,Serialize,Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannerEvent {
+    ///a goal node of lower cost than the current best is added to the tree, ``cost_previous`` is None for the first solution
+    NewBestSolution { iteration: u32, cost: f32, cost_previous: Option<f32> },
+}
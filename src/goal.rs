@@ -0,0 +1,197 @@
+//! Goal regions
+//!
+//! Set of states accepted as reaching the goal, relative to the goal state of Param.
+//! Positional and heading components of a state are identified by SystemModel::position_dims and SystemModel::heading_dim.
+//!
+//! Scenario files may specify a region, eg:
+//! "goal_region": { "tolerance": { "position": 0.02, "heading": 0.3 } }
+//! "goal_region": { "box": [ 0.05, 0.05, 0.1 ] }
+//!
+//! Goal biased sampling draws from the ball inscribed in the positional extent of the region unless a radius is configured.
+//! Predicate regions have no extent, their biased samples are the goal state itself.
+
+use std::fmt;
+use std::sync::Arc;
+use std::f32::consts::PI;
+
+use rand::RngCore;
+use serde::{Serialize,Deserialize};
+
+use crate::states::States;
+use crate::control::Control;
+use crate::system_model::SystemModel;
+
+#[derive(Clone,Serialize,Deserialize)]
+#[serde(rename_all = "lowercase", bound = "")]
+pub enum GoalRegion<TS> {
+
+    ///euclidean distance of position within ``position``, absolute heading difference within ``heading`` if given
+    Tolerance { position: f32, heading: Option<f32> },
+
+    ///absolute difference within half extent for each state dimension, dimensions beyond the list are not constrained
+    Box( Vec<f32> ),
+
+    ///arbitrary goal condition of a state, distance to goal falls back to positional distance
+    #[serde(skip)]
+    Predicate( Arc<dyn Fn(&TS) -> bool + Send + Sync> ),
+}
+
+impl<TS> fmt::Debug for GoalRegion<TS> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoalRegion::Tolerance { position, heading } => {
+                f.debug_struct("Tolerance")
+                    .field("position", position )
+                    .field("heading", heading )
+                    .finish()
+            },
+            GoalRegion::Box( x ) => { f.debug_tuple("Box").field( x ).finish() },
+            GoalRegion::Predicate(_) => { write!( f, "Predicate" ) },
+        }
+    }
+}
+
+///absolute angle difference in [0,PI]
+fn angle_diff( a: f32, b: f32 ) -> f32 {
+    let d = ( a - b ).abs() % ( 2. * PI );
+    if d > PI { 2. * PI - d } else { d }
+}
+
+impl<TS> GoalRegion<TS> where TS: States {
+
+    pub fn contains<TC,TObs>( & self, model: &dyn SystemModel<TS,TC,TObs>, state: &TS, goal: &TS ) -> bool where TC: Control, TObs: States {
+        match self {
+            GoalRegion::Predicate(f) => { f( state ) },
+            _ => { self.distance( model, state, goal ) <= 0. },
+        }
+    }
+
+    ///distance of ``state`` to the region, 0 if inside, used for progress reporting
+    pub fn distance<TC,TObs>( & self, model: &dyn SystemModel<TS,TC,TObs>, state: &TS, goal: &TS ) -> f32 where TC: Control, TObs: States {
+
+        let a = state.get_vals();
+        let b = goal.get_vals();
+        let dims_pos = model.position_dims();
+        let dim_heading = model.heading_dim();
+
+        let dist_pos = a.iter().zip( b.iter() )
+            .take( dims_pos )
+            .fold( 0., |acc, (x,y)| acc + (x-y)*(x-y) )
+            .sqrt();
+
+        match self {
+            GoalRegion::Tolerance { position, heading } => {
+
+                let excess_pos = ( dist_pos - position ).max( 0. );
+
+                let excess_heading = match ( heading, dim_heading ) {
+                    ( Some(tol), Some(i) ) => { ( angle_diff( a[i], b[i] ) - tol ).max( 0. ) },
+                    _ => { 0. },
+                };
+
+                excess_pos + excess_heading
+            },
+            GoalRegion::Box( half_extents ) => {
+                half_extents.iter().zip( a.iter().zip( b.iter() ) ).enumerate()
+                    .map(|(i,(h,(x,y)))| {
+                        let d = if Some(i) == dim_heading { angle_diff( *x, *y ) } else { ( x - y ).abs() };
+                        ( d - h ).max( 0. )
+                    })
+                    .fold( 0., |acc, x| acc + x*x )
+                    .sqrt()
+            },
+            GoalRegion::Predicate(f) => {
+                if f( state ) { 0. } else { dist_pos }
+            },
+        }
+    }
+
+    ///true if heading of a state is not constrained by the region
+    pub fn ignores_heading<TC,TObs>( & self, model: &dyn SystemModel<TS,TC,TObs> ) -> bool where TC: Control, TObs: States {
+        match ( self, model.heading_dim() ) {
+            ( GoalRegion::Tolerance { heading, .. }, _ ) => { heading.is_none() },
+            ( GoalRegion::Box( half_extents ), Some(i) ) => { half_extents.len() <= i },
+            _ => { true },
+        }
+    }
+
+    ///radius of the largest ball around the goal position within the region, None if the region has no positional extent
+    pub fn inscribed_radius<TC,TObs>( & self, model: &dyn SystemModel<TS,TC,TObs> ) -> Option<f32> where TC: Control, TObs: States {
+        match self {
+            GoalRegion::Tolerance { position, .. } => { Some( *position ) },
+            GoalRegion::Box( half_extents ) => {
+                half_extents.iter()
+                    .take( model.position_dims() )
+                    .cloned()
+                    .fold( None, |acc: Option<f32>, x| Some( acc.map_or( x, |y| y.min(x) ) ) )
+            },
+            GoalRegion::Predicate(_) => { None },
+        }
+    }
+
+    ///seed sample for goal biased sampling, within ``radius`` of the goal position if given, within the inscribed ball of the region otherwise,
+    ///heading is sampled uniformly if ``ignore_heading`` is set or the region does not constrain it
+    pub fn sample<TC,TObs>( & self, model: &dyn SystemModel<TS,TC,TObs>, goal: TS, radius: Option<f32>, ignore_heading: bool, rng: &mut dyn RngCore ) -> TS where TC: Control, TObs: States {
+        
+        let r = radius.or_else(|| self.inscribed_radius( model ) ).unwrap_or( 0. );
+        
+        model.sample_goal_region( goal, r, ignore_heading || self.ignores_heading( model ), rng )
+    }
+}
+
+#[test]
+fn test_goal_region(){
+
+    use crate::states::States3D;
+    use crate::dynamics_dubins::DubinsCar;
+
+    let model = DubinsCar::default();
+    let goal = States3D([0.5, 0.5, 0.]);
+
+    let r = GoalRegion::Tolerance { position: 0.1, heading: Some(0.5) };
+    assert!( r.contains( &model, &States3D([0.55, 0.5, 0.4]), &goal ) );
+    assert!( r.contains( &model, &States3D([0.55, 0.5, 2. * PI - 0.4]), &goal ) );
+    assert!( !r.contains( &model, &States3D([0.55, 0.5, 1.]), &goal ) );
+    assert!( ( r.distance( &model, &States3D([0.7, 0.5, 0.]), &goal ) - 0.1 ).abs() < 1e-5 );
+    
+    let r = GoalRegion::Box( vec![ 0.1, 0.2 ] );
+    assert!( r.contains( &model, &States3D([0.55, 0.65, 3.]), &goal ) );
+    assert!( !r.contains( &model, &States3D([0.65, 0.5, 0.]), &goal ) );
+    assert!( r.ignores_heading( &model ) );
+
+    let r : GoalRegion<States3D> = serde_json::from_str( r#"{ "tolerance": { "position": 0.02, "heading": null } }"# ).unwrap();
+    assert!( r.ignores_heading( &model ) );
+    
+    let r = GoalRegion::Predicate( Arc::new(|x: &States3D| x.0[0] > 0.9 ) );
+    assert!( r.contains( &model, &States3D([0.95, 0., 0.]), &goal ) );
+    assert_eq!( r.inscribed_radius( &model ), None );
+}
+
+#[test]
+fn test_goal_region_sample(){
+
+    use rand::prelude::*;
+    use crate::states::States3D;
+    use crate::dynamics_dubins::DubinsCar;
+
+    let model = DubinsCar::default();
+    let goal = States3D([0.5, 0.5, 1.]);
+    let mut rng = StdRng::seed_from_u64( 0 );
+
+    //biased samples fall within the region unless a radius is configured
+    for r in [ GoalRegion::Tolerance { position: 0.1, heading: Some(0.5) },
+               GoalRegion::Box( vec![ 0.2, 0.05, 0.3 ] ) ].iter() {
+        for _ in 0..100 {
+            let x = r.sample( &model, goal.clone(), None, false, &mut rng );
+            assert!( r.contains( &model, &x, &goal ) );
+        }
+    }
+
+    let r = GoalRegion::Box( vec![ 0.2, 0.05 ] );
+    assert_eq!( r.inscribed_radius( &model ), Some( 0.05 ) );
+    
+    let far = (0..100).map(|_| r.sample( &model, goal.clone(), Some(0.5), false, &mut rng ) )
+        .any(|x| !r.contains( &model, &x, &goal ) );
+    assert!( far );
+}
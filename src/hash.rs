@@ -0,0 +1,11 @@
+//! Hash containers with fixed hashing keys
+//!
+//! Iteration order of std containers using RandomState differs from run to run,
+//! these are used in the planner so that a given seed reproduces the same tree.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::BuildHasherDefault;
+
+pub type HashMap<K,V> = std::collections::HashMap<K,V,BuildHasherDefault<DefaultHasher>>;
+
+pub type HashSet<T> = std::collections::HashSet<T,BuildHasherDefault<DefaultHasher>>;
@@ -1,4 +1,4 @@
-use std::time::{Duration, Instant};
+use std::time::Instant;
 
 pub struct Timer {
     t: Instant,
@@ -14,12 +14,7 @@ impl Default for Timer {
 
 impl Timer {
 
-    pub fn reset( & mut self ){
-        self.t = Instant::now();
-    }
-
     pub fn dur_ms( & mut self ) -> f64 {
-        let t_milli = self.t.elapsed().as_nanos() as f64 / 1e6;
-        t_milli
+        self.t.elapsed().as_nanos() as f64 / 1e6
     }
 }
@@ -0,0 +1,70 @@
+//! Sample Based Planner
+//!
+//! Core planning library shared by the `planner`, `gen_obs` and `map2poly` binaries.
+//!
+//! Typical use is to load a dynamical model (eg: `dynamics_dubins::load_model`),
+//! load obstacles (see `map_loader`), then construct a `PlannerBasic` and call
+//! `Planner::plan_iteration` until it reports no further change.
+//!
+//! Rendering is not part of this library.
+
+//explicit field initialization, clones of copyable states, negated comparisons rejecting NaN and
+//toolchain compatible std constants / Option::map_or are the style of this crate
+#![allow(clippy::redundant_field_names,
+         clippy::clone_on_copy,
+         clippy::neg_cmp_op_on_partial_ord,
+         clippy::legacy_numeric_constants,
+         clippy::unnecessary_map_or,
+         clippy::manual_is_multiple_of,
+         clippy::module_inception,
+         clippy::too_many_arguments,
+         clippy::type_complexity)]
+
+#[macro_use] extern crate log;
+
+mod instrumentation;
+
+pub mod hash;
+pub mod error;
+pub mod planner_param;
+pub mod system_model;
+pub mod goal;
+pub mod cost;
+pub mod validity;
+pub mod planner;
+pub mod planner_basic;
+pub mod planner_portfolio;
+pub mod stats;
+pub mod event;
+pub mod solution;
+pub mod states;
+pub mod control;
+pub mod dynamics_dubins;
+pub mod dubins;
+pub mod dynamics_airplane;
+pub mod dynamics_reeds_shepp;
+pub mod reeds_shepp;
+pub mod collision;
+pub mod shortcut;
+pub mod rrt;
+pub mod map_loader;
+pub mod moprim;
+pub mod prob_instances;
+pub mod model_registry;
+
+#[cfg(test)]
+mod test_fixture;
+
+pub use planner::Planner;
+pub use planner_basic::PlannerBasic;
+pub use planner_param::{Param,ParamObstacles,ParamTree,ObsVariant,PlannerConfig};
+pub use system_model::SystemModel;
+pub use goal::GoalRegion;
+pub use rrt::rrt::RRT;
+pub use rrt::sst::SST;
+pub use states::{States,States1D,States2D,States3D,States4D};
+pub use control::{Control,Control1D,Control2D,Control3D,Control4D};
+pub use moprim::MoPrim;
+pub use solution::Solution;
+pub use error::PlannerError;
+pub use event::PlannerEvent;
@@ -1,5 +1,8 @@
 // #![feature(label_break_value)]
 
+//plan functions take the parsed run setup as arguments, plan_render without rendering keeps the signature of the render build
+#![allow(clippy::too_many_arguments, clippy::boxed_local)]
+
 #[macro_use] extern crate log;
 
 extern crate pretty_env_logger;
@@ -8,184 +11,148 @@ extern crate pretty_env_logger;
 
 use std::env;
 use std::collections::HashMap;
-use std::io::{stdin};
-              
-mod instrumentation;
-mod planner_param;
-mod planner;
-mod planner_basic;
-mod stats;
-mod states;
-mod dynamics_dubins;
-mod dynamics_airplane;
-mod rrt;
-mod control;
-mod map_loader;
-mod moprim;
-mod prob_instances;
-
-use planner_param::{Param,ParamObstacles,ObsVariant,ParamTree};
-use planner::Planner;
-use planner_basic::{PlannerBasic};
-use states::{States,States1D,States2D,States3D,States4D};
-use control::*;
-use instrumentation::*;
+
+extern crate sample_planning;
+
+use sample_planning::planner_param::{Param,ParamObstacles,PlannerConfig,PlannerKind,PLANNERS,StopCondition};
+use sample_planning::planner::Planner;
+use sample_planning::planner_basic::{PlannerBasic};
+use sample_planning::planner_portfolio::PortfolioPlanner;
+use sample_planning::states::States;
+use sample_planning::control::*;
+use sample_planning::{prob_instances,map_loader,cost};
+use sample_planning::prob_instances::{Scenario,MapPath};
+use sample_planning::model_registry::{self,ModelVisitor};
+use sample_planning::stats::Stats;
+use sample_planning::solution::Solution;
+use sample_planning::error::PlannerError;
+use sample_planning::event::PlannerEvent;
+use sample_planning::shortcut::ShortcutStats;
+
+use serde::Serialize;
 
 extern crate chrono;
-use chrono::{Duration,DateTime,Local};
 
+#[cfg(feature = "render")]
 extern crate kiss3d;
+#[cfg(feature = "render")]
 extern crate nalgebra as na;
-extern crate mazth;
-
-use zpatial::mazth::{rbox::RecBox,triprism::TriPrism};
-use zpatial::mazth::i_shape::IShape;
 
+#[cfg(feature = "render")]
 use na::{Vector3, UnitQuaternion, Translation3, Point3, U3};
+#[cfg(feature = "render")]
 use kiss3d::window::Window;
+#[cfg(feature = "render")]
 use kiss3d::light::Light;
+#[cfg(feature = "render")]
 use kiss3d::camera::*;
 
+#[cfg(feature = "render")]
 extern crate ncollide3d;
+#[cfg(feature = "render")]
 use ncollide3d::procedural::{TriMesh,IndexBuffer};
 
-use rrt::rrt::RRT;
-
 extern crate clap;
-use clap::{Arg, App, SubCommand};
-
-extern crate serde;
-use serde::{Serialize,Deserialize};
-
-#[derive(Serialize, Deserialize)]
-struct ObsData(pub [f32; 4]);
-
-#[derive(Serialize, Deserialize)]
-struct Obs {
-    obs: Vec<ObsData>,
+use clap::{Arg, App, ArgMatches};
+
+#[cfg(feature = "render")]
+fn to_point3<T>( i: &T ) -> Point3<f32> where T: States {
+    let v = i.get_vals_3();
+    Point3::new( v[0],
+                 v[1],
+                 v[2] )
 }
 
-impl From<&States3D> for Point3<f32> {
-    fn from(i: &States3D) -> Point3<f32> {
-        Point3::new( i.0[0],
-                     i.0[1],
-                     i.0[2] )
-    }
+///triangulated game map for rendering
+#[cfg_attr(not(feature = "render"), allow(dead_code))]
+struct CustomMap {
+    verts: Vec<(f32,f32)>,
+    tris: Vec<[usize;3]>,
+    max_x: f32,
+    max_y: f32,
 }
 
-///alternative to loading a obstacle file
-fn generate_obstacles<TObs>() -> ParamObstacles<TObs> where TObs: States {
-
-    use std::marker::PhantomData;
-    use rand::Rng;
+impl CustomMap {
+    #[cfg(feature = "render")]
+    fn mesh( &self ) -> TriMesh<f32> {
     
-    //generate boxes for now
-    let boxes = {
-
-        let mut rng = rand::thread_rng();
-        
-        (0..1).map( |_| {
-
-            let size = rng.gen_range(0.01, 0.075);
-            let x = rng.gen_range(0.25, 0.75);
-            let y = rng.gen_range(0.25, 0.75);
-            RecBox::init( &[ x, y, 0. ], size ) //constant height obstacles
-        } ).collect::<Vec<_>>()
-    };
+        let mesh_points = self.verts.iter().map(|(x,y)| Point3::new(*x,*y,0.) ).collect::<Vec<_>>();
+        let mesh_tris = self.tris.iter().map(|x| Point3::new(x[0] as u32,x[1] as u32 ,x[2] as u32) ).collect::<Vec<_>>();
     
-    ParamObstacles {
-        obstacles: ObsVariant::RBOX(boxes),
-        states_info: PhantomData,
+        let indexbuf = IndexBuffer::Unified( mesh_tris );
+
+        TriMesh::new( mesh_points,
+                      None,
+                      None,
+                      Some(indexbuf) )
     }
 }
 
-fn load_obs_from_file<TObs>(f: &str) -> ParamObstacles<TObs> where TObs: States {
-
-    use std::marker::PhantomData;
-    use std::fs::File;
-    use std::io::Read;
+///returns obstacles and map for rendering
+fn load_custom_map<TObs>( path_nodes: &str, path_ele: &str ) -> Result<( ParamObstacles<TObs>, CustomMap ), PlannerError> where TObs: States {
     
-    let mut s = String::new();
-    let mut f = File::open(f).expect("obstacle file cannot be opened");
-    f.read_to_string(& mut s).expect("file cannot be read to string");
-    let obs : Obs = serde_json::from_str( s.as_str() ).expect("obstacle deserialization failed");
-    
-    let boxes = obs.obs.iter()
-        .map(|x| {
-            let coords = x.0;
-            RecBox::init( &[ coords[0] as _, //x
-                             coords[1] as _, //y
-                             coords[2] as _], //z
-                             coords[3] as _) //size
-        } ).collect::<Vec<_>>();
+    //load custom map
+
+    let (obs,verts,tris,max_x,max_y) = map_loader::load_custom_map( path_nodes, path_ele )?;
     
-    ParamObstacles {
-        obstacles: ObsVariant::RBOX(boxes),
-        states_info: PhantomData,
-    }    
+    Ok( ( obs,
+          CustomMap { verts,
+                      tris,
+                      max_x,
+                      max_y } ) )
 }
 
-///returns planner, mesh, mesh_max_x, mesh_max_y
-fn load_custom_map( path_nodes: &str, path_ele: &str ) -> ( ParamObstacles<States3D>,
-                                                            TriMesh<f32>,
-                                                            f32,
-                                                            f32 ) {
+fn load_obs_map<TObs>( obs_path: &str ) -> Result<ParamObstacles<TObs>, PlannerError> where TObs: States {
     
-    //load custom map
+    map_loader::load_obs_from_file::<TObs>(obs_path)
+}
 
-    use map_loader;
+///value of argument ``name``, None if not given, InvalidParam if it does not parse
+fn parse_arg<T>( matches: &ArgMatches, name: &str ) -> Result<Option<T>, PlannerError> where T: std::str::FromStr {
+    match matches.value_of( name ) {
+        Some(x) => {
+            x.parse().map( Some ).map_err(|_| PlannerError::InvalidParam( format!( "{} argument not valid: {}", name, x ) ) )
+        },
+        _ => { Ok( None ) },
+    }
+}
 
-    println!("loading map.");
+///overrides ``config`` with valued arguments
+fn parse_config_args( config: &mut PlannerConfig, matches: &ArgMatches ) -> Result<(), PlannerError> {
 
-    let (verts,tris,max_x,max_y) = map_loader::load_map(path_ele,path_nodes);
+    if let Some(x) = parse_arg( matches, "mo_prim_thresh" )? {
+        config.mo_prim_thresh = x;
+    }
 
-    println!("loaded map.");
-    
-    let mesh_points = verts.iter().map(|(x,y)| Point3::new(*x,*y,0.) ).collect::<Vec<_>>();
-    let mesh_tris = tris.iter().map(|x| Point3::new(x[0] as u32,x[1] as u32 ,x[2] as u32) ).collect::<Vec<_>>();
+    if let Some(x) = parse_arg( matches, "goal_bias" )? {
+        config.goal_bias = x;
+    }
 
-    info!("custom map triangle vert count: {}, triangle count: {}", mesh_points.len(), mesh_tris.len() );
-    
-    let indexbuf = IndexBuffer::Unified( mesh_tris );
+    if let Some(x) = parse_arg( matches, "steer_to_goal" )? {
+        config.steer_to_goal = Some( x );
+    }
 
-    let map_custom_mesh = TriMesh::new( mesh_points,
-                                             None,
-                                             None,
-                                             Some(indexbuf) );
+    if let Some(x) = parse_arg( matches, "shortcut" )? {
+        config.shortcut_iterations = x;
+    }
 
-    let triangle_prims = tris.iter()
-        .map(|x| {
-            
-            let v0 = verts[x[0] as usize];
-            let v1 = verts[x[1] as usize];
-            let v2 = verts[x[2] as usize];
+    if let Some(x) = parse_arg( matches, "goal_bias_radius" )? {
+        config.goal_bias_radius = Some( x );
+    }
 
-            let height = 1.;
-            let tp = TriPrism::init( &[ v0.0 as _, v0.1 as _, -0.5,
-                                        v1.0 as _, v1.1 as _, -0.5,
-                                        v2.0 as _, v2.1 as _, -0.5 ], height );
-            tp
+    if let Some(x) = parse_arg( matches, "collision_resolution" )? {
+        config.collision_resolution = Some( x );
+    }
 
-        }).collect::<Vec<_>>();
+    if let Some(x) = parse_arg( matches, "seed" )? {
+        config.seed = Some( x );
+    }
 
-    use std::marker::PhantomData;
-    
-    let obs = ParamObstacles {
-        obstacles: ObsVariant::TRIPRISM(triangle_prims),
-        states_info: PhantomData,
-    };
-    
-    ( obs,
-      map_custom_mesh,
-      max_x,
-      max_y )
-}
+    if let Some(x) = matches.value_of("planner") {
+        config.planner = x.parse()?;
+    }
 
-fn load_obs_map( obs_path: &str ) -> ParamObstacles<States3D> {
-    
-    let obs = load_obs_from_file::<States3D>(obs_path);
-    
-    obs
+    Ok(())
 }
 
 fn main() {
@@ -205,10 +172,16 @@ fn main() {
              .short("i")
              .help("iteration upper bound")
              .takes_value(true))
+        .arg(Arg::with_name("time_budget")
+             .short("t")
+             .long("time_budget")
+             .help("planning time upper bound in milliseconds, planner stops on whichever of iteration and time bound is reached first")
+             .takes_value(true))
         .arg(Arg::with_name("model")
              .short("m")
-             .help("model selection")
+             .help("model selection, defaults to model of the problem instance")
              .default_value("dubins")
+             .possible_values( model_registry::MODELS )
              .takes_value(true))
         .arg(Arg::with_name("obstacle")
              .short("o")
@@ -224,184 +197,573 @@ fn main() {
              .takes_value(true))
         .arg(Arg::with_name("prob_inst")
              .short("p")
-             .help("problem instance name")
+             .help("problem instance name, looked up in scenario directory")
              .required(true)
              .takes_value(true))
+        .arg(Arg::with_name("scenarios")
+             .short("s")
+             .long("scenarios")
+             .help("scenario directory")
+             .default_value( prob_instances::SCENARIO_DIR )
+             .takes_value(true))
         .arg(Arg::with_name("batch_iter")
              .short("b")
              .help("batched iteration for display")
              .takes_value(true))
+        .arg(Arg::with_name("headless")
+             .long("headless")
+             .help("plan without rendering, writes solution, statistics and status to output file or stdout, exit status is 0 if goal is reached, 1 otherwise"))
+        .arg(Arg::with_name("output")
+             .long("output")
+             .help("output file of headless report (json), defaults to stdout")
+             .takes_value(true))
+        .arg(Arg::with_name("solution")
+             .long("solution")
+             .help("write solution trajectory (states, controls, durations, cost) to file, csv if file ends with .csv, json otherwise")
+             .takes_value(true))
+        .arg(Arg::with_name("portfolio")
+             .long("portfolio")
+             .help("headless mode only, plan with N independent trees in parallel (seeds offset by worker index), report the best solution and per worker statistics")
+             .takes_value(true))
+        .arg(Arg::with_name("save_checkpoint")
+             .long("save_checkpoint")
+             .help("write planner tree (json) to file when planning stops, for resuming with --resume")
+             .takes_value(true))
+        .arg(Arg::with_name("resume")
+             .long("resume")
+             .help("continue planning from a tree written by --save_checkpoint, with the same model and obstacles")
+             .takes_value(true))
+        .arg(Arg::with_name("opt_rounds")
+             .long("opt_rounds")
+             .help("number of importance sampling rounds in headless mode with path_optimize (default: 20)")
+             .takes_value(true))
+        .arg(Arg::with_name("config")
+             .short("c")
+             .long("config")
+             .help("planner config file (json), flags below are applied on top of it")
+             .takes_value(true))
+        .arg(Arg::with_name("motion_primitives")
+             .long("motion_primitives")
+             .help("use motion primitives"))
+        .arg(Arg::with_name("mo_prim_debug")
+             .long("mo_prim_debug")
+             .help("display accepted motion primitive candidates"))
+        .arg(Arg::with_name("mo_prim_thresh")
+             .long("mo_prim_thresh")
+             .help("goal distance threshold for motion primitive lookup (default: 0.25)")
+             .takes_value(true))
+        .arg(Arg::with_name("runge_kutta")
+             .long("runge_kutta")
+             .help("4th order runge-kutta propagation, defaults to Euler stepping"))
+        .arg(Arg::with_name("disable_pruning")
+             .long("disable_pruning")
+             .help("disable pruning of inactive nodes"))
+        .arg(Arg::with_name("nn_sample_log")
+             .long("nn_sample_log")
+             .help("use proportional to log(# nodes) for nearest neighbour query, defaults to sqrt(# nodes)"))
+        .arg(Arg::with_name("nn_naive")
+             .long("nn_naive")
+             .help("use linear nearest neighbour query"))
+        .arg(Arg::with_name("disable_witness_disturbance")
+             .long("disable_witness_disturbance")
+             .help("disable witness representative disturbance on low witness discovery rate"))
+        .arg(Arg::with_name("state_propagate_sample")
+             .long("state_propagate_sample")
+             .help("frontier node selection, 10 samples, 50%"))
+        .arg(Arg::with_name("batch_propagate_sample")
+             .long("batch_propagate_sample")
+             .help("control propagation selection, 10 samples, 50%, applicable if nn_naive is NOT enabled"))
+        .arg(Arg::with_name("path_optimize")
+             .long("path_optimize")
+             .help("use importance sampling for optimization"))
+        .arg(Arg::with_name("goal_bias")
+             .long("goal_bias")
+             .help("probability of sampling from goal region instead of state space (default: 0)")
+             .takes_value(true))
+        .arg(Arg::with_name("goal_bias_radius")
+             .long("goal_bias_radius")
+             .help("radius of goal region around goal position for goal biased sampling (default: inscribed in goal region)")
+             .takes_value(true))
+        .arg(Arg::with_name("goal_bias_ignore_heading")
+             .long("goal_bias_ignore_heading")
+             .help("sample heading uniformly in goal region"))
+        .arg(Arg::with_name("anytime")
+             .long("anytime")
+             .help("anytime mode, keep improving after a solution is found until iteration / time bound is reached"))
+        .arg(Arg::with_name("collision_resolution")
+             .long("collision_resolution")
+             .help("maximum propagation duration between collision checked sub-states (default: simulation step)")
+             .takes_value(true))
+        .arg(Arg::with_name("collision_chord_only")
+             .long("collision_chord_only")
+             .help("collision check only the segment between end points of each propagation, faster but curved motions may cut through thin obstacles"))
+        .arg(Arg::with_name("edge_substates")
+             .long("edge_substates")
+             .help("keep integrated states of edges at simulation step resolution, edges are drawn and exported as polylines"))
+        .arg(Arg::with_name("steer_to_goal")
+             .long("steer_to_goal")
+             .help("connect new nodes within distance d of the goal exactly to the goal state with the model's steering function, eg: dubins curves (default: disabled)")
+             .takes_value(true))
+        .arg(Arg::with_name("shortcut")
+             .long("shortcut")
+             .help("shortcut and smooth the final solution with N random attempts, keeping it collision free and dynamically feasible (default: 0, disabled)")
+             .takes_value(true))
+        .arg(Arg::with_name("seed")
+             .long("seed")
+             .help("seed for random number generation, identical seeds give identical trees (default: entropy)")
+             .takes_value(true))
+        .arg(Arg::with_name("planner")
+             .long("planner")
+             .help("tree planner: sst, kinodynamic rrt / rrt_star baselines without pruning, or kpiece expanding from the boundary of a projection grid (default: sst)")
+             .possible_values( PLANNERS )
+             .takes_value(true))
         .get_matches();
         
-    let display_witness_info = matches.is_present("witness");
+    //planner config ---
 
-    //select init and goal states
+    let mut config = match matches.value_of("config") {
+        Some(x) => {
+            match PlannerConfig::load_from_file( x ) {
+                Ok(c) => { c },
+                Err(e) => {
+                    error!( "{}", e );
+                    std::process::exit( 2 );
+                },
+            }
+        },
+        _ => { PlannerConfig::default() },
+    };
 
-    let prob_inst = {
-        #[cfg(feature="airplane")]
-        {
-            prob_instances::load_4d_3d()
-        }
-        #[cfg(not(feature="airplane"))]
-        {
-            prob_instances::load_3d_3d()
+    config.motion_primitives |= matches.is_present("motion_primitives");
+    config.mo_prim_debug |= matches.is_present("mo_prim_debug");
+    config.runge_kutta |= matches.is_present("runge_kutta");
+    config.disable_pruning |= matches.is_present("disable_pruning");
+    config.nn_sample_log |= matches.is_present("nn_sample_log");
+    config.nn_naive |= matches.is_present("nn_naive");
+    config.disable_witness_disturbance |= matches.is_present("disable_witness_disturbance");
+    config.state_propagate_sample |= matches.is_present("state_propagate_sample");
+    config.batch_propagate_sample |= matches.is_present("batch_propagate_sample");
+    config.path_optimize |= matches.is_present("path_optimize");
+    config.stop_on_solution &= !matches.is_present("anytime");
+    config.goal_bias_ignore_heading |= matches.is_present("goal_bias_ignore_heading");
+    config.edge_substates |= matches.is_present("edge_substates");
+    config.collision_chord_only |= matches.is_present("collision_chord_only");
+
+    if let Err(e) = parse_config_args( &mut config, &matches ) {
+        error!( "{}", e );
+        std::process::exit( 2 );
+    }
+
+    info!( "planner config: {}", &config );
+
+    //dynamical model selection ---
+    
+    let scenario_dir = matches.value_of("scenarios").unwrap();
+    
+    let model_query = if matches.occurrences_of("model") == 0 {
+        match prob_instances::find_model( scenario_dir, matches.value_of("prob_inst").unwrap() ) {
+            Some(x) => { x },
+            _ => { matches.value_of("model").unwrap().to_string() },
         }
+    } else {
+        matches.value_of("model").unwrap().to_string()
     };
 
-    let prob_inst_query = matches.value_of("prob_inst").unwrap();
+    info!( "model: {}", model_query );
 
-    let ( init_goal_pair, param_tree, prop_step, iter_bound, map_path ) = match prob_inst.get(prob_inst_query){
-        Some( (ini, goal, tree_param, step, iter, path) ) => { ( (*ini,*goal), tree_param.clone(), *step, *iter, path.clone() ) },
-        _ => {
-            panic!("problem instancen {} not found", prob_inst_query );
+    let integrator = config.integrator();
+
+    let run = PlanRun { matches: &matches,
+                        config };
+
+    match model_registry::dispatch( &model_query, integrator, scenario_dir, run ).and_then(|status| status ) {
+        Ok(exit_status) => { std::process::exit( exit_status ); },
+        Err(e) => {
+            error!( "{}", e );
+            std::process::exit( 2 );
         },
-    };
+    }
+}
 
-    let iterations = match matches.value_of( "iterations" ) {
-        Some(x) => {
-            x.parse().expect("iteration argument not a number" )
+///result of a headless run
+#[derive(Serialize)]
+struct HeadlessReport<TS,TC> where TS: States, TC: Control {
+    ///"solved" or "not_solved"
+    status: &'static str,
+    planner: PlannerKind,
+    stats: Stats,
+    ///state space trajectory with controls and durations
+    solution: Option<Solution<TS,TC>>,
+    ///events of the initial run (before importance sampling rounds), eg: improvements in anytime mode
+    events: Vec<PlannerEvent>,
+    ///cost before and after post-processing of the solution, if enabled
+    shortcut: Option<ShortcutStats>,
+}
+
+impl<TS,TC> HeadlessReport<TS,TC> where TS: States, TC: Control {
+    fn from_planner<TObs>( pl: &PlannerBasic<TS,TC,TObs> ) -> Self where TObs: States {
+        
+        let stats = pl.get_stats();
+        
+        HeadlessReport {
+            status: if stats.goal_reached { "solved" } else { "not_solved" },
+            planner: pl.config().planner,
+            stats,
+            solution: pl.get_solution(),
+            events: vec![],
+            shortcut: None,
+        }
+    }
+}
+
+///runs all workers of ``portfolio`` to completion, writes report of best solution and per worker statistics to ``output`` or stdout,
+///returns exit status
+fn plan_portfolio<TS,TC,TObs>( mut portfolio: PortfolioPlanner<TS,TC,TObs>,
+                               output: Option<&str>,
+                               solution_path: Option<&str> ) -> Result<i32, PlannerError> where TS: States, TC: Control, TObs: States {
+
+    info!("computing (portfolio of {} workers)...", portfolio.num_workers() );
+
+    let report = portfolio.plan();
+
+    if let ( Some(path), Some(sol) ) = ( solution_path, report.solution.as_ref() ) {
+        sol.save( path )?;
+        info!("solution written to {}", path );
+    }
+
+    let s = serde_json::to_string_pretty( &report ).expect("report serialization failed");
+    
+    match output {
+        Some(path) => {
+            std::fs::write( path, s ).map_err(|e| PlannerError::io( path, e ) )?;
+            info!("report written to {}", path );
         },
         _ => {
-            match iter_bound {
-                Some(x) => { x },
-                _ => { panic!("iteration not provided"); },
-            }
+            println!("{}", s);
         },
-    };
-        
-    //dynamical model selection ---
-    
-    let model_query = matches.value_of("model").unwrap();
+    }
 
-    let models : HashMap<_,_> = vec![
-        {
-            #[cfg(feature="airplane")]
-            {
-                ("airplane", dynamics_airplane::load_model())
-            }
-            #[cfg(not(feature="airplane"))]
-            {
-                
-                ("dubins", dynamics_dubins::load_model())
-            }
+    Ok( if report.solution.is_some() { 0 } else { 1 } )
+}
+
+///runs planner to completion without rendering, writes report to ``output`` or stdout and returns exit status
+fn plan_headless<TS,TC,TObs>( mut pl: Box<PlannerBasic<TS,TC,TObs>>,
+                              iter_batch: Option<u32>,
+                              path_optimize: bool,
+                              opt_rounds: u32,
+                              shortcut_iterations: u32,
+                              output: Option<&str>,
+                              solution_path: Option<&str>,
+                              checkpoint_path: Option<&str> ) -> Result<i32, PlannerError> where TS: States, TC: Control, TObs: States {
+
+    info!("computing (headless)...");
+
+    let mut events = vec![];
+    
+    while pl.plan_iteration( iter_batch ) {
+        for e in pl.take_events() {
+            info!("{:?}", e );
+            events.push( e );
         }
-    ].into_iter().collect();
+    }
 
-    let model_sel = match models.get( model_query ) {
-        Some(m) => {
+    events.extend( pl.take_events() );
 
-            let mut model_default = m.clone();
+    let mut report = HeadlessReport::from_planner( &pl );
+    report.events = events;
+
+    if path_optimize && report.stats.goal_reached {
+        
+        for i in 0..opt_rounds {
             
-            model_default.iterations_bound = iterations;
+            info!("importance sampling round {}/{}", i+1, opt_rounds );
+            
+            pl.plan_init_imp_samp();
+            
+            while pl.plan_iteration( iter_batch ) {}
 
-            match prop_step {
-                Some(x) => {
-                    model_default.sim_delta = x;
+            //keep the lowest cost solution over all rounds
+            let r = HeadlessReport::from_planner( &pl );
+            match ( r.stats.cost, report.stats.cost ) {
+                ( Some(c_new), Some(c_best) ) if c_new < c_best => {
+                    report = HeadlessReport { events: report.events, ..r };
                 },
                 _ => {},
             }
+        }
+    }
+
+    if shortcut_iterations > 0 {
+        if let Some(sol) = report.solution.take() {
+            let r = pl.shortcut_solution( &sol, shortcut_iterations );
+            report.solution = Some( r.solution );
+            report.shortcut = Some( r.stats );
+        }
+    }
+
+    if let Some(sol) = report.solution.as_ref() {
+        if let Some(i) = pl.check_solution( sol ) {
+            warn!("solution edge {} in collision at collision resolution", i );
+        }
+    }
+
+    if let ( Some(path), Some(sol) ) = ( solution_path, report.solution.as_ref() ) {
+        sol.save( path )?;
+        info!("solution written to {}", path );
+    }
+
+    if let Some(path) = checkpoint_path {
+        match pl.save_checkpoint( path ) {
+            Ok(_) => { info!("checkpoint written to {}", path ); },
+            Err(e) => { error!("{}", e ); },
+        }
+    }
+
+    let s = serde_json::to_string_pretty( &report ).expect("report serialization failed");
+    
+    match output {
+        Some(path) => {
+            std::fs::write( path, s ).map_err(|e| PlannerError::io( path, e ) )?;
+            info!("report written to {}", path );
+        },
+        _ => {
+            println!("{}", s);
+        },
+    }
+
+    Ok( if report.stats.goal_reached { 0 } else { 1 } )
+}
+
+struct PlanRun<'a> {
+    matches: &'a ArgMatches<'a>,
+    config: PlannerConfig,
+}
+
+impl<'a> ModelVisitor for PlanRun<'a> {
+    
+    ///exit status of the process
+    type Output = Result<i32, PlannerError>;
+    
+    fn visit<TS,TC,TObs>( self,
+                          model: Param<TS,TC,TObs>,
+                          prob_inst: HashMap< String, Scenario<TS> > ) -> Result<i32, PlannerError> where TS: States, TC: Control, TObs: States {
+
+        let matches = self.matches;
+        let config = self.config;
+        
+        let display_witness_info = matches.is_present("witness");
+        
+        //select init and goal states
+
+        let prob_inst_query = matches.value_of("prob_inst").unwrap();
+
+        let ( init_goal_pair, goal_region, constraints, cost_terms, param_tree, prop_step, iter_bound, time_bound, map_path ) = match prob_inst.get(prob_inst_query){
+            Some( x ) => { ( (x.start.clone(),x.goal.clone()), x.goal_region.clone(), x.constraints.clone(), x.cost.clone(), x.tree.clone(), x.sim_step, x.iterations, x.time_budget_ms, x.map.clone() ) },
+            _ => {
+                return Err( PlannerError::InstanceNotFound( prob_inst_query.to_string() ) )
+            },
+        };
+
+        let iterations = match matches.value_of( "iterations" ) {
+            Some(x) => {
+                Some( x.parse().map_err(|_| PlannerError::InvalidParam( format!( "iteration argument not a number: {}", x ) ) )? )
+            },
+            _ => { iter_bound },
+        };
+
+        let time_budget = match matches.value_of( "time_budget" ) {
+            Some(x) => {
+                Some( x.parse().map_err(|_| PlannerError::InvalidParam( format!( "time budget argument not a number: {}", x ) ) )? )
+            },
+            _ => { time_bound },
+        };
+
+        let stop_condition = match ( iterations, time_budget ) {
+            ( Some(i), Some(t) ) => { StopCondition::IterationsOrTime( i, t ) },
+            ( Some(i), _ ) => { StopCondition::Iterations( i ) },
+            ( _, Some(t) ) => { StopCondition::TimeMilliSeconds( t ) },
+            _ => {
+                return Err( PlannerError::InvalidParam( "iteration or time budget not provided".to_string() ) )
+            },
+        };
+        
+        let model_sel = {
+
+            let mut model_default = model;
+        
+            model_default.stop_condition = stop_condition;
+
+            if let Some(x) = prop_step {
+                model_default.sim_delta = x;
+            }
 
             model_default.states_init = init_goal_pair.0;
             model_default.states_goal = init_goal_pair.1;
 
+            if let Some(x) = goal_region {
+                model_default.goal_region = x;
+            }
+
+            model_default.constraints.extend( constraints );
+
             info!("model selected: {}", model_default);
-            
+        
             model_default
-        },
-        _ => { panic!("model not found: {}", model_query) },
-    };
+        };
 
-    let mut planner = None;
-    // let mut planner : Option<Box<Planner<States3D,Control1D,States3D> >> = None;
-    // let mut planner : Option<Box<Planner<States4D,Control2D,States3D> >> = None;
-    let mut obs_copy : Option<ParamObstacles<States3D>> = None;
+        //cost blend of scenario depends on obstacles
+        let with_cost = |o: &ParamObstacles<TObs>| {
+            let mut m = model_sel.clone();
+            if let Some(ref terms) = cost_terms {
+                m.cost = cost::from_terms( terms, o );
+            }
+            m
+        };
 
-    let mut map_custom_mesh = None;
-    let mut map_custom_max_x = 1.;
-    let mut map_custom_max_y = 1.;
+        let mut planner = None;
+        let mut obs_copy : Option<ParamObstacles<TObs>> = None;
 
-    info!( "plan info: {}", &model_sel );
+        let mut map_custom = None;
+
+        info!( "plan info: {}", &model_sel );
     
-    info!( "tree param: {}", &param_tree );
+        info!( "tree param: {}", &param_tree );
     
-    match ( map_path, ( matches.value_of("custom_map_nodes"), matches.value_of("custom_map_ele") ) ) {
-        ( Some(prob_instances::MapPath::Game((node_path,ele_path))), ( Some(node_path_cmdline), Some(ele_path_cmdline) ) ) => {
-            //priority for command line
-            let (o,m,x,y) = load_custom_map( node_path_cmdline, ele_path_cmdline );            
-            planner = Some(Box::new( PlannerBasic::init( model_sel.clone(),
-                                                         o.clone(),
-                                                         param_tree.clone() ) ) );
-            obs_copy = Some(o);
-            map_custom_mesh = Some(m);
-            map_custom_max_x = x;
-            map_custom_max_y = y;
-        },
-        ( Some(prob_instances::MapPath::Game((node_path,ele_path))), _ ) => {
-            let (o,m,x,y) = load_custom_map( node_path, ele_path );
-            planner = Some(Box::new( PlannerBasic::init( model_sel.clone(),
-                                                         o.clone(),
-                                                         param_tree.clone() ) ) );
-            obs_copy = Some(o);
-            map_custom_mesh = Some(m);
-            map_custom_max_x = x;
-            map_custom_max_y = y;
-        },
-        ( _, ( Some(node_path_cmdline), Some(ele_path_cmdline) ) ) => {
-            let (o,m,x,y) = load_custom_map( node_path_cmdline, ele_path_cmdline );
-            planner = Some( Box::new( PlannerBasic::init( model_sel.clone(),
-                                                          o.clone(),
-                                                          param_tree.clone() ) ) );
-            obs_copy = Some(o);
-            map_custom_mesh = Some(m);
-            map_custom_max_x = x;
-            map_custom_max_y = y;
-        },
-        _ => {},
-    }
-
-    if planner.is_none() {
-        match ( map_path, matches.value_of("obstacle") ) {
-            ( Some(prob_instances::MapPath::Obs(obs_path)), Some(obs_path_cmdline) ) => {
+        match ( map_path.as_ref(), ( matches.value_of("custom_map_nodes"), matches.value_of("custom_map_ele") ) ) {
+            ( Some(MapPath::Game{ nodes: _node_path, ele: _ele_path }), ( Some(node_path_cmdline), Some(ele_path_cmdline) ) ) => {
                 //priority for command line
-                let o = load_obs_map( obs_path_cmdline );
-                planner = Some( Box::new( PlannerBasic::init( model_sel.clone(),
-                                                              o.clone(),
-                                                              param_tree.clone() ) ) );
+                let (o,m) = load_custom_map( node_path_cmdline, ele_path_cmdline )?;            
+                planner = Some(Box::new( PlannerBasic::init( with_cost( &o ),
+                                                             o.clone(),
+                                                             param_tree.clone(),
+                                                             config.clone() )? ) );
                 obs_copy = Some(o);
+                map_custom = Some(m);
             },
-            ( Some(prob_instances::MapPath::Obs(obs_path)), _ ) => {
-                let o = load_obs_map( obs_path );
-                planner = Some( Box::new( PlannerBasic::init( model_sel.clone(),
-                                                              o.clone(),
-                                                              param_tree.clone() ) ) );
+            ( Some(MapPath::Game{ nodes: node_path, ele: ele_path }), _ ) => {
+                let (o,m) = load_custom_map( node_path, ele_path )?;
+                planner = Some(Box::new( PlannerBasic::init( with_cost( &o ),
+                                                             o.clone(),
+                                                             param_tree.clone(),
+                                                             config.clone() )? ) );
                 obs_copy = Some(o);
+                map_custom = Some(m);
             },
-            ( _, Some(obs_path_cmdline) ) => {
-                let o = load_obs_map( obs_path_cmdline );
-                planner = Some( Box::new( PlannerBasic::init( model_sel.clone(),
+            ( _, ( Some(node_path_cmdline), Some(ele_path_cmdline) ) ) => {
+                let (o,m) = load_custom_map( node_path_cmdline, ele_path_cmdline )?;
+                planner = Some( Box::new( PlannerBasic::init( with_cost( &o ),
                                                               o.clone(),
-                                                              param_tree.clone() ) ) );
+                                                              param_tree.clone(),
+                                                              config.clone() )? ) );
                 obs_copy = Some(o);
+                map_custom = Some(m);
             },
             _ => {},
         }
-    }
 
-    if planner.is_none() || obs_copy.is_none() {
-        panic!("map / obstacle not provided");
-    }
+        if planner.is_none() {
+            match ( map_path.as_ref(), matches.value_of("obstacle") ) {
+                ( Some(MapPath::Obs(_obs_path)), Some(obs_path_cmdline) ) => {
+                    //priority for command line
+                    let o = load_obs_map( obs_path_cmdline )?;
+                    planner = Some( Box::new( PlannerBasic::init( with_cost( &o ),
+                                                                  o.clone(),
+                                                                  param_tree.clone(),
+                                                                  config.clone() )? ) );
+                    obs_copy = Some(o);
+                },
+                ( Some(MapPath::Obs(obs_path)), _ ) => {
+                    let o = load_obs_map( obs_path )?;
+                    planner = Some( Box::new( PlannerBasic::init( with_cost( &o ),
+                                                                  o.clone(),
+                                                                  param_tree.clone(),
+                                                                  config.clone() )? ) );
+                    obs_copy = Some(o);
+                },
+                ( _, Some(obs_path_cmdline) ) => {
+                    let o = load_obs_map( obs_path_cmdline )?;
+                    planner = Some( Box::new( PlannerBasic::init( with_cost( &o ),
+                                                                  o.clone(),
+                                                                  param_tree.clone(),
+                                                                  config.clone() )? ) );
+                    obs_copy = Some(o);
+                },
+                _ => {},
+            }
+        }
+
+        if planner.is_none() || obs_copy.is_none() {
+            return Err( PlannerError::InvalidParam( "map / obstacle not provided".to_string() ) )
+        }
+
+        //portfolio workers plan from scratch and only report, without checkpoints or post-processing
+        if matches.is_present("portfolio") {
+            if let Some(x) = [ "resume", "save_checkpoint", "shortcut" ].iter().find(|x| matches.is_present( x ) ) {
+                return Err( PlannerError::InvalidParam( format!( "--{} not supported with --portfolio", x ) ) )
+            }
+            if config.shortcut_iterations > 0 {
+                return Err( PlannerError::InvalidParam( "shortcut_iterations not supported with --portfolio".to_string() ) )
+            }
+            if !matches.is_present("headless") {
+                return Err( PlannerError::InvalidParam( "--portfolio requires --headless".to_string() ) )
+            }
+        }
+
+        if let ( Some(path), Some(pl) ) = ( matches.value_of("resume"), planner.as_mut() ) {
+            pl.load_checkpoint( path, param_tree.clone() )?;
+            info!("resumed from checkpoint {}", path );
+        }
     
-    //plan ---
+        //plan ---
 
-    let iter_batch = match matches.value_of("batch_iter"){
-        Some(x) => Some(x.parse::<u32>().expect("batch iter not a number")),
-        _ => None,
-    };
+        let iter_batch = parse_arg::<u32>( matches, "batch_iter" )?;
+
+        if matches.is_present("headless") {
+
+            if let Some(n) = parse_arg::<usize>( matches, "portfolio" )? {
+                let o = obs_copy.unwrap();
+                let portfolio = PortfolioPlanner::init_seeded( with_cost( &o ), o, param_tree, config, n )?;
+                return plan_portfolio( portfolio,
+                                           matches.value_of("output"),
+                                           matches.value_of("solution") );
+            }
+            
+            let opt_rounds = parse_arg::<u32>( matches, "opt_rounds" )?.unwrap_or( 20 );
+            
+            return plan_headless( planner.unwrap(),
+                                      iter_batch,
+                                      config.path_optimize,
+                                      opt_rounds,
+                                      config.shortcut_iterations,
+                                      matches.value_of("output"),
+                                      matches.value_of("solution"),
+                                      matches.value_of("save_checkpoint") );
+        }
     
-    //render ---
+        plan_render( planner.unwrap(),
+                     &model_sel,
+                     obs_copy.unwrap(),
+                     map_custom,
+                     iter_batch,
+                     display_witness_info,
+                     &config,
+                     matches )
+    }
+}
+
+///plans with rendering of the tree, solution and obstacles until the window is closed or planning stops, returns exit status
+#[cfg(feature = "render")]
+fn plan_render<TS,TC,TObs>( pl: Box<PlannerBasic<TS,TC,TObs>>,
+                            model_sel: &Param<TS,TC,TObs>,
+                            obs: ParamObstacles<TObs>,
+                            map_custom: Option<CustomMap>,
+                            iter_batch: Option<u32>,
+                            display_witness_info: bool,
+                            config: &PlannerConfig,
+                            matches: &ArgMatches ) -> Result<i32, PlannerError> where TS: States, TC: Control, TObs: States {
 
     let mut camera = ArcBall::new( Point3::new(0.5,0.5,3.), Point3::new(0.5,0.5,0.) );
-        
+    
     let mut window = Window::new("Sample Planner");
     window.set_light(Light::StickToCamera);
     window.set_background_color( 1., 1., 1. );
@@ -409,19 +771,19 @@ fn main() {
     let mut g2 = window.add_group();
 
     let mut obs_data = vec![];
-    
-    if map_custom_mesh.is_some(){
+
+    if let Some(map) = map_custom {
         //scale map size to maximum of 1.0 on longest (x,y) dimensions
-        let scale = if map_custom_max_x > map_custom_max_y {
-            map_custom_max_x }
+        let scale = if map.max_x > map.max_y {
+            map.max_x }
         else {
-            map_custom_max_y
+            map.max_y
         };
-        
-        let mut handle = g2.add_trimesh( map_custom_mesh.unwrap(), Vector3::new( 1./scale, 1./scale, 1.) );
+    
+        let mut handle = g2.add_trimesh( map.mesh(), Vector3::new( 1./scale, 1./scale, 1.) );
         handle.set_color(0.93, 0.93, 0.75);
     } else {
-        
+    
         let mut g1 = window.add_group();
         g1.append_translation(&Translation3::new(0.5, 0.5, 0.0));
 
@@ -430,8 +792,8 @@ fn main() {
         c.set_lines_width(5.);
         c.set_points_size(5.0);
         c.set_surface_rendering_activation(false);
-        
-        match obs_copy.unwrap().obstacles {
+    
+        match obs.obstacles {
             ObsVariant::RBOX(ref o) => {
                 obs_data = o.iter().map(|x| {
                     let l = x._size as f32;
@@ -442,47 +804,45 @@ fn main() {
         }
     }
 
-    let mut pl = planner.unwrap();
+    let mut pl = pl;
 
     info!("computing...");
-    
-    while window.render_with_camera( & mut camera ) {
 
-        let mut timer = Timer::default();
+    while window.render_with_camera( & mut camera ) {
 
         let changed = pl.plan_iteration( iter_batch );
-        
+
+        for e in pl.take_events() {
+            info!("{:?}", e );
+        }
+    
         // info!( "plan batch iteration ({:?}) time: {} ms", iter_batch, timer.dur_ms() );
 
         let coords_points : Vec<Point3<f32>> = pl.get_trajectories().iter()
-            .map(|x| Point3::from(x) )
+            .map(|x| to_point3(x) )
             .collect();
-        
+    
         let coords : Vec<((Point3<f32>,Point3<f32>),u32)> = pl.get_trajectories_edges().iter()
-            .map(|x| {
-                let a = ((x.0).0).0;
-                let b = ((x.0).1).0;
-                ( ( Point3::new(a[0],a[1],a[2]),
-                    Point3::new(b[0],b[1],b[2]) ),
-                    x.1 )
+            .flat_map(|x| {
+                //split polyline of edge into segments
+                x.0.windows(2)
+                    .map(move |w| ( ( to_point3( &w[0] ), to_point3( &w[1] ) ), x.1 ) )
             })
             .collect();
 
         let traj_solution : Vec<((Point3<f32>,Point3<f32>),u32)> = pl.get_trajectory_best_edges().iter()
-            .map(|x| {
-                let a = ((x.0).0).0;
-                let b = ((x.0).1).0;
-                ( ( Point3::new(a[0],a[1],a[2]),
-                    Point3::new(b[0],b[1],b[2]) ),
-                    x.1 )
+            .flat_map(|x| {
+                //split polyline of edge into segments
+                x.0.windows(2)
+                    .map(move |w| ( ( to_point3( &w[0] ), to_point3( &w[1] ) ), x.1 ) )
             })
             .collect();
-        
+    
         //(witness, witness representative) pairs
         let coords_witnesses : Vec<(Point3<f32>,Point3<f32>)> = pl.get_witness_pairs().iter()
             .map(|x| {
-                let a = (x.0).0;
-                let b = (x.1).0;
+                let a = x.0.get_vals_3();
+                let b = x.1.get_vals_3();
                 ( Point3::new(a[0],a[1],a[2]),
                   Point3::new(b[0],b[1],b[2]) )
             })
@@ -490,8 +850,8 @@ fn main() {
 
         let mo_prim_candidates : Vec<(Point3<f32>,Point3<f32>)> = pl.get_trajectories_mo_prim_candidates().iter()
             .map(|x| {
-                let a = (x.0).0;
-                let b = (x.1).0;
+                let a = x.0.get_vals_3();
+                let b = x.1.get_vals_3();
                 ( Point3::new(a[0],a[1],a[2]),
                   Point3::new(b[0],b[1],b[2]) )
             })
@@ -502,7 +862,7 @@ fn main() {
             .for_each(|x| {
                 window.draw_line( &x.0, &x.1, &Point3::new(0.9,1.0,0.0) );
             } );
-        
+    
         coords.iter()
             .for_each(|x| {
                 //draw edge of different colours due to different type of edges
@@ -522,10 +882,10 @@ fn main() {
                     // window.draw_line( &(x.0).0, &(x.0).1, &Point3::new(0.,1.,0.) );
                 // }
             } );
-            
+        
         //domain perimeter
         window.set_point_size(0.3);    
-        
+    
         // coords_points.iter()
         //     .for_each(|x| { window.draw_point( &x, &Point3::new(0.,0.,1.) ); } );
 
@@ -538,18 +898,18 @@ fn main() {
                     window.draw_point( &x.1, &Point3::new(0.,0.,1.) );
                 } );
         }
-        
+    
         window.set_point_size(10.);
-        
+    
         //start point
-        
-        let config_state_init = (model_sel.project_state_to_config)(model_sel.states_init.clone());
-        window.draw_point( &Point3::from( &config_state_init ),
+    
+        let config_state_init = model_sel.model.project_state_to_config(model_sel.states_init.clone());
+        window.draw_point( &to_point3( &config_state_init ),
                             &Point3::new(0.,1.,0.) );
 
         //dest point
-        let config_state_goal = (model_sel.project_state_to_config)(model_sel.states_goal.clone());
-        window.draw_point( &Point3::from( &config_state_goal ),
+        let config_state_goal = model_sel.model.project_state_to_config(model_sel.states_goal.clone());
+        window.draw_point( &to_point3( &config_state_goal ),
                             &Point3::new(1.,0.,0.) );
 
         obs_data.iter()
@@ -563,7 +923,25 @@ fn main() {
 
         if !changed {
             use std::io::{self,Read};
-            println!("found an initial feasible path..");
+            let stats = pl.get_stats();
+            if stats.goal_reached {
+                println!("found an initial feasible path..");
+            } else {
+                println!("planner stopped without a feasible path: {:?}", stats.stop_reason );
+            }
+            if let ( Some(path), Some(sol) ) = ( matches.value_of("solution"), pl.get_solution() ) {
+                let sol = if config.shortcut_iterations > 0 {
+                    pl.shortcut_solution( &sol, config.shortcut_iterations ).solution
+                } else {
+                    sol
+                };
+                sol.save( path )?;
+                info!("solution written to {}", path );
+            }
+            if let Some(path) = matches.value_of("save_checkpoint") {
+                pl.save_checkpoint( path )?;
+                info!("checkpoint written to {}", path );
+            }
             use std::{thread, time};
             let t_s = time::Duration::from_millis(3000);
             thread::sleep(t_s);
@@ -571,56 +949,49 @@ fn main() {
         }
     }
 
-    #[cfg(feature="path_optimize")]
-    {
+    if config.path_optimize {
         loop {
-            
+        
             println!("starting importance sampling..");
-            
+        
             pl.plan_init_imp_samp();
-            
+        
             while window.render_with_camera( & mut camera ) {
 
-                let mut timer = Timer::default();
-
                 let changed = pl.plan_iteration( iter_batch );
 
                 //projected state space sampling distribution mean
                 pl.get_sampling_distr().iter()
                     .for_each(|x|{
-                        let p = Point3::from(x);
+                        let p = to_point3(x);
                         window.draw_point( &p, &Point3::new(1.,1.,0.) );
                     });
-                
+            
                 let coords_points : Vec<Point3<f32>> = pl.get_trajectories().iter()
-                    .map(|x| Point3::from(x) )
+                    .map(|x| to_point3(x) )
                     .collect();
-                
+            
                 let coords : Vec<((Point3<f32>,Point3<f32>),u32)> = pl.get_trajectories_edges().iter()
-                    .map(|x| {
-                        let a = ((x.0).0).0;
-                        let b = ((x.0).1).0;
-                        ( ( Point3::new(a[0],a[1],a[2]),
-                            Point3::new(b[0],b[1],b[2]) ),
-                            x.1 )
+                    .flat_map(|x| {
+                        //split polyline of edge into segments
+                        x.0.windows(2)
+                            .map(move |w| ( ( to_point3( &w[0] ), to_point3( &w[1] ) ), x.1 ) )
                     })
                     .collect();
 
                 let traj_solution : Vec<((Point3<f32>,Point3<f32>),u32)> = pl.get_trajectory_best_edges().iter()
-                    .map(|x| {
-                        let a = ((x.0).0).0;
-                        let b = ((x.0).1).0;
-                        ( ( Point3::new(a[0],a[1],a[2]),
-                            Point3::new(b[0],b[1],b[2]) ),
-                            x.1 )
+                    .flat_map(|x| {
+                        //split polyline of edge into segments
+                        x.0.windows(2)
+                            .map(move |w| ( ( to_point3( &w[0] ), to_point3( &w[1] ) ), x.1 ) )
                     })
                     .collect();
-                
+            
                 //(witness, witness representative) pairs
                 let coords_witnesses : Vec<(Point3<f32>,Point3<f32>)> = pl.get_witness_pairs().iter()
                     .map(|x| {
-                        let a = (x.0).0;
-                        let b = (x.1).0;
+                        let a = x.0.get_vals_3();
+                        let b = x.1.get_vals_3();
                         ( Point3::new(a[0],a[1],a[2]),
                           Point3::new(b[0],b[1],b[2]) )
                     })
@@ -628,8 +999,8 @@ fn main() {
 
                 let mo_prim_candidates : Vec<(Point3<f32>,Point3<f32>)> = pl.get_trajectories_mo_prim_candidates().iter()
                     .map(|x| {
-                        let a = (x.0).0;
-                        let b = (x.1).0;
+                        let a = x.0.get_vals_3();
+                        let b = x.1.get_vals_3();
                         ( Point3::new(a[0],a[1],a[2]),
                           Point3::new(b[0],b[1],b[2]) )
                     })
@@ -640,7 +1011,7 @@ fn main() {
                     .for_each(|x| {
                         window.draw_line( &x.0, &x.1, &Point3::new(0.9,1.0,0.0) );
                     } );
-                
+            
                 coords.iter()
                     .for_each(|x| {
                         //draw edge of different colours due to different type of edges
@@ -656,7 +1027,7 @@ fn main() {
                         //draw edge of different colours due to different type of edges
                         window.draw_line( &(x.0).0, &(x.0).1, &Point3::new(1.,0.,0.) );
                     } );
-                
+            
                 //domain perimeter
                 window.set_point_size(0.3);    
 
@@ -669,18 +1040,18 @@ fn main() {
                             window.draw_point( &x.1, &Point3::new(0.,0.,1.) );
                         } );
                 }
-                
+            
                 window.set_point_size(10.);
-                
+            
                 //start point
-                
-                let config_state_init = (model_sel.project_state_to_config)(model_sel.states_init.clone());
-                window.draw_point( &Point3::from( &config_state_init ),
+            
+                let config_state_init = model_sel.model.project_state_to_config(model_sel.states_init.clone());
+                window.draw_point( &to_point3( &config_state_init ),
                                     &Point3::new(0.,1.,0.) );
 
                 //dest point
-                let config_state_goal = (model_sel.project_state_to_config)(model_sel.states_goal.clone());
-                window.draw_point( &Point3::from( &config_state_goal ),
+                let config_state_goal = model_sel.model.project_state_to_config(model_sel.states_goal.clone());
+                window.draw_point( &to_point3( &config_state_goal ),
                                     &Point3::new(1.,0.,0.) );
 
                 obs_data.iter()
@@ -691,7 +1062,7 @@ fn main() {
                         c.append_translation( &Translation3::new( b.0, b.1, b.2 ) );
                         c.set_color(0.8, 0.8, 0.);
                     });
-                
+            
                 if !changed {
                     // use std::{thread, time};
                     // let t_s = time::Duration::from_millis(500);
@@ -701,4 +1072,18 @@ fn main() {
             }
         }
     }
+
+    Ok( 0 )
+}
+
+#[cfg(not(feature = "render"))]
+fn plan_render<TS,TC,TObs>( _pl: Box<PlannerBasic<TS,TC,TObs>>,
+                            _model_sel: &Param<TS,TC,TObs>,
+                            _obs: ParamObstacles<TObs>,
+                            _map_custom: Option<CustomMap>,
+                            _iter_batch: Option<u32>,
+                            _display_witness_info: bool,
+                            _config: &PlannerConfig,
+                            _matches: &ArgMatches ) -> Result<i32, PlannerError> where TS: States, TC: Control, TObs: States {
+    Err( PlannerError::InvalidParam( "planner built without feature render, run with --headless".to_string() ) )
 }
@@ -1,19 +1,44 @@
 
 use std::collections::{HashMap,HashSet};
+use std::marker::PhantomData;
+use std::str::FromStr;
 
-/// returns list of vertex coordinates, list of triangle vertex indices, max_x, max_y
+use crate::states::States;
+use crate::planner_param::{ParamObstacles,ObsVariant};
+use crate::error::{PlannerError,Result};
+
+use zpatial::mazth::{rbox::RecBox,triprism::TriPrism};
+
+use serde::{Serialize,Deserialize};
 
-pub fn load_map( path_ele: & str, path_nodes: &str ) -> (Vec<(f32,f32)>, Vec<[usize;3]>, f32, f32) {
+///obstacle box: [x, y, z, size]
+#[derive(Serialize, Deserialize)]
+pub struct ObsData(pub [f32; 4]);
+
+///obstacle file format
+#[derive(Serialize, Deserialize)]
+pub struct Obs {
+    pub obs: Vec<ObsData>,
+}
+
+///parses whitespace separated field ``idx`` on line ``n`` (starting from 0) of ``path``
+fn parse_field<T>( fields: &[&str], idx: usize, path: &str, n: usize, name: &str ) -> Result<T> where T: FromStr {
+    fields.get( idx )
+        .and_then(|x| x.parse::<T>().ok() )
+        .ok_or_else(|| PlannerError::parse( path, n + 1, format!("{} missing or not a number", name) ) )
+}
+
+/// returns list of vertex coordinates, list of triangle vertex indices, max_x, max_y
+pub fn load_map( path_ele: & str, path_nodes: &str ) -> Result<(Vec<(f32,f32)>, Vec<[usize;3]>, f32, f32)> {
     
     use std::fs::File;
-    use std::io::Read;
     use std::io::BufReader;
     use std::io::BufRead;
     
-    let mut f_ele = File::open(path_ele).expect("map file cannot be opened");
+    let f_ele = File::open(path_ele).map_err(|e| PlannerError::io( path_ele, e ) )?;
     let b_ele = BufReader::new(&f_ele);
     
-    let mut f_nodes = File::open(path_nodes).expect("map file cannot be opened");
+    let f_nodes = File::open(path_nodes).map_err(|e| PlannerError::io( path_nodes, e ) )?;
     let b_nodes = BufReader::new(&f_nodes);
 
     let mut num_nodes = 0;
@@ -27,29 +52,27 @@ pub fn load_map( path_ele: & str, path_nodes: &str ) -> (Vec<(f32,f32)>, Vec<[us
     let mut min_y = 99999.;
     
     for(n,line) in b_nodes.lines().enumerate() {
+
+        let a = line.map_err(|e| PlannerError::io( path_nodes, e ) )?;
+        let fields = a.split_whitespace().collect::<Vec<_>>();
+        
         if n == 0 {
             
-            num_nodes = line.unwrap()
-                .split_whitespace()
-                .nth(0).unwrap()
-                .parse::<usize>().expect("num_nodes");
+            num_nodes = parse_field::<usize>( &fields, 0, path_nodes, n, "num_nodes" )?;
             
             nodes = vec![ (0.,0.); num_nodes ];
             
         } else {
             
-            let a = line.expect("line");
-            
-            let idx = a.split_whitespace().nth(0).unwrap()
-                .parse::<usize>().expect("node index");
+            let idx = parse_field::<usize>( &fields, 0, path_nodes, n, "node index" )?;
 
-            assert!(idx<num_nodes);
+            if idx >= num_nodes {
+                return Err( PlannerError::parse( path_nodes, n + 1, format!("node index {} out of range of {} nodes", idx, num_nodes ) ) )
+            }
 
-            let x = a.split_whitespace().nth(1).unwrap()
-                .parse::<f32>().expect("node x");
+            let x = parse_field::<f32>( &fields, 1, path_nodes, n, "node x" )?;
             
-            let y = a.split_whitespace().nth(2).unwrap()
-                .parse::<f32>().expect("node y");
+            let y = parse_field::<f32>( &fields, 2, path_nodes, n, "node y" )?;
             
             max_x = if x > max_x { x } else { max_x };
             max_y = if y > max_y { y } else { max_y };
@@ -85,31 +108,33 @@ pub fn load_map( path_ele: & str, path_nodes: &str ) -> (Vec<(f32,f32)>, Vec<[us
     let mut tris : Vec<[usize;3]> = vec![];
     
     for(n,line) in b_ele.lines().enumerate() {
+
+        let a = line.map_err(|e| PlannerError::io( path_ele, e ) )?;
+        let fields = a.split_whitespace().collect::<Vec<_>>();
+        
         if n == 0 {
-            num_tris = line.unwrap()
-                .split_whitespace()
-                .nth(0).unwrap()
-                .parse::<usize>().expect("num_tris");
+            
+            num_tris = parse_field::<usize>( &fields, 0, path_ele, n, "num_tris" )?;
             
             tris = vec![ [0,0,0]; num_tris ];
             
         } else {
             
-            let a = line.expect("line");
-            
-            let idx = a.split_whitespace().nth(0).unwrap()
-                .parse::<usize>().expect("tri index");
+            let idx = parse_field::<usize>( &fields, 0, path_ele, n, "tri index" )?;
 
-            assert!(idx<num_tris);
+            if idx >= num_tris {
+                return Err( PlannerError::parse( path_ele, n + 1, format!("tri index {} out of range of {} triangles", idx, num_tris ) ) )
+            }
 
-            let n0 = a.split_whitespace().nth(1).unwrap()
-                .parse::<usize>().expect("tri n0");
+            let n0 = parse_field::<usize>( &fields, 1, path_ele, n, "tri n0" )?;
             
-            let n1 = a.split_whitespace().nth(2).unwrap()
-                .parse::<usize>().expect("tri n1");
+            let n1 = parse_field::<usize>( &fields, 2, path_ele, n, "tri n1" )?;
+
+            let n2 = parse_field::<usize>( &fields, 3, path_ele, n, "tri n2" )?;
 
-            let n2 = a.split_whitespace().nth(3).unwrap()
-                .parse::<usize>().expect("tri n2");
+            if let Some(x) = [n0,n1,n2].iter().find(|x| **x >= num_nodes ) {
+                return Err( PlannerError::parse( path_ele, n + 1, format!("tri vertex {} out of range of {} nodes", x, num_nodes ) ) )
+            }
 
             tris[idx] = [n0,n1,n2];
 
@@ -119,5 +144,343 @@ pub fn load_map( path_ele: & str, path_nodes: &str ) -> (Vec<(f32,f32)>, Vec<[us
         }
     }
     
-    ( nodes, tris, max_x, max_y )
+    Ok( ( nodes, tris, max_x, max_y ) )
+}
+
+///alternative to loading a obstacle file
+pub fn generate_obstacles<TObs>() -> ParamObstacles<TObs> where TObs: States {
+
+    use rand::Rng;
+    
+    //generate boxes for now
+    let boxes = {
+
+        let mut rng = rand::thread_rng();
+        
+        (0..1).map( |_| {
+
+            let size = rng.gen_range(0.01, 0.075);
+            let x = rng.gen_range(0.25, 0.75);
+            let y = rng.gen_range(0.25, 0.75);
+            RecBox::init( &[ x, y, 0. ], size ) //constant height obstacles
+        } ).collect::<Vec<_>>()
+    };
+    
+    ParamObstacles {
+        obstacles: ObsVariant::RBOX(boxes),
+        states_info: PhantomData,
+    }
+}
+
+pub fn load_obs_from_file<TObs>(f: &str) -> Result<ParamObstacles<TObs>> where TObs: States {
+
+    let s = std::fs::read_to_string( f ).map_err(|e| PlannerError::io( f, e ) )?;
+    let obs : Obs = serde_json::from_str( s.as_str() ).map_err(|e| PlannerError::json( f, e ) )?;
+    
+    let boxes = obs.obs.iter()
+        .map(|x| {
+            let coords = x.0;
+            RecBox::init( &[ coords[0] as _, //x
+                             coords[1] as _, //y
+                             coords[2] as _], //z
+                             coords[3] as _) //size
+        } ).collect::<Vec<_>>();
+    
+    Ok( ParamObstacles {
+        obstacles: ObsVariant::RBOX(boxes),
+        states_info: PhantomData,
+    } )
+}
+
+///returns obstacles, list of vertex coordinates, list of triangle vertex indices, max_x, max_y
+///
+///obstacle triangles of the map are extruded as triangular prisms, free space points are passed to the triangulation as holes
+pub fn load_custom_map<TObs>( path_nodes: &str, path_ele: &str ) -> Result<( ParamObstacles<TObs>,
+                                                                     Vec<(f32,f32)>,
+                                                                     Vec<[usize;3]>,
+                                                                     f32,
+                                                                     f32 )> where TObs: States {
+    
+    info!("loading map.");
+
+    let (verts,tris,max_x,max_y) = load_map(path_ele,path_nodes)?;
+
+    info!("loaded map.");
+    
+    info!("custom map triangle vert count: {}, triangle count: {}", verts.len(), tris.len() );
+
+    let triangle_prims = tris.iter()
+        .map(|x| {
+            
+            let v0 = verts[x[0]];
+            let v1 = verts[x[1]];
+            let v2 = verts[x[2]];
+
+            let height = 1.;
+            TriPrism::init( &[ v0.0 as _, v0.1 as _, -0.5,
+                               v1.0 as _, v1.1 as _, -0.5,
+                               v2.0 as _, v2.1 as _, -0.5 ], height )
+
+        }).collect::<Vec<_>>();
+    
+    let obs = ParamObstacles {
+        obstacles: ObsVariant::TRIPRISM(triangle_prims),
+        states_info: PhantomData,
+    };
+    
+    Ok( ( obs,
+          verts,
+          tris,
+          max_x,
+          max_y ) )
+}
+
+///transform custom grid map (movingai .map format) at ``f_in`` to poly file at ``f_out`` for Triangle
+pub fn map_to_poly( f_in: &str, f_out: &str ) -> Result<()> {
+
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::io::BufRead;
+    
+    let f = File::open(f_in).map_err(|e| PlannerError::io( f_in, e ) )?;
+    let b = BufReader::new(&f);
+    let mut h = 0;
+    let mut w = 0;
+    let mut idx_h = 0;
+
+    let mut m_orig : Vec<Vec<u8>>= vec![];
+
+    let mut free_space = HashSet::new();
+    
+    for(n,line) in b.lines().enumerate() {
+
+        let l = line.map_err(|e| PlannerError::io( f_in, e ) )?;
+        
+        if n == 0 { continue; } 
+        else if n == 1 {
+            h = parse_field::<usize>( &l.split_whitespace().collect::<Vec<_>>(), 1, f_in, n, "height" )?;
+        }else if n == 2 {
+            w = parse_field::<usize>( &l.split_whitespace().collect::<Vec<_>>(), 1, f_in, n, "width" )?;
+        }else if n == 3 { continue; }
+        else{
+            if idx_h >= h {
+                break;
+            }
+            
+            let row = l.chars().take(w)
+                .map(|x| if x == '.' {1} else {0}).collect::<Vec<_>>();
+            
+            if w != row.len() {
+                return Err( PlannerError::parse( f_in, n + 1, format!("row length {} less than map width {}", row.len(), w ) ) )
+            }
+            
+            m_orig.push(row);
+            
+            idx_h += 1;
+        }            
+    }
+
+    if m_orig.len() != h {
+        return Err( PlannerError::parse( f_in, m_orig.len() + 5, format!("row count {} less than map height {}", m_orig.len(), h ) ) )
+    }
+
+    info!("(h,w): ({},{})", h, w);
+
+    let mut m = m_orig.clone();
+    
+    //dilate free space of the map to avoid degenerate area for triangulation
+    for i in 0..h as i32 {
+        for j in 0..w as i32 {
+            if m_orig[i as usize][j as usize] == 1 {
+
+                if i > 0 && i < h as i32 -1 && j > 0 && j < w as i32 -1 {
+                    free_space.insert((i,j));
+                }
+                
+                let neighbour = [ (i-1,j),
+                                  (i+1,j),
+                                  (i,j-1),
+                                  (i,j+1),
+                                  (i-1,j-1),
+                                  (i+1,j+1),
+                                  (i-1,j+1),
+                                  (i+1,j-1),
+                ];
+
+                neighbour.iter()
+                    .filter(|(y,x)| *y >= 0 && *y < h as i32 && *x >= 0 && *x < w as i32 )
+                    .filter(|(y,x)| m_orig[*y as usize][*x as usize] == 0 )
+                    .for_each(|(y,x)| { m[*y as usize][*x as usize] = 1; } );
+                
+            }
+        }
+    }
+    
+    //get perimeter of free space
+    let mut candidates = HashSet::new();
+    
+    for i in 0..h as i32 {
+        for j in 0..w as i32 {
+            if m[i as usize][j as usize] == 1 {
+                
+                let neighbour = [ (i-1,j),
+                                  (i+1,j),
+                                  (i,j-1),
+                                  (i,j+1),
+                                  (i-1,j-1),
+                                  (i-1,j+1),
+                                  (i+1,j+1),
+                                  (i+1,j-1)];
+
+                let near_obstacle = neighbour.iter()
+                    .filter(|(y,x)| *y >= 0 && *y < h as i32 && *x >= 0 && *x < w as i32 )
+                    .any(|(y,x)| m[*y as usize][*x as usize] == 0 );
+                
+                if near_obstacle ||
+                    i == 0 || i == h as i32 -1 || //edge cases where free space hugs the border of the map
+                    j == 0 || j == w as i32 -1 {
+                    candidates.insert( (i,j) );
+                }
+            }
+        }
+    }
+
+    let mut m_out = vec![ vec![ 0; w ]; h ];
+    
+    candidates.iter().for_each(|(y,x)| { m_out[*y as usize][*x as usize] = 1; } );
+    
+    let mut used = HashSet::new();
+
+    let mut perimeters = vec![];
+    
+    for i in candidates.iter() {
+        
+        if used.contains(i) {
+            continue;
+        }
+
+        let mut cur = *i;
+        
+        used.insert(cur);
+
+        let mut active = vec![ cur ];
+
+        let mut last_length = active.len();
+        
+        loop {
+            
+            let(y,x) = cur;
+            
+            let neighbour = [ (y-1,x),
+                              (y+1,x),
+                              (y,x-1),
+                              (y,x+1) ];
+            
+            for n in neighbour.iter() {
+                if candidates.contains( n ) &&
+                    !used.contains( n ) {
+
+                        active.push( *n );
+                        used.insert( *n );
+                        cur = *n;
+                        break;
+                    }
+            }
+
+            let new_length = active.len();
+            
+            if new_length == last_length {
+                break;
+            }
+
+            last_length = new_length;
+        }
+        
+        perimeters.push( active );
+    }
+
+    info!("perimenters group count: {:?}", perimeters.len() );
+
+    perimeters.sort_unstable_by_key( |x| std::cmp::Reverse( x.len() ) );
+
+    perimeters = perimeters.iter().filter(|x| x.len() >= 3 ).cloned().collect();
+    
+    if perimeters.is_empty() {
+        return Err( PlannerError::parse( f_in, 1, "map has no free space perimeter" ) )
+    }
+
+    //get hole points
+    let holes = free_space.iter().collect::<Vec<_>>();
+
+    //add an out rectangle to contain the interior of the map
+    let outer_most = vec![ (-2i32, -2i32),
+                           (h as i32+2, -2i32),
+                           (h as i32+2, w as i32+2),
+                           (-2i32, w as i32 +2), ];
+    
+    perimeters.push( outer_most );
+    
+    let mut total_verts = 0;
+    let mut total_segments = 0;
+    
+    for i in perimeters.iter(){
+        total_segments += i.len();
+        total_verts += i.len();
+    }
+
+    use std::fmt::Write;
+
+    let mut out = String::new();
+    
+    writeln!( &mut out, "{} 2 0 0",total_verts ).unwrap();
+
+    let mut hash_verts = HashMap::new();
+    
+    let mut idx_vert = 0;
+    for i in perimeters.iter(){
+        for j in i.iter(){
+            let (y,x) = j;
+            writeln!( &mut out, "{} {} {}", idx_vert, x, y ).unwrap(); //x,y
+            hash_verts.insert( *j, idx_vert );
+            idx_vert += 1;
+        }
+    }
+
+    writeln!( &mut out, "{} 0",total_segments ).unwrap();
+
+    let mut idx_segment = 0;
+    for i in perimeters.iter(){
+        for j in 0..i.len() {
+            let v1 = i[j];
+            let v2 = i[(j+1)%i.len()];
+            let idx1 = hash_verts.get(&v1).unwrap();
+            let idx2 = hash_verts.get(&v2).unwrap();
+            writeln!( &mut out, "{} {} {}", idx_segment, idx1, idx2 ).unwrap();
+            idx_segment += 1;
+        }
+    }
+
+    writeln!( &mut out, "{}", holes.len() ).unwrap();
+
+    for (idx,(y,x)) in holes.iter().enumerate(){
+        writeln!( &mut out, "{} {} {}", idx, x, y ).unwrap();
+    }
+
+    std::fs::write( f_out, out ).map_err(|e| PlannerError::io( f_out, e ) )
+}
+
+#[test]
+fn test_load_map_malformed_node(){
+
+    let dir = std::env::temp_dir();
+    let path_nodes = dir.join("sample_planning_test_malformed.node");
+    let path_ele = dir.join("sample_planning_test_malformed.ele");
+    
+    std::fs::write( &path_nodes, "2 2 0 0\n0 0.0 0.0\n5 1.0 1.0\n" ).unwrap();
+    std::fs::write( &path_ele, "0 3 0\n" ).unwrap();
+
+    match load_map( path_ele.to_str().unwrap(), path_nodes.to_str().unwrap() ) {
+        Err( PlannerError::Parse { line, .. } ) => { assert_eq!( line, 3 ); },
+        _ => { panic!("expected parse error"); },
+    }
 }
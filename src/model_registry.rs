@@ -0,0 +1,43 @@
+//! Dynamical models selectable by name at runtime
+//!
+//! Each model has its own state, control and configuration space types, so the caller supplies a
+//! ``ModelVisitor`` that is instantiated for the selected combination together with the scenarios of the model.
+
+use std::collections::HashMap;
+
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::{Param,Integrator};
+use crate::prob_instances::{self,Scenario};
+use crate::{dynamics_dubins,dynamics_airplane,dynamics_reeds_shepp};
+use crate::error::{PlannerError,Result};
+
+///names of available models
+pub const MODELS: &[&str] = &[ "dubins", "airplane", "reeds_shepp" ];
+
+pub trait ModelVisitor {
+    type Output;
+    fn visit<TS,TC,TObs>( self,
+                          model: Param<TS,TC,TObs>,
+                          scenarios: HashMap< String, Scenario<TS> > ) -> Self::Output
+        where TS: States, TC: Control, TObs: States;
+}
+
+///loads model of given name and its scenarios from ``scenario_dir`` and passes them to the visitor
+pub fn dispatch<V>( name: &str, integrator: Integrator, scenario_dir: &str, visitor: V ) -> Result<V::Output> where V: ModelVisitor {
+    match name {
+        "dubins" => {
+            Ok( visitor.visit( dynamics_dubins::load_model( integrator ),
+                               prob_instances::load_dir( scenario_dir, name )? ) )
+        },
+        "airplane" => {
+            Ok( visitor.visit( dynamics_airplane::load_model( integrator ),
+                               prob_instances::load_dir( scenario_dir, name )? ) )
+        },
+        "reeds_shepp" => {
+            Ok( visitor.visit( dynamics_reeds_shepp::load_model( integrator ),
+                               prob_instances::load_dir( scenario_dir, name )? ) )
+        },
+        _ => { Err( PlannerError::ModelNotFound( name.to_string() ) ) },
+    }
+}
@@ -3,12 +3,13 @@
 //! This module allows saving and reteival of motion primitives.
 //! This requires transformation function to map back and forth between system states and reference frame for looking up motion primitives
 
-use std::collections::HashMap;
+use std::sync::Arc;
+
+use rand::{Rng,RngCore};
 
-use crate::rrt::rrt::RRT;
 use crate::states::States;
 use crate::control::Control;
-use crate::planner_param::{Param,ParamObstacles};
+use crate::system_model::SystemModel;
 
 extern crate mazth;
 
@@ -30,14 +31,8 @@ pub struct Motion <TS,TC> where TS: States, TC: Control {
 
 pub struct MoPrim <TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
-    ///measure of closeness in state space
-    pub ss_metric: fn(TS,TS)->f32,
-
-    ///transform of world to canonical frame of motion primitive lookup
-    pub xform: fn(TS,TS)->TS,
-
-    ///transform of canonical motion primitive lookup to world coordinate
-    pub xform_inv: fn(TS,TS)->TS,
+    ///provides measure of closeness in state space and transforms between world and canonical frame of motion primitive lookup
+    pub model: Arc<dyn SystemModel<TS,TC,TObs>>,
 
     ///saved lookup data
     pub lookup: Vec<Motion<TS,TC> >,
@@ -54,16 +49,13 @@ pub struct MoPrim <TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
 impl <TS,TC,TObs> MoPrim < TS,TC,TObs > where TS: States, TC: Control, TObs: States {
     
-    pub fn init( dist: fn(TS,TS)->f32,
-                 transform: fn(TS,TS)->TS,
-                 transform_inv: fn(TS,TS)->TS ) -> MoPrim< TS, TC, TObs > {
+    ///``model`` must provide motion primitive transforms
+    pub fn init( model: Arc<dyn SystemModel<TS,TC,TObs>> ) -> MoPrim< TS, TC, TObs > {
 
         use std::marker::PhantomData;
         
         MoPrim {
-            ss_metric: dist,
-            xform: transform,
-            xform_inv: transform_inv,
+            model: model,
             lookup: vec![],
             capacity: 1000,
             phantom_tc: PhantomData,
@@ -72,16 +64,14 @@ impl <TS,TC,TObs> MoPrim < TS,TC,TObs > where TS: States, TC: Control, TObs: Sta
     }
 
     /// add a motion to the lookup
-    pub fn add_motion( & mut self, q_start: TS, q_end: TS, u: TC, t: f32, c: f32 ){
+    pub fn add_motion( & mut self, q_start: TS, q_end: TS, u: TC, t: f32, c: f32, rng: &mut dyn RngCore ){
 
         //get q_end relative to the lookup frame of reference
-        let qq_end = (self.xform)( q_start, q_end );
+        let qq_end = self.model.motion_primitive_xform( q_start, q_end ).expect("motion primitive transform");
 
         //todo: algo for sparcity constraint, use randomize eviction for now
         if self.lookup.len() > self.capacity {
             
-            use rand::Rng;
-            let mut rng = rand::thread_rng();
             let idx : usize = rng.gen_range(0, self.lookup.len());
 
             self.lookup[idx] = Motion { q: qq_end,
@@ -102,11 +92,11 @@ impl <TS,TC,TObs> MoPrim < TS,TC,TObs > where TS: States, TC: Control, TObs: Sta
 
         //get q_query relative to the lookup frame of reference
         
-        let qq_query = (self.xform)( q_current, q_query );
+        let qq_query = self.model.motion_primitive_xform( q_current, q_query ).expect("motion primitive transform");
         
         self.lookup.iter()
             .filter_map(|x|{
-                if (self.ss_metric)(qq_query.clone(),x.q.clone()) > cost_threshold {
+                if self.model.ss_metric(qq_query.clone(),x.q.clone()) > cost_threshold {
                     None
                 } else {
                     Some( x.clone() )
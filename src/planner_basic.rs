@@ -1,12 +1,15 @@
 use crate::planner_param::Param;
 use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::event::PlannerEvent;
+use crate::error::{PlannerError,Result};
 use crate::planner::Planner;
+use crate::shortcut::{self,ShortcutResult};
 use crate::states::States;
 use crate::control::Control;
 
-use std::marker::PhantomData;
-use std::cell::RefCell;
-use rand::Rng;
+use rand::prelude::*;
+use rand::rngs::StdRng;
 
 extern crate pretty_env_logger;
 
@@ -16,31 +19,18 @@ use crate::rrt::*;
 use crate::rrt::rrt::RRT;
 use crate::planner_param::*;
 
-use crate::states::*;
-
-use zpatial::implement::bvh_median::Bvh;
-use zpatial::interface::i_spatial_accel::*;
-
-use zpatial::mazth::{rbox::RecBox,triprism::TriPrism};
-
-use zpatial::mazth::{
-    i_bound::{ IBound, BoundType },
-    i_shape::ShapeType,
-    bound::AxisAlignedBBox,
-    bound_sphere::BoundSphere,
-};
-
 pub struct PlannerBasic <TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
     param: Param <TS,TC,TObs>,
     param_obstacle: ParamObstacles<TObs>,
     states_cur: Option<TS>,
     trajectory: Vec<TObs>,
-    trajectory_edge: Vec<((TObs,TObs),u32)>,
-    trajectory_best: Vec<((TObs,TObs),u32)>,
+    trajectory_edge: Vec<(Vec<TObs>,u32)>,
+    trajectory_best: Vec<(Vec<TObs>,u32)>,
     witness_pairs: Vec<(TObs,TObs)>,
-    fini: bool,
-    rrt_tree: sst::SST<TS,TC,TObs>,
+    ///tree of the planner selected by config
+    rrt_tree: Box<dyn RRT<TS,TC,TObs>>,
+    config: PlannerConfig,
     
     stat_duration: f64,
     
@@ -52,31 +42,25 @@ pub struct PlannerBasic <TS,TC,TObs> where TS: States, TC: Control, TObs: States
 impl <TS,TC,TObs> PlannerBasic <TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub fn init( param: Param<TS,TC,TObs>,
                  param_obs: ParamObstacles<TObs>,
-                 param_tree: ParamTree ) -> PlannerBasic<TS,TC,TObs> {
+                 param_tree: ParamTree,
+                 config: PlannerConfig ) -> Result<PlannerBasic<TS,TC,TObs>> {
 
-        use zpatial::mazth::i_shape::IShape;
-        
-        let mut obs_tree = Bvh::init(10);
-
-        //get bounds as [(idx,aabb_bound)]
-        let bounds = match param_obs.obstacles {
-            ObsVariant::RBOX(ref x) => {
-                x.iter()
-                    .enumerate()
-                    .map(|x| (x.0, x.1.get_bound()) )
-                    .collect::<Vec<_>>()
+        let rrt_tree : Box<dyn RRT<TS,TC,TObs>> = match config.planner {
+            PlannerKind::Sst => {
+                Box::new( sst::SST::init( &param, param_obs.clone(), param_tree, config.clone() )? )
             },
-            ObsVariant::TRIPRISM(ref x) => {
-                x.iter()
-                    .enumerate()
-                    .map(|x| (x.0, x.1.get_bound()) )
-                    .collect::<Vec<_>>()
+            PlannerKind::Rrt => {
+                Box::new( kinorrt::KinoRRT::init( &param, param_obs.clone(), param_tree, config.clone(), false )? )
+            },
+            PlannerKind::RrtStar => {
+                Box::new( kinorrt::KinoRRT::init( &param, param_obs.clone(), param_tree, config.clone(), true )? )
+            },
+            PlannerKind::Kpiece => {
+                Box::new( kpiece::Kpiece::init( &param, param_obs.clone(), param_tree, config.clone() )? )
             },
         };
 
-        obs_tree.build_all( &bounds[..] ).is_ok();
-        
-        Self{
+        Ok( Self{
             param: param.clone(),
             param_obstacle: param_obs.clone(),
             states_cur: None,
@@ -84,18 +68,54 @@ impl <TS,TC,TObs> PlannerBasic <TS,TC,TObs> where TS: States, TC: Control, TObs:
             trajectory_edge: vec![],
             trajectory_best: vec![],
             witness_pairs: vec![],
-            fini: false,
-            rrt_tree: sst::SST::init( &param,
-                                       obs_tree, //contains proxy to obstacles
-                                       param_obs, //contains actual obstacles
-                                       param_tree ),
+            rrt_tree: rrt_tree,
+            config: config,
 
             trajectory_mo_prim_candidates: vec![],
 
             stat_duration: 0.,
 
             sampling_distr: vec![],
+        } )
+    }
+
+    pub fn config( & self ) -> &PlannerConfig {
+        &self.config
+    }
+
+    ///collision checks each edge of ``solution`` at the configured collision resolution or sim_delta if finer, returns index of the first edge in collision
+    pub fn check_solution( & self, solution: &Solution<TS,TC> ) -> Option<usize> {
+        self.rrt_tree.get_collision_checker().check_solution( &self.param, solution )
+    }
+
+    ///shortcuts and smooths ``solution`` for ``iterations`` attempts, randomness is seeded from config seed or entropy
+    pub fn shortcut_solution( & self, solution: &Solution<TS,TC>, iterations: u32 ) -> ShortcutResult<TS,TC> {
+        
+        let mut rng = match self.config.seed {
+            Some(x) => { StdRng::seed_from_u64( x ) },
+            _ => { StdRng::from_entropy() },
+        };
+        
+        shortcut::shortcut( &self.param, self.rrt_tree.get_collision_checker(), solution, iterations, &mut rng )
+    }
+
+    ///writes tree of the planner to ``path``, see rrt::checkpoint
+    pub fn save_checkpoint( & self, path: &str ) -> Result<()> {
+        self.rrt_tree.save_checkpoint( path )
+    }
+
+    ///replaces tree of the planner with checkpoint at ``path``, planning continues from it with the current config,
+    ///only supported by planner sst
+    pub fn load_checkpoint( & mut self, path: &str, param_tree: ParamTree ) -> Result<()> {
+        if self.config.planner != PlannerKind::Sst {
+            return Err( PlannerError::InvalidParam( format!("checkpoints not supported by planner {:?}", self.config.planner ) ) )
         }
+        self.rrt_tree = Box::new( sst::SST::load_checkpoint( &self.param,
+                                                             self.param_obstacle.clone(),
+                                                             param_tree,
+                                                             self.config.clone(),
+                                                             path )? );
+        Ok(())
     }
 }
 
@@ -107,8 +127,6 @@ impl <TS,TC,TObs> Planner<TS,TC,TObs> for PlannerBasic <TS,TC,TObs> where TS: St
     
     fn plan_iteration( & mut self, iteration: Option<u32> ) -> bool {
             
-        let mut count = 0;
-
         let mut timer = Timer::default();
         
         let changed = self.rrt_tree.iterate( iteration );
@@ -139,11 +157,11 @@ impl <TS,TC,TObs> Planner<TS,TC,TObs> for PlannerBasic <TS,TC,TObs> where TS: St
         self.trajectory.as_ref()
     }
 
-    fn get_trajectories_edges( & self ) -> &[((TObs,TObs),u32)] {
+    fn get_trajectories_edges( & self ) -> &[(Vec<TObs>,u32)] {
         self.trajectory_edge.as_ref()
     }
 
-    fn get_trajectory_best_edges( & self ) -> &[((TObs,TObs),u32)] {
+    fn get_trajectory_best_edges( & self ) -> &[(Vec<TObs>,u32)] {
         self.trajectory_best.as_ref()
     }
 
@@ -161,4 +179,186 @@ impl <TS,TC,TObs> Planner<TS,TC,TObs> for PlannerBasic <TS,TC,TObs> where TS: St
     fn get_sampling_distr( & self ) -> &[TObs] {
         self.sampling_distr.as_ref()
     }
+
+    fn get_stats( & self ) -> Stats {
+        Stats {
+            duration_ms: self.stat_duration,
+            ..self.rrt_tree.get_stats()
+        }
+    }
+
+    fn get_solution( & self ) -> Option<Solution<TS,TC>> {
+        self.rrt_tree.get_best_solution()
+    }
+
+    fn take_events( & mut self ) -> Vec<PlannerEvent> {
+        self.rrt_tree.take_events()
+    }
+}
+
+#[test]
+fn test_seed_reproducible(){
+
+    use crate::test_fixture;
+
+    let plan = |config: &PlannerConfig| {
+        let param = test_fixture::dubins( config, 300_000 );
+        let obs = test_fixture::obstacles_empty();
+        let mut planner = PlannerBasic::init( param, obs, ParamTree::default(), config.clone() ).expect("planner init");
+        planner.plan_iteration( Some(200) );
+        planner.get_trajectories().iter().map(|x| x.get_vals() ).collect::<Vec<_>>()
+    };
+
+    let config = PlannerConfig { seed: Some(7), ..PlannerConfig::default() };
+
+    let a = plan( &config );
+    let b = plan( &config );
+    
+    assert!( a.len() > 1 );
+    assert_eq!( a, b );
+
+    //candidates of batch propagation are evaluated in parallel
+    let config = PlannerConfig { seed: Some(7), batch_propagate_sample: true, ..PlannerConfig::default() };
+
+    let a = plan( &config );
+    let b = plan( &config );
+    
+    assert!( a.len() > 1 );
+    assert_eq!( a, b );
+}
+
+#[test]
+fn test_anytime_improves(){
+
+    use crate::test_fixture;
+
+    let config = PlannerConfig { seed: Some(3), stop_on_solution: false, ..PlannerConfig::default() };
+    
+    let param = test_fixture::dubins( &config, 1_500 );
+    let obs = test_fixture::obstacles_empty();
+    let mut planner = PlannerBasic::init( param, obs, ParamTree::default(), config ).expect("planner init");
+
+    let mut costs = vec![];
+    while planner.plan_iteration( Some(500) ) {
+        for e in planner.take_events() {
+            match e {
+                PlannerEvent::NewBestSolution { cost, cost_previous, .. } => {
+                    assert_eq!( cost_previous, costs.last().cloned() );
+                    costs.push( cost );
+                },
+            }
+        }
+    }
+
+    let stats = planner.get_stats();
+    
+    assert_eq!( stats.stop_reason, Some(StopReason::IterationBound) );
+    assert!( costs.len() > 1 );
+    assert!( costs.windows(2).all(|x| x[1] < x[0] ) );
+    assert_eq!( stats.cost, costs.last().cloned() );
+    assert_eq!( planner.get_solution().map(|x| x.cost ), stats.cost );
+}
+
+#[test]
+fn test_edge_substates(){
+
+    use crate::test_fixture;
+
+    //keeps growing past the first solution
+    let config = PlannerConfig { seed: Some(7), edge_substates: true, stop_on_solution: false, ..PlannerConfig::default() };
+    
+    let param = test_fixture::dubins( &config, 300 );
+    let sim_delta = param.sim_delta;
+    let obs = test_fixture::obstacles_empty();
+    //propagations of 3 simulation steps
+    let param_tree = ParamTree { prop_delta_low: 3., prop_delta_high: 3., ..ParamTree::default() };
+    let mut planner = PlannerBasic::init( param, obs, param_tree, config ).expect("planner init");
+    planner.plan_iteration( Some(500) );
+
+    let edges = planner.get_trajectories_edges();
+    
+    assert_eq!( planner.get_stats().stop_reason, Some(StopReason::IterationBound) );
+    assert!( edges.len() > 100 );
+    assert!( edges.iter().all(|x| x.0.len() >= 2 ) );
+    assert!( edges.iter().any(|x| x.0.len() > 2 ) );
+
+    //consecutive sub-states are at most sim_delta apart at unit speed
+    assert!( edges.iter().all(|x| x.0.windows(2).all(|w| {
+        let a = w[0].get_vals();
+        let b = w[1].get_vals();
+        ( (a[0]-b[0]).powi(2) + (a[1]-b[1]).powi(2) ).sqrt() <= sim_delta + 1e-4
+    }) ) );
+}
+
+#[test]
+fn test_steer_to_goal(){
+
+    use crate::test_fixture;
+    use crate::goal::GoalRegion;
+    use crate::collision;
+    use crate::states::States3D;
+
+    //tight goal region is reached by integrating the curve's controls accurately
+    let config = PlannerConfig { seed: Some(4), steer_to_goal: Some(0.3), runge_kutta: true, ..PlannerConfig::default() };
+    
+    let mut param = test_fixture::dubins( &config, 20_000 );
+    param.states_goal = States3D([0.8, 0.1, 1.5]);
+    param.goal_region = GoalRegion::Tolerance { position: 0.001, heading: Some(0.001) };
+    let goal = param.states_goal.clone();
+    let resolution = config.collision_step( param.sim_delta );
+    let model = param.model.clone();
+    let obs = test_fixture::obstacles_empty();
+    let mut planner = PlannerBasic::init( param, obs, ParamTree::default(), config ).expect("planner init");
+
+    while planner.plan_iteration( Some(500) ) {}
+
+    assert_eq!( planner.get_stats().stop_reason, Some(StopReason::SolutionFound) );
+
+    //exact goal heading is reached by a dubins curve
+    let sol = planner.get_solution().expect("no solution");
+    let end = sol.states.last().unwrap();
+    assert!( ( end.0[0] - goal.0[0] ).abs() < 1e-3 && ( end.0[1] - goal.0[1] ).abs() < 1e-3 );
+    assert!( ( end.0[2] - goal.0[2] ).abs() < 1e-3 );
+    assert!( planner.get_trajectory_best_edges().iter().any(|x| x.1 == 2 ) );
+
+    //replaying controls reproduces the solution, steered edges included
+    let replay = collision::integrate_edges( &*model,
+                                             sol.states[0].clone(),
+                                             sol.controls.iter().cloned().zip( sol.durations.iter().cloned() ).collect(),
+                                             resolution );
+    assert_eq!( replay.len(), sol.states.len() - 1 );
+    for ((_,_,sub),s) in replay.iter().zip( sol.states[1..].iter() ) {
+        assert_eq!( sub.last().unwrap().0, s.0 );
+    }
+}
+
+#[test]
+fn test_planner_kind(){
+
+    use crate::test_fixture;
+
+    for kind in [ PlannerKind::Rrt, PlannerKind::RrtStar, PlannerKind::Kpiece ].iter() {
+
+        let config = PlannerConfig { seed: Some(5), planner: *kind, ..PlannerConfig::default() };
+
+        //solutions have to go around the box between start and goal
+        let param = test_fixture::dubins( &config, 10_000 );
+        let obs = test_fixture::obstacles_between();
+        let mut planner = PlannerBasic::init( param, obs, ParamTree::default(), config ).expect("planner init");
+
+        while planner.plan_iteration( Some(500) ) {}
+
+        assert_eq!( planner.get_stats().stop_reason, Some(StopReason::SolutionFound) );
+        assert!( planner.get_witness_pairs().is_empty() );
+
+        let sol = planner.get_solution().expect("no solution");
+        assert_eq!( planner.check_solution( &sol ), None );
+        assert!( planner.load_checkpoint( "unused", ParamTree::default() ).is_err() );
+    }
+
+    //motion primitives are specific to sst
+    let config = PlannerConfig { planner: PlannerKind::Rrt, motion_primitives: true, ..PlannerConfig::default() };
+    let param = test_fixture::dubins( &config, 20_000 );
+    let obs = test_fixture::obstacles_empty();
+    assert!( PlannerBasic::init( param, obs, ParamTree::default(), config ).is_err() );
 }
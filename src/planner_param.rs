@@ -1,40 +1,92 @@
 use std::marker::PhantomData;
 use std::fmt;
+use std::sync::Arc;
 
 use crate::states::States;
 use crate::control::Control;
+use crate::system_model::SystemModel;
+use crate::goal::GoalRegion;
+use crate::cost::CostFunction;
+use crate::validity::StateConstraint;
+use crate::error::{PlannerError,Result};
 
 extern crate mazth;
 
-use zpatial::mazth::i_shape::IShape;
 use zpatial::mazth::{rbox::RecBox,triprism::TriPrism};
 
+use serde::{Serialize,Deserialize};
+
+///budget of a planning run
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum StopCondition {
-    Iterations(i32),
-    TimeMilliSeconds(i32),
+    Iterations(u32),
+    TimeMilliSeconds(u32),
+    ///iterations and milliseconds, whichever runs out first
+    IterationsOrTime(u32,u32),
+}
+
+///reason a planning run stopped
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopReason {
+    SolutionFound,
+    IterationBound,
+    TimeBound,
+    ///stopped by a portfolio planner after another worker found a solution
+    Cancelled,
+}
+
+impl StopCondition {
+
+    ///returns the reason for stopping if the budget is exhausted after ``iterations`` taking ``time_ms``
+    pub fn exhausted( & self, iterations: u32, time_ms: f64 ) -> Option<StopReason> {
+        
+        let ( iter_bound, time_bound ) = match *self {
+            StopCondition::Iterations(x) => { ( Some(x), None ) },
+            StopCondition::TimeMilliSeconds(x) => { ( None, Some(x) ) },
+            StopCondition::IterationsOrTime(x,y) => { ( Some(x), Some(y) ) },
+        };
+
+        if iter_bound.map_or( false, |x| iterations >= x ) {
+            Some( StopReason::IterationBound )
+        } else if time_bound.map_or( false, |x| time_ms >= x as f64 ) {
+            Some( StopReason::TimeBound )
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone,Debug)]
 pub struct Param <T, C, TObs> where T: States, C: Control, TObs: States {
     // pub memory_limit: Option<i32>,
-    pub stop_cond: fn(T/*system state*/,TObs/*config state*/,T/*desired state*/)->bool,
     pub states_init: T,
     pub states_goal: T,
-    pub dynamics: fn(T,C,f32)->T, //uses state space of system
-    pub project_state_to_config: fn(T)->TObs,
     pub sim_delta: f32, //to be used as simulation step size
-    pub param_sampler: fn(f32)->C, //sampling in parameter space
-    pub ss_sampler: fn()->T, //sampleing in state space
-    pub ss_metric: fn(T,T)->f32, //distance function in state space
-    pub cs_metric: fn(TObs, TObs) -> f32, //estimated cloness ness in configuration space
-    pub iterations_bound: u32,
+    pub stop_condition: StopCondition,
+
+    ///states accepted as reaching states_goal
+    pub goal_region: GoalRegion<T>,
 
-    //optional, but would ggive error if running motion_primitives feature without providing functions
-    pub motion_primitive_xform: Option<fn(T,T)->T>,
-    pub motion_primitive_xform_inv: Option<fn(T,T)->T>,
+    ///dynamics, samplers and metrics of the system
+    pub model: Arc<dyn SystemModel<T,C,TObs>>,
 
-    pub ss_add: fn(T,T)->T,
-    pub ss_mul: fn(T,f32)->T,
+    ///edge cost minimised by the planner
+    pub cost: Arc<dyn CostFunction<T,C,TObs>>,
+
+    ///constraints on propagated states in addition to SystemModel::state_valid
+    pub constraints: Vec<StateConstraint<T>>,
+}
+
+impl<T,C,TObs> Param<T,C,TObs> where T: States, C: Control, TObs: States {
+
+    ///true if all ``states`` satisfy SystemModel::state_valid and ``constraints``
+    pub fn states_valid( & self, states: &[T] ) -> bool {
+        let model = &*self.model;
+        states.iter().all(|x| model.state_valid( x ) &&
+                          self.constraints.iter().all(|c| c.satisfied( model, x ) ) )
+    }
 }
 
 #[derive(Clone,Debug)]
@@ -57,13 +109,18 @@ impl<T,C,TObs> fmt::Display for Param <T, C, TObs> where T: States, C: Control,
             .field("states_init", &format!("{:?}",&self.states_init) )
             .field("states_goal", &format!("{:?}",&self.states_goal) )
             .field("sim_delta", &self.sim_delta )
-            .field("iterations_bound", &self.iterations_bound )
+            .field("stop_condition", &self.stop_condition )
+            .field("goal_region", &self.goal_region )
+            .field("model", &format!("{:?}",&self.model) )
+            .field("cost", &format!("{:?}",&self.cost) )
+            .field("constraints", &self.constraints )
             .finish()
     }
 }
 
 
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(default)]
 pub struct ParamTree {
     pub delta_v: f32,
     pub delta_s: f32,
@@ -94,3 +151,194 @@ impl fmt::Display for ParamTree {
             .finish()
     }
 }
+
+///numerical integration scheme used by the dynamics models for propagation
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub enum Integrator {
+    Euler,
+    RungeKutta4,
+}
+
+///tree planner run by PlannerBasic
+#[derive(Clone,Copy,Debug,PartialEq,Serialize,Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlannerKind {
+    ///stable sparse rrt, see rrt::sst
+    Sst,
+    ///kinodynamic rrt: nearest node, random control, no pruning, see rrt::kinorrt
+    Rrt,
+    ///kinodynamic rrt with lowest cost parent in a neighbourhood and rewiring with the model's steering function, see rrt::kinorrt
+    RrtStar,
+    ///expansion from boundary cells of a config space projection grid, see rrt::kpiece
+    Kpiece,
+}
+
+impl std::str::FromStr for PlannerKind {
+
+    type Err = PlannerError;
+    
+    fn from_str( name: &str ) -> Result<PlannerKind> {
+        match name {
+            "sst" => { Ok( PlannerKind::Sst ) },
+            "rrt" => { Ok( PlannerKind::Rrt ) },
+            "rrt_star" => { Ok( PlannerKind::RrtStar ) },
+            "kpiece" => { Ok( PlannerKind::Kpiece ) },
+            _ => { Err( PlannerError::InvalidParam( format!( "unknown planner {}, expected one of {:?}", name, PLANNERS ) ) ) },
+        }
+    }
+}
+
+///names of planners parsed by PlannerKind
+pub const PLANNERS: &[&str] = &[ "sst", "rrt", "rrt_star", "kpiece" ];
+
+///runtime switches for planner behaviour, settable from command line or a json config file
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(default)]
+pub struct PlannerConfig {
+    ///use motion primitive lookup for propagation toward goal
+    pub motion_primitives: bool,
+    ///save accepted motion primitive candidates for display
+    pub mo_prim_debug: bool,
+    ///config space distance to goal under which motion primitive lookup is attempted
+    pub mo_prim_thresh: f32,
+    ///use 4th order runge-kutta for propagation, defaults to Euler stepping
+    pub runge_kutta: bool,
+    pub disable_pruning: bool,
+    ///use proportional to log(# nodes) for nearest neighbour query, defaults to sqrt(# nodes)
+    pub nn_sample_log: bool,
+    ///use linear nearest neighbour query
+    pub nn_naive: bool,
+    ///default is active for witness discovery rate of < 10% of recent iterations
+    pub disable_witness_disturbance: bool,
+    ///frontier node selection, 10 samples, 50%
+    pub state_propagate_sample: bool,
+    ///control propagation selection, 10 samples, 50%, applicable if nn_naive is NOT enabled
+    pub batch_propagate_sample: bool,
+    ///use importance sampling for optimization after an initial feasible path is found
+    pub path_optimize: bool,
+    ///seed for random number generation of the planner, identical seeds give identical trees, defaults to entropy
+    pub seed: Option<u64>,
+    ///end the run when the first solution is found, otherwise run in anytime mode, improving the solution until stop_condition of Param is met
+    pub stop_on_solution: bool,
+    ///probability of drawing the seed sample from the goal region instead of the state space or mixture model
+    pub goal_bias: f32,
+    ///radius of goal region ball around goal position for goal biased sampling, defaults to the ball inscribed in goal_region of Param
+    pub goal_bias_radius: Option<f32>,
+    ///sample heading uniformly in goal region
+    pub goal_bias_ignore_heading: bool,
+    ///maximum propagation duration between collision checked sub-states, defaults to sim_delta of Param
+    pub collision_resolution: Option<f32>,
+    ///collision check only the chord between end points of each propagation, ignores collision_resolution
+    pub collision_chord_only: bool,
+    ///keep integrated states of each edge at sim_delta resolution for rendering and solution export
+    pub edge_substates: bool,
+    ///shortcutting and smoothing attempts on the final solution, 0 disables post-processing
+    pub shortcut_iterations: u32,
+    ///config space distance to goal under which new nodes are connected exactly to the goal with the model's steering function,
+    ///disabled if None
+    pub steer_to_goal: Option<f32>,
+    ///tree planner, defaults to sst
+    pub planner: PlannerKind,
+    ///edge length of cells of the position projection grid of planner kpiece
+    pub kpiece_cell_size: f32,
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        PlannerConfig {
+            motion_primitives: false,
+            mo_prim_debug: false,
+            mo_prim_thresh: 0.25,
+            runge_kutta: false,
+            disable_pruning: false,
+            nn_sample_log: false,
+            nn_naive: false,
+            disable_witness_disturbance: false,
+            state_propagate_sample: false,
+            batch_propagate_sample: false,
+            path_optimize: false,
+            seed: None,
+            stop_on_solution: true,
+            goal_bias: 0.,
+            goal_bias_radius: None,
+            goal_bias_ignore_heading: false,
+            collision_resolution: None,
+            collision_chord_only: false,
+            edge_substates: false,
+            shortcut_iterations: 0,
+            steer_to_goal: None,
+            planner: PlannerKind::Sst,
+            kpiece_cell_size: 0.025,
+        }
+    }
+}
+
+impl PlannerConfig {
+
+    pub fn load_from_file( path: &str ) -> Result<PlannerConfig> {
+        
+        let s = std::fs::read_to_string( path ).map_err(|e| PlannerError::io( path, e ) )?;
+        serde_json::from_str( s.as_str() ).map_err(|e| PlannerError::json( path, e ) )
+    }
+
+    ///resolution of the collision checker for a simulation step of ``sim_delta``, None if only chords are checked
+    pub fn collision_step( & self, sim_delta: f32 ) -> Option<f32> {
+        if self.collision_chord_only {
+            None
+        } else {
+            Some( self.collision_resolution.unwrap_or( sim_delta ) )
+        }
+    }
+
+    pub fn integrator( & self ) -> Integrator {
+        if self.runge_kutta {
+            Integrator::RungeKutta4
+        } else {
+            Integrator::Euler
+        }
+    }
+}
+
+impl fmt::Display for PlannerConfig {
+   
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        
+        f.debug_struct("PlannerConfig")
+            .field("motion_primitives", &self.motion_primitives )
+            .field("mo_prim_debug", &self.mo_prim_debug )
+            .field("mo_prim_thresh", &self.mo_prim_thresh )
+            .field("runge_kutta", &self.runge_kutta )
+            .field("disable_pruning", &self.disable_pruning )
+            .field("nn_sample_log", &self.nn_sample_log )
+            .field("nn_naive", &self.nn_naive )
+            .field("disable_witness_disturbance", &self.disable_witness_disturbance )
+            .field("state_propagate_sample", &self.state_propagate_sample )
+            .field("batch_propagate_sample", &self.batch_propagate_sample )
+            .field("path_optimize", &self.path_optimize )
+            .field("seed", &self.seed )
+            .field("stop_on_solution", &self.stop_on_solution )
+            .field("goal_bias", &self.goal_bias )
+            .field("goal_bias_radius", &self.goal_bias_radius )
+            .field("goal_bias_ignore_heading", &self.goal_bias_ignore_heading )
+            .field("collision_resolution", &self.collision_resolution )
+            .field("collision_chord_only", &self.collision_chord_only )
+            .field("edge_substates", &self.edge_substates )
+            .field("shortcut_iterations", &self.shortcut_iterations )
+            .field("steer_to_goal", &self.steer_to_goal )
+            .field("planner", &self.planner )
+            .field("kpiece_cell_size", &self.kpiece_cell_size )
+            .finish()
+    }
+}
+
+#[test]
+fn test_stop_condition_exhausted(){
+
+    let c = StopCondition::IterationsOrTime( 100, 50 );
+    
+    assert_eq!( c.exhausted( 10, 10. ), None );
+    assert_eq!( c.exhausted( 100, 10. ), Some(StopReason::IterationBound) );
+    assert_eq!( c.exhausted( 10, 50. ), Some(StopReason::TimeBound) );
+    assert_eq!( StopCondition::TimeMilliSeconds( 50 ).exhausted( 1_000_000, 10. ), None );
+    assert_eq!( StopCondition::Iterations( 100 ).exhausted( 10, 1e9 ), None );
+}
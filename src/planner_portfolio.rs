@@ -0,0 +1,141 @@
+//! Portfolio of independent SST trees planned in parallel
+//!
+//! Workers differ by seed and / or config. With stop_on_solution, all workers are cancelled once one of them finds a solution,
+//! otherwise (anytime mode) each worker runs until the stop condition of Param. The lowest cost solution over workers is returned.
+
+use std::sync::atomic::{AtomicBool,Ordering};
+
+use rayon::prelude::*;
+use serde::{Serialize,Deserialize};
+
+use crate::planner_param::{Param,ParamObstacles,ParamTree,PlannerConfig,PlannerKind,StopReason};
+use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::error::{PlannerError,Result};
+use crate::states::States;
+use crate::control::Control;
+use crate::rrt::sst::SST;
+use crate::rrt::rrt::RRT;
+
+///iterations of a worker between checks for cancellation
+const WORKER_BATCH: u32 = 100;
+
+pub struct PortfolioPlanner<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+    workers: Vec<SST<TS,TC,TObs>>,
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States and Control
+pub struct PortfolioResult<TS,TC> where TS: States, TC: Control {
+    ///index of the worker with the lowest cost solution
+    pub best_worker: Option<usize>,
+    pub solution: Option<Solution<TS,TC>>,
+    ///statistics of each worker, in worker order
+    pub workers: Vec<Stats>,
+}
+
+impl <TS,TC,TObs> PortfolioPlanner<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    ///one worker per config of ``configs``, workers are SST trees regardless of PlannerConfig::planner
+    pub fn init( param: Param<TS,TC,TObs>,
+                 param_obs: ParamObstacles<TObs>,
+                 param_tree: ParamTree,
+                 configs: Vec<PlannerConfig> ) -> Result<PortfolioPlanner<TS,TC,TObs>> {
+
+        if let Some(c) = configs.iter().find(|c| c.planner != PlannerKind::Sst ) {
+            return Err( PlannerError::InvalidParam( format!("portfolio does not support planner {:?}", c.planner ) ) )
+        }
+
+        let workers = configs.into_iter()
+            .map(|c| SST::init( &param, param_obs.clone(), param_tree.clone(), c ) )
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok( PortfolioPlanner { workers: workers } )
+    }
+
+    ///``n`` workers with ``config``, worker i is seeded with seed + i if config has a seed, from entropy otherwise
+    pub fn init_seeded( param: Param<TS,TC,TObs>,
+                        param_obs: ParamObstacles<TObs>,
+                        param_tree: ParamTree,
+                        config: PlannerConfig,
+                        n: usize ) -> Result<PortfolioPlanner<TS,TC,TObs>> {
+
+        let configs = (0..n).map(|i| PlannerConfig { seed: config.seed.map(|x| x.wrapping_add( i as u64 ) ),
+                                                     ..config.clone() } )
+            .collect();
+
+        Self::init( param, param_obs, param_tree, configs )
+    }
+
+    pub fn num_workers( & self ) -> usize {
+        self.workers.len()
+    }
+
+    ///runs all workers in parallel until they stop, returns the best solution and per worker statistics
+    pub fn plan( & mut self ) -> PortfolioResult<TS,TC> {
+
+        let cancel = AtomicBool::new( false );
+
+        self.workers.par_iter_mut().for_each(|w| {
+
+            while !cancel.load( Ordering::Relaxed ) {
+                if !w.iterate( Some(WORKER_BATCH) ) {
+                    break
+                }
+            }
+
+            match w.stop_reason {
+                Some(StopReason::SolutionFound) => { cancel.store( true, Ordering::Relaxed ); },
+                None => { w.stop_reason = Some( StopReason::Cancelled ); },
+                _ => {},
+            }
+        });
+
+        self.result()
+    }
+
+    ///best solution and per worker statistics of the workers' current state
+    pub fn result( & self ) -> PortfolioResult<TS,TC> {
+
+        let solutions = self.workers.iter().map(|w| w.get_best_solution() ).collect::<Vec<_>>();
+
+        //lowest cost, first worker on ties
+        let best_worker = solutions.iter().enumerate()
+            .filter_map(|(i,x)| x.as_ref().map(|s| (i,s.cost) ) )
+            .fold( None, |acc: Option<(usize,f32)>, (i,c)| match acc {
+                Some((_,c_best)) if c_best <= c => { acc },
+                _ => { Some((i,c)) },
+            })
+            .map(|(i,_)| i );
+
+        PortfolioResult {
+            best_worker: best_worker,
+            solution: best_worker.and_then(|i| solutions[i].clone() ),
+            workers: self.workers.iter().map(|w| w.get_stats() ).collect(),
+        }
+    }
+}
+
+#[test]
+fn test_portfolio(){
+
+    use crate::test_fixture;
+
+    let config = PlannerConfig { seed: Some(11), ..PlannerConfig::default() };
+
+    let param = test_fixture::dubins( &config, 3_000 );
+    let obs = test_fixture::obstacles_empty();
+
+    let mut portfolio = PortfolioPlanner::init_seeded( param, obs, ParamTree::default(), config, 3 ).expect("portfolio init");
+    assert_eq!( portfolio.num_workers(), 3 );
+
+    let r = portfolio.plan();
+
+    assert_eq!( r.workers.len(), 3 );
+
+    let best = r.best_worker.expect("no solution");
+    assert_eq!( r.workers[best].stop_reason, Some(StopReason::SolutionFound) );
+    assert_eq!( r.solution.map(|x| x.cost ), r.workers[best].cost );
+
+    assert!( r.workers.iter().all(|x| x.stop_reason.is_some() ) );
+}
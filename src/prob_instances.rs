@@ -1,404 +1,208 @@
+//! Problem instances
+//!
+//! Scenarios are json files, typically kept together in a directory (see scenarios/), eg:
+//! {
+//!   "name": "obs3",
+//!   "model": "dubins",
+//!   "start": [ 0.2, 0.1, 0.0 ],
+//!   "goal": [ 0.8, 0.8, 0.0 ],
+//!   "tree": { "delta_v": 0.12, "delta_s": 0.07, "prop_delta_low": 0.1, "prop_delta_high": 1.0 },
+//!   "sim_step": 0.06,
+//!   "iterations": 250000,
+//!   "map": { "obs": "obstacles/obs3.txt" }
+//! }
+
 use std::collections::HashMap;
+use std::path::{Path,PathBuf};
+
+use serde::{Serialize,Deserialize};
 
 use crate::states::*;
 use crate::planner_param::ParamTree;
+use crate::goal::GoalRegion;
+use crate::cost::CostTerm;
+use crate::validity::StateConstraint;
+use crate::error::{PlannerError,Result};
 
-use std::f32::consts::PI;
-
-/// returns list of instance setup:
-/// init state space, goal config space,
-/// delta_s, delta_v, monte carlo prop time scale bounds
-/// propagation step (optional, defaults to dynamics file setting)
-/// iteration upper bound (optional, default to commandline if not provided)
-/// map_name (optional, default to commandline input)
+///default directory of scenario files
+pub const SCENARIO_DIR: &str = "scenarios";
 
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum MapPath {
-    Game( (& 'static str,& 'static str) ), //*.node path, *.ele path)
-    Obs( (& 'static str) ), //obstable map path
+    Game { nodes: String, ele: String }, //*.node path, *.ele path
+    Obs(String), //obstable map path
 }
 
-pub fn load_3d_3d() -> HashMap< & 'static str, (States3D, States3D, ParamTree, Option<f32>, Option<u32>, Option<MapPath> )> {
-    
-    let mut hm = HashMap::new();
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States
+pub struct Scenario<TS> where TS: States {
+
+    ///lookup name, defaults to file name without extension
+    #[serde(default)]
+    pub name: String,
+
+    ///dynamical model name, see model_registry::MODELS
+    pub model: String,
+
+    ///init state space
+    pub start: TS,
+
+    ///goal state space
+    pub goal: TS,
+
+    ///states accepted as reaching goal (optional, defaults to model setting), see goal.rs
+    #[serde(default)]
+    pub goal_region: Option<GoalRegion<TS>>,
+
+    ///constraints on propagated states in addition to model validity (optional), see validity.rs
+    #[serde(default)]
+    pub constraints: Vec<StateConstraint<TS>>,
+
+    ///weighted blend of edge costs (optional, defaults to duration), see cost.rs
+    #[serde(default)]
+    pub cost: Option<Vec<CostTerm>>,
+
+    ///delta_s, delta_v, monte carlo prop time scale bounds
+    #[serde(default)]
+    pub tree: ParamTree,
+
+    ///propagation step (optional, defaults to dynamics file setting)
+    #[serde(default)]
+    pub sim_step: Option<f32>,
 
-    //randomly generated boxes
-    
-    hm.insert("obs3", ( States3D([0.2, 0.1, 0.]),
-                        States3D([0.8, 0.8, 0.]),
-                        ParamTree {
-                            delta_s: 0.07,
-                            delta_v: 0.12,
-                            // delta_s: 0.05,
-                            // delta_v: 0.1,
-                            // delta_s: 0.0001,
-                            // delta_v: 0.0002,
-                            // delta_s: 0.003,
-                            // delta_v: 0.006,
-                            // prop_delta_low: 0.05,
-                            // prop_delta_high: 1.,
-                            prop_delta_low: 0.1,
-                            prop_delta_high: 1.,   
-                        },
-                        Some(0.06),
-                        // Some(0.015),
-                        Some(250_000),
-                        Some(MapPath::Obs(&"obstacles/obs3.txt")),
-    ) );
-
-    hm.insert("obs_sparse", ( States3D([0.2, 0.1, 0.]),
-                              States3D([0.8, 0.8, 0.]),
-                              ParamTree {
-                                  delta_s: 0.02,
-                                  delta_v: 0.04,
-                                  prop_delta_low: 0.1,
-                                  prop_delta_high: 1.,   
-                              },
-                              Some(0.03),
-                              Some(250_000),
-                              Some(MapPath::Obs(&"obstacles/obs_sparse.txt")),
-    ) );
-
-    //dragon age files
-
-    hm.insert("den005d", ( States3D([0.5, 0.92, 0.]),
-                           States3D([0.3, 0.037, 0.]),
-                           ParamTree {
-                               delta_s: 0.003,
-                               delta_v: 0.006,
-                               prop_delta_low: 0.05,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.065),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/den005d.1.node",
-                                               &"maps_custom/dragon_age/poly/den005d.1.ele" ) ) ),
-    ) );
-
-    hm.insert("den501d", ( States3D([0.3, 0.96, -PI]),
-                           States3D([0.4, 0.31, 0.]),
-                           ParamTree {
-                               delta_s: 0.005,
-                               delta_v: 0.01,
-                               prop_delta_low: 0.05,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.065),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/den501d.1.node",
-                                               &"maps_custom/dragon_age/poly/den501d.1.ele" ) ) ),
-    ) );
-    
-    hm.insert("den901d", ( States3D([0.17, 0.6, 0.]),
-                           States3D([0.65, 0.85, PI]),
-                           ParamTree {
-                               delta_s: 0.003,
-                               delta_v: 0.006,
-                               prop_delta_low: 0.25,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.065),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/den901d.1.node",
-                                               &"maps_custom/dragon_age/poly/den901d.1.ele" ) ) ),
-    ) );
-
-    hm.insert("hrt201d", ( States3D([0.11, 0.11, 0.]),
-                           States3D([0.83, 0.78, 0.]),
-                           ParamTree {
-                               delta_s: 0.002,
-                               delta_v: 0.004,
-                               prop_delta_low: 0.25,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.065),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/hrt201d.1.node",
-                                               &"maps_custom/dragon_age/poly/hrt201d.1.ele" ) ) ),
-    ) );
-
-    hm.insert("isound1", ( States3D([0.52, 0.89, 0.]),
-                           States3D([0.82, 0.15, 0.]),
-                           ParamTree {
-                               delta_s: 0.0001,
-                               delta_v: 0.0002,
-                               // delta_s: 0.01,
-                               // delta_v: 0.02,
-                               prop_delta_low: 0.1,
-                               // prop_delta_low: 1.,
-                               prop_delta_high: 1.,   
-                           },
-                           // Some(0.05),
-                           Some(0.04),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/isound1.1.node",
-                                               &"maps_custom/dragon_age/poly/isound1.1.ele" ) ) ),
-    ) );
-    
-    hm.insert("lak100n", ( States3D([0.27, 0.73, 0.]),
-                           States3D([0.39, 0.2, 0.]),
-                           ParamTree {
-                               delta_s: 0.001,
-                               delta_v: 0.002,
-                               prop_delta_low: 0.2,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.05),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/lak100n.1.node",
-                                               &"maps_custom/dragon_age/poly/lak100n.1.ele" ) ) ),
-    ) );
-    
-    hm.insert("lak302d", ( States3D([0.35, 0.95, 0.]),
-                           States3D([0.32, 0.04, 0.]),
-                           ParamTree {
-                               delta_s: 0.0075,
-                               delta_v: 0.012,
-                               prop_delta_low: 0.25,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.06),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/lak302d.1.node",
-                                               &"maps_custom/dragon_age/poly/lak302d.1.ele" ) ) ),
-    ) );
-
-    hm.insert("lak504d", ( States3D([0.35, 0.925, 0.]),
-                           States3D([0.32, 0.065, 0.]),
-                           ParamTree {
-                               delta_s: 0.0075,
-                               delta_v: 0.012,
-                               prop_delta_low: 0.25,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.06),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/lak504d.1.node",
-                                               &"maps_custom/dragon_age/poly/lak504d.1.ele" ) ) ),
-    ) );
-    
-    hm.insert("lgt604d", ( States3D([0.05, 0.9, 0.]),
-                           States3D([0.95, 0.3, 0.]),
-                           ParamTree {
-                               delta_s: 0.0075,
-                               delta_v: 0.012,
-                               prop_delta_low: 0.25,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.06),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/lgt604d.1.node",
-                                               &"maps_custom/dragon_age/poly/lgt604d.1.ele" ) ) ),
-    ) );
-
-    hm.insert("ost004d", ( States3D([0.27, 0.85, 0.]),
-                           States3D([0.25, 0.15, 0.]),
-                           ParamTree {
-                               delta_s: 0.0002,
-                               delta_v: 0.005,
-                               prop_delta_low: 0.25,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.05),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/ost004d.1.node",
-                                               &"maps_custom/dragon_age/poly/ost004d.1.ele" ) ) ),
-    ) );
-
-    hm.insert("ost100d", ( States3D([0.4, 0.65, PI]),
-                           States3D([0.73, 0.33, 0.]),
-                           ParamTree {
-                               delta_s: 0.003,
-                               delta_v: 0.006,
-                               prop_delta_low: 0.2,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.06),
-                           Some(600_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/ost100d.1.node",
-                                               &"maps_custom/dragon_age/poly/ost100d.1.ele" ) ) ),
-    ) );
-    
-    hm.insert("orz000d", ( States3D([0.25, 0.9, 0.]),
-                           States3D([0.25, 0.1, 0.]),
-                           ParamTree {
-                               delta_s: 0.004,
-                               delta_v: 0.008,
-                               // delta_s: 0.001,
-                               // delta_v: 0.002,                               
-                               prop_delta_low: 0.025,
-                               prop_delta_high: 1.,   
-                           },
-                           Some(0.07),
-                           Some(500_000),
-                           Some(MapPath::Game((&"maps_custom/dragon_age/poly/orz000d.1.node",
-                                               &"maps_custom/dragon_age/poly/orz000d.1.ele" ) ) ),
-    ) );    
-
-
-    //sc1
-    hm.insert("AcrosstheCape", ( States3D([0.02, 0.95, 0.]),
-                                 States3D([0.9, 0.1, 0.]),
-                                 ParamTree {
-                                     delta_s: 0.002,
-                                     delta_v: 0.004,
-                                     prop_delta_low: 0.05,
-                                     prop_delta_high: 1.,   
-                                 },
-                                 Some(0.065),
-                                 Some(500_000),
-                                 Some(MapPath::Game((&"maps_custom/sc1/poly/AcrosstheCape.1.node",
-                                                     &"maps_custom/sc1/poly/AcrosstheCape.1.ele" ) ) ),
-    ) );
-
-    hm.insert("Archipelago", ( States3D([0.02, 0.95, 0.]),
-                                 States3D([0.9, 0.1, 0.]),
-                                 ParamTree {
-                                     delta_s: 0.005,
-                                     delta_v: 0.01,
-                                     prop_delta_low: 0.1,
-                                     prop_delta_high: 1.,   
-                                 },
-                                 Some(0.065),
-                                 Some(500_000),
-                                 Some(MapPath::Game((&"maps_custom/sc1/poly/Archipelago.1.node",
-                                                     &"maps_custom/sc1/poly/Archipelago.1.ele" ) ) ),
-    ) );
-
-    hm.insert("BigGameHunters", ( States3D([0.05, 0.95, 0.]),
-                                  States3D([0.9, 0.1, 0.]),
-                                  ParamTree {
-                                      delta_s: 0.0002,
-                                      delta_v: 0.005,
-                                      prop_delta_low: 0.25,
-                                      prop_delta_high: 1.,   
-                                  },
-                                  Some(0.05),
-                                  Some(500_000),
-                                  Some(MapPath::Game((&"maps_custom/sc1/poly/BigGameHunters.1.node",
-                                                      &"maps_custom/sc1/poly/BigGameHunters.1.ele" ) ) ),
-    ) );
-
-    hm.insert("EbonLakes", ( States3D([0.05, 0.9, 0.]),
-                             States3D([0.9, 0.05, 0.]),
-                             ParamTree {
-                                 delta_s: 0.005,
-                                 delta_v: 0.01,
-                                 prop_delta_low: 0.1,
-                                 prop_delta_high: 1.,   
-                             },
-                             Some(0.065),
-                             Some(500_000),
-                             Some(MapPath::Game((&"maps_custom/sc1/poly/EbonLakes.1.node",
-                                                 &"maps_custom/sc1/poly/EbonLakes.1.ele" ) ) ),
-    ) );
-
-    hm.insert("RedCanyons", ( States3D([0.05, 0.9, 0.]),
-                             States3D([0.9, 0.05, 0.]),
-                             ParamTree {
-                                 delta_s: 0.0002,
-                                 delta_v: 0.005,
-                                 prop_delta_low: 0.25,
-                                 prop_delta_high: 1.,   
-                             },
-                             Some(0.05),
-                             Some(500_000),
-                             Some(MapPath::Game((&"maps_custom/sc1/poly/RedCanyons.1.node",
-                                                 &"maps_custom/sc1/poly/RedCanyons.1.ele" ) ) ),
-    ) );
-
-    hm.insert("JungleSiege", ( States3D([0.5, 0.97, 1.5*PI]),
-                              States3D([0.5, 0.05, 0.]),
-                              ParamTree {
-                                  delta_s: 0.0002,
-                                  delta_v: 0.005,
-                                  prop_delta_low: 0.25,
-                                  prop_delta_high: 1.,   
-                              },
-                              Some(0.05),
-                              Some(500_000),
-                              Some(MapPath::Game((&"maps_custom/sc1/poly/JungleSiege.1.node",
-                                                  &"maps_custom/sc1/poly/JungleSiege.1.ele" ) ) ),
-    ) );
-
-    hm.insert("WheelofWar", ( States3D([0.5, 0.98, 0.]),
-                               States3D([0.5, 0.03, 0.]),
-                               ParamTree {
-                                   delta_s: 0.0055,
-                                   delta_v: 0.01,
-                                   prop_delta_low: 0.25,
-                                   prop_delta_high: 1.,   
-                               },
-                               Some(0.06),
-                               Some(500_000),
-                               Some(MapPath::Game((&"maps_custom/sc1/poly/WheelofWar.1.node",
-                                                   &"maps_custom/sc1/poly/WheelofWar.1.ele" ) ) ),
-    ) );
-
-
-    //dragon age 2
-    hm.insert("ca_caverns1", ( States3D([0.21, 0.98, 1.5*PI]),
-                               States3D([0.38, 0.02, 0.]),
-                               ParamTree {
-                                   delta_s: 0.002,
-                                   delta_v: 0.004,
-                                   prop_delta_low: 0.1,
-                                   prop_delta_high: 1.,   
-                               },
-                               Some(0.065),
-                               Some(500_000),
-                               Some(MapPath::Game((&"maps_custom/dragon_age_2/poly/ca_caverns1.1.node",
-                                                   &"maps_custom/dragon_age_2/poly/ca_caverns1.1.ele" ) ) ),
-    ) );
-
-    hm.insert("ht_0_hightown", ( States3D([0.15, 0.35, 0.]),
-                                 States3D([0.34, 0.92, 0.]),
-                                 ParamTree {
-                                     delta_s: 0.002,
-                                     delta_v: 0.004,
-                                     prop_delta_low: 0.1,
-                                     prop_delta_high: 1.,   
-                                 },
-                                 Some(0.065),
-                                 Some(500_000),
-                                 Some(MapPath::Game((&"maps_custom/dragon_age_2/poly/ht_0_hightown.1.node",
-                                                     &"maps_custom/dragon_age_2/poly/ht_0_hightown.1.ele" ) ) ),
-    ) );
-    
-    hm
+    ///iteration upper bound (optional, default to commandline if not provided)
+    #[serde(default)]
+    pub iterations: Option<u32>,
+
+    ///planning time upper bound in milliseconds (optional, default to commandline if not provided)
+    #[serde(default)]
+    pub time_budget_ms: Option<u32>,
+
+    ///map (optional, default to commandline input)
+    #[serde(default)]
+    pub map: Option<MapPath>,
+}
+
+///fields common to scenarios of all models
+#[derive(Deserialize)]
+struct ScenarioHeader {
+    #[serde(default)]
+    name: String,
+    model: String,
 }
 
+fn scenario_name( name: String, path: &Path ) -> String {
+    if name.is_empty() {
+        path.file_stem().and_then(|x| x.to_str() ).unwrap_or("").to_string()
+    } else {
+        name
+    }
+}
+
+///returns json files in ``dir``
+fn scenario_files( dir: &str ) -> Result<Vec<PathBuf>> {
+
+    let mut files = std::fs::read_dir( dir ).map_err(|e| PlannerError::io( dir, e ) )?
+        .filter_map(|x| x.ok() )
+        .map(|x| x.path() )
+        .filter(|x| x.extension().map_or( false, |ext| ext == "json" ) )
+        .collect::<Vec<_>>();
+
+    files.sort();
+
+    Ok( files )
+}
+
+impl<TS> Scenario<TS> where TS: States {
+
+    pub fn load_from_file( path: &str ) -> Result<Scenario<TS>> {
+
+        let s = std::fs::read_to_string( path ).map_err(|e| PlannerError::io( path, e ) )?;
+
+        let mut scenario : Scenario<TS> = serde_json::from_str( s.as_str() ).map_err(|e| PlannerError::json( path, e ) )?;
+
+        scenario.name = scenario_name( scenario.name, Path::new(path) );
+
+        Ok( scenario )
+    }
+}
+
+///loads scenarios of ``model`` from json files in ``dir``, keyed by scenario name
+pub fn load_dir<TS>( dir: &str, model: &str ) -> Result<HashMap< String, Scenario<TS> >> where TS: States {
 
-pub fn load_4d_3d() -> HashMap< & 'static str, (States4D, States4D, ParamTree, Option<f32>, Option<u32>, Option<MapPath> )> {
-    
     let mut hm = HashMap::new();
 
-    //randomly generated boxes
-    
-    hm.insert("obs3", ( States4D([0.2, 0.2, 0., 0.]),
-                        States4D([0.8, 0.8, 0.4, 0.]),
-                        ParamTree {
-                            delta_s: 0.075,
-                            delta_v: 0.12,
-                            prop_delta_low: 0.05,
-                            prop_delta_high: 1.,   
-                        },
-                        Some(0.12),
-                        Some(250_000),
-                        Some(MapPath::Obs(&"obstacles/obs3.txt")),
-    ) );
-
-    hm.insert("obs_3d", ( States4D([0.1, 0.1, 0., 0.]),
-                          States4D([0.5, 0.5, 0.6, 0.]),
-                          ParamTree {
-                              delta_s: 0.075,
-                              delta_v: 0.12,
-                              prop_delta_low: 0.05,
-                              prop_delta_high: 1.,   
-                          },
-                          Some(0.12),
-                          Some(250_000),
-                          Some(MapPath::Obs(&"obstacles/obs_3d.txt")),
-    ) );
-
-    hm
+    for path in scenario_files( dir )? {
+
+        let path_str = path.to_string_lossy();
+        
+        let s = std::fs::read_to_string( &path ).map_err(|e| PlannerError::io( &path_str, e ) )?;
+
+        let header : ScenarioHeader = serde_json::from_str( s.as_str() ).map_err(|e| PlannerError::json( &path_str, e ) )?;
+
+        if header.model != model {
+            continue;
+        }
+
+        let mut scenario : Scenario<TS> = serde_json::from_str( s.as_str() ).map_err(|e| PlannerError::json( &path_str, e ) )?;
+
+        scenario.name = scenario_name( scenario.name, &path );
+
+        if hm.contains_key( &scenario.name ) {
+            warn!( "duplicate scenario {} for model {}, ignoring {:?}", scenario.name, model, path );
+        } else {
+            hm.insert( scenario.name.clone(), scenario );
+        }
+    }
+
+    info!( "loaded {} scenarios for model {} from {}", hm.len(), model, dir );
+
+    Ok( hm )
+}
+
+///returns model of the first scenario named ``name`` in ``dir``
+pub fn find_model( dir: &str, name: &str ) -> Option<String> {
+
+    scenario_files( dir ).ok()?.iter()
+        .filter_map(|path| {
+            let s = std::fs::read_to_string( path ).ok()?;
+            let header : ScenarioHeader = serde_json::from_str( s.as_str() ).ok()?;
+            if scenario_name( header.name, path ) == name {
+                Some( header.model )
+            } else {
+                None
+            }
+        })
+        .next()
+}
+
+#[test]
+fn test_load_dir(){
+
+    let dir = std::env::temp_dir().join( format!( "sample_planning_test_scenarios_{}", std::process::id() ) );
+    std::fs::create_dir_all( &dir ).unwrap();
+
+    let files = [ ( "a.json", r#"{ "model": "dubins", "start": [0.1,0.1,0.0], "goal": [0.9,0.9,0.0] }"# ),
+                  ( "b.json", r#"{ "name": "a", "model": "dubins", "start": [0.2,0.2,0.0], "goal": [0.9,0.9,0.0] }"# ),
+                  ( "c.json", r#"{ "name": "plane", "model": "airplane", "start": [0.1,0.1,0.1,0.0,0.0,0.0], "goal": [0.9,0.9,0.9,0.0,0.0,0.0] }"# ),
+                  ( "d.txt", "not a scenario" ) ];
+
+    for (f,s) in files.iter() {
+        std::fs::write( dir.join(f), s ).unwrap();
+    }
+
+    let dir_str = dir.to_str().unwrap();
+
+    let hm = load_dir::<States3D>( dir_str, "dubins" ).unwrap();
+
+    //name defaults to file stem, duplicate from b.json is ignored, other models are skipped
+    assert_eq!( hm.len(), 1 );
+    assert_eq!( hm["a"].start.get_vals(), vec![ 0.1, 0.1, 0.0 ] );
+
+    assert_eq!( find_model( dir_str, "a" ), Some( "dubins".to_string() ) );
+    assert_eq!( find_model( dir_str, "plane" ), Some( "airplane".to_string() ) );
+    assert_eq!( find_model( dir_str, "d" ), None );
+
+    std::fs::remove_dir_all( &dir ).unwrap();
 }
@@ -0,0 +1,414 @@
+//! Reeds-Shepp curves
+//!
+//! Shortest paths of a car driving forward and backward with bounded turning radius between two states [ x, y, theta ],
+//! composed of left turn (L), straight (S) and right turn (R) segments, with cusps where the direction of travel changes.
+//! Based on Reeds and Shepp, Optimal paths for a car that goes both forwards and backwards, using the formulas of the
+//! CSC, CCC, CCCC, CCSC and CCSCC families with their time flipped and reflected variants.
+//! Formulas are evaluated in double precision in the frame of the start state, with lengths normalized by radius.
+
+use std::f64::consts::PI;
+
+use crate::states::States3D;
+use crate::control::Control2D;
+use crate::dubins::{self,Turn};
+
+const ZERO: f64 = 1e-10;
+
+const L: Option<Turn> = Some( Turn::L );
+const S: Option<Turn> = Some( Turn::S );
+const R: Option<Turn> = Some( Turn::R );
+const N: Option<Turn> = None;
+
+///segment turns of each path type, unused trailing segments are None
+const PATH_TYPES: [[Option<Turn>;5];18] = [
+    [ L, R, L, N, N ],
+    [ R, L, R, N, N ],
+    [ L, R, L, R, N ],
+    [ R, L, R, L, N ],
+    [ L, R, S, L, N ],
+    [ R, L, S, R, N ],
+    [ L, S, R, L, N ],
+    [ R, S, L, R, N ],
+    [ L, R, S, R, N ],
+    [ R, L, S, L, N ],
+    [ R, S, R, L, N ],
+    [ L, S, L, R, N ],
+    [ L, S, R, N, N ],
+    [ R, S, L, N, N ],
+    [ L, S, L, N, N ],
+    [ R, S, R, N, N ],
+    [ L, R, S, L, R ],
+    [ R, L, S, R, L ],
+];
+
+#[derive(Clone,Debug)]
+pub struct ReedsSheppPath {
+    pub start: States3D,
+    pub radius: f32,
+    ///turn and signed segment length normalized by radius, negative when driving backward
+    pub segments: Vec<(Turn,f32)>,
+}
+
+///angle in [-PI,PI)
+fn mod2pi( x: f64 ) -> f64 {
+    let v = x % ( 2. * PI );
+    if v < -PI {
+        v + 2. * PI
+    } else if v >= PI {
+        v - 2. * PI
+    } else {
+        v
+    }
+}
+
+///polar coordinates (r,theta) of (x,y)
+fn polar( x: f64, y: f64 ) -> (f64,f64) {
+    ( ( x*x + y*y ).sqrt(), y.atan2( x ) )
+}
+
+fn tau_omega( u: f64, v: f64, xi: f64, eta: f64, phi: f64 ) -> (f64,f64) {
+    let delta = mod2pi( u - v );
+    let a = u.sin() - delta.sin();
+    let b = u.cos() - delta.cos() - 1.;
+    let t1 = ( eta * a - xi * b ).atan2( xi * a + eta * b );
+    let t2 = 2. * ( delta.cos() - v.cos() - u.cos() ) + 3.;
+    let tau = if t2 < 0. { mod2pi( t1 + PI ) } else { mod2pi( t1 ) };
+    ( tau, mod2pi( tau - u + v - phi ) )
+}
+
+fn lp_sp_lp( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let (u,t) = polar( x - phi.sin(), y - 1. + phi.cos() );
+    if t >= -ZERO {
+        let v = mod2pi( phi - t );
+        if v >= -ZERO {
+            return Some( (t,u,v) )
+        }
+    }
+    None
+}
+
+fn lp_sp_rp( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let (u1,t1) = polar( x + phi.sin(), y - 1. - phi.cos() );
+    let u1 = u1 * u1;
+    if u1 >= 4. {
+        let u = ( u1 - 4. ).sqrt();
+        let theta = 2f64.atan2( u );
+        let t = mod2pi( t1 + theta );
+        let v = mod2pi( t - phi );
+        if t >= -ZERO && v >= -ZERO {
+            return Some( (t,u,v) )
+        }
+    }
+    None
+}
+
+fn lp_rm_l( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let (u1,theta) = polar( x - phi.sin(), y - 1. + phi.cos() );
+    if u1 <= 4. {
+        let u = -2. * ( 0.25 * u1 ).asin();
+        let t = mod2pi( theta + 0.5 * u + PI );
+        let v = mod2pi( phi - t + u );
+        if t >= -ZERO && u <= ZERO {
+            return Some( (t,u,v) )
+        }
+    }
+    None
+}
+
+fn lp_rup_lum_rm( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let xi = x + phi.sin();
+    let eta = y - 1. - phi.cos();
+    let rho = 0.25 * ( 2. + ( xi*xi + eta*eta ).sqrt() );
+    if rho <= 1. {
+        let u = rho.acos();
+        let (t,v) = tau_omega( u, -u, xi, eta, phi );
+        if t >= -ZERO && v <= ZERO {
+            return Some( (t,u,v) )
+        }
+    }
+    None
+}
+
+fn lp_rum_lum_rp( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let xi = x + phi.sin();
+    let eta = y - 1. - phi.cos();
+    let rho = ( 20. - xi*xi - eta*eta ) / 16.;
+    if ( 0. ..= 1. ).contains( &rho ) {
+        let u = -rho.acos();
+        if u >= -0.5 * PI {
+            let (t,v) = tau_omega( u, u, xi, eta, phi );
+            if t >= -ZERO && v >= -ZERO {
+                return Some( (t,u,v) )
+            }
+        }
+    }
+    None
+}
+
+fn lp_rm_sm_lm( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let (rho,theta) = polar( x - phi.sin(), y - 1. + phi.cos() );
+    if rho >= 2. {
+        let r = ( rho*rho - 4. ).sqrt();
+        let u = 2. - r;
+        let t = mod2pi( theta + r.atan2( -2. ) );
+        let v = mod2pi( phi - 0.5 * PI - t );
+        if t >= -ZERO && u <= ZERO && v <= ZERO {
+            return Some( (t,u,v) )
+        }
+    }
+    None
+}
+
+fn lp_rm_sm_rm( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let xi = x + phi.sin();
+    let eta = y - 1. - phi.cos();
+    let (rho,theta) = polar( -eta, xi );
+    if rho >= 2. {
+        let t = theta;
+        let u = 2. - rho;
+        let v = mod2pi( t + 0.5 * PI - phi );
+        if t >= -ZERO && u <= ZERO && v <= ZERO {
+            return Some( (t,u,v) )
+        }
+    }
+    None
+}
+
+fn lp_rm_s_lm_rp( x: f64, y: f64, phi: f64 ) -> Option<(f64,f64,f64)> {
+    let xi = x + phi.sin();
+    let eta = y - 1. - phi.cos();
+    let (rho,_) = polar( xi, eta );
+    if rho >= 2. {
+        let u = 4. - ( rho*rho - 4. ).sqrt();
+        if u <= ZERO {
+            let t = mod2pi( ( ( 4. - u ) * xi - 2. * eta ).atan2( -2. * xi + ( u - 4. ) * eta ) );
+            let v = mod2pi( t - phi );
+            if t >= -ZERO && v >= -ZERO {
+                return Some( (t,u,v) )
+            }
+        }
+    }
+    None
+}
+
+type Formula = fn( f64, f64, f64 ) -> Option<(f64,f64,f64)>;
+
+///(path type, signed normalized segment lengths) of every path family that connects the origin to (x,y,phi) in normalized units
+fn candidates( x: f64, y: f64, phi: f64 ) -> Vec<(usize,Vec<f64>)> {
+
+    let mut out = vec![];
+
+    //each formula with its time flipped, reflected and time flipped + reflected variants,
+    //``lengths`` maps (t,u,v) to segment lengths, which are negated for time flipped variants
+    let mut add = |f: Formula, x: f64, y: f64, phi: f64, types: [usize;2], lengths: &dyn Fn(f64,f64,f64) -> Vec<f64>| {
+        let variants = [ ( x, y, phi, types[0], 1. ),
+                         ( -x, y, -phi, types[0], -1. ),
+                         ( x, -y, -phi, types[1], 1. ),
+                         ( -x, -y, phi, types[1], -1. ) ];
+        for (x, y, phi, ty, sign) in variants.iter() {
+            if let Some((t,u,v)) = f( *x, *y, *phi ) {
+                out.push( ( *ty, lengths( t, u, v ).into_iter().map(|l| l * sign ).collect() ) );
+            }
+        }
+    };
+
+    let h = 0.5 * PI;
+
+    //backward variants are evaluated from the end state
+    let xb = x * phi.cos() + y * phi.sin();
+    let yb = x * phi.sin() - y * phi.cos();
+
+    //CSC
+    add( lp_sp_lp, x, y, phi, [14,15], &|t,u,v| vec![ t, u, v ] );
+    add( lp_sp_rp, x, y, phi, [12,13], &|t,u,v| vec![ t, u, v ] );
+
+    //CCC
+    add( lp_rm_l, x, y, phi, [0,1], &|t,u,v| vec![ t, u, v ] );
+    add( lp_rm_l, xb, yb, phi, [0,1], &|t,u,v| vec![ v, u, t ] );
+
+    //CCCC
+    add( lp_rup_lum_rm, x, y, phi, [2,3], &|t,u,v| vec![ t, u, -u, v ] );
+    add( lp_rum_lum_rp, x, y, phi, [2,3], &|t,u,v| vec![ t, u, u, v ] );
+
+    //CCSC
+    add( lp_rm_sm_lm, x, y, phi, [4,5], &|t,u,v| vec![ t, -h, u, v ] );
+    add( lp_rm_sm_rm, x, y, phi, [8,9], &|t,u,v| vec![ t, -h, u, v ] );
+    add( lp_rm_sm_lm, xb, yb, phi, [6,7], &|t,u,v| vec![ v, u, -h, t ] );
+    add( lp_rm_sm_rm, xb, yb, phi, [10,11], &|t,u,v| vec![ v, u, -h, t ] );
+
+    //CCSCC
+    add( lp_rm_s_lm_rp, x, y, phi, [16,17], &|t,u,v| vec![ t, -h, u, -h, v ] );
+
+    out
+}
+
+///all Reeds-Shepp paths from ``start`` to ``end`` with turning ``radius``, empty if radius is not positive
+pub fn paths( start: &States3D, end: &States3D, radius: f32 ) -> Vec<ReedsSheppPath> {
+
+    if !( radius > 0. ) {
+        return vec![]
+    }
+
+    let r = radius as f64;
+    let dx = ( end.0[0] - start.0[0] ) as f64;
+    let dy = ( end.0[1] - start.0[1] ) as f64;
+    let (c, s) = ( (start.0[2] as f64).cos(), (start.0[2] as f64).sin() );
+
+    let x = ( c * dx + s * dy ) / r;
+    let y = ( -s * dx + c * dy ) / r;
+    let phi = end.0[2] as f64 - start.0[2] as f64;
+
+    candidates( x, y, phi ).into_iter()
+        .map(|(ty, lengths)| {
+            ReedsSheppPath {
+                start: start.clone(),
+                radius: radius,
+                segments: PATH_TYPES[ty].iter()
+                    .zip( lengths.iter() )
+                    .filter_map(|(turn, l)| turn.map(|x| ( x, *l as f32 ) ) )
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+///shortest Reeds-Shepp path from ``start`` to ``end`` with turning ``radius``, None if radius is not positive
+pub fn shortest_path( start: &States3D, end: &States3D, radius: f32 ) -> Option<ReedsSheppPath> {
+    paths( start, end, radius ).into_iter()
+        .min_by(|a,b| a.length().partial_cmp( &b.length() ).unwrap_or( std::cmp::Ordering::Equal ) )
+}
+
+impl ReedsSheppPath {
+
+    ///total distance travelled, forward and backward
+    pub fn length( & self ) -> f32 {
+        self.segments.iter().map(|x| x.1.abs() ).sum::<f32>() * self.radius
+    }
+
+    ///number of changes of driving direction
+    pub fn cusps( & self ) -> usize {
+        self.segments.iter()
+            .filter(|x| x.1 != 0. )
+            .collect::<Vec<_>>()
+            .windows(2)
+            .filter(|x| ( x[0].1 > 0. ) != ( x[1].1 > 0. ) )
+            .count()
+    }
+
+    ///state at travelled distance ``s`` along the path, clamped to the path
+    pub fn state_at( & self, s: f32 ) -> States3D {
+
+        let r = self.radius;
+
+        let mut remain = s.max( 0. ) / r;
+        let mut q = [ self.start.0[0] / r, self.start.0[1] / r, self.start.0[2] ];
+
+        for (turn, len) in self.segments.iter() {
+            let step = remain.min( len.abs() );
+            q = dubins::advance( q, *turn, step * len.signum() );
+            remain -= step;
+        }
+
+        let h = q[2] % ( 2. * std::f32::consts::PI );
+        let h = if h < 0. { h + 2. * std::f32::consts::PI } else { h };
+
+        States3D([ q[0] * r, q[1] * r, h ])
+    }
+
+    pub fn end( & self ) -> States3D {
+        self.state_at( self.length() )
+    }
+
+    ///edges of the path for a car of ``speed``, as ([signed speed, heading rate] control, duration, states along the edge),
+    ///states are at most ``resolution`` apart in duration, only the end of each edge if ``resolution`` is None
+    pub fn edges( & self, speed: f32, resolution: Option<f32> ) -> Vec<(Control2D,f32,Vec<States3D>)> {
+
+        let mut offset = 0.;
+        let mut out = vec![];
+
+        for (turn, len) in self.segments.iter() {
+
+            let l = len.abs() * self.radius;
+
+            if l <= 0. {
+                continue;
+            }
+
+            let duration = l / speed;
+
+            let v = if *len < 0. { -speed } else { speed };
+
+            let u = match turn {
+                Turn::L => { v / self.radius },
+                Turn::R => { -v / self.radius },
+                Turn::S => { 0. },
+            };
+
+            let n = match resolution {
+                Some(r) if r > 0. => { ( duration / r ).ceil().max( 1. ) as usize },
+                _ => { 1 },
+            };
+
+            let states = (1..=n).map(|i| self.state_at( offset + l * i as f32 / n as f32 ) ).collect();
+
+            out.push( ( Control2D([ v, u ]), duration, states ) );
+
+            offset += l;
+        }
+
+        out
+    }
+}
+
+#[test]
+fn test_reeds_shepp_paths(){
+
+    use rand::prelude::*;
+    use rand::rngs::StdRng;
+
+    use std::f32::consts::PI;
+
+    let mut rng = StdRng::seed_from_u64( 3 );
+
+    let close = |a: &States3D, b: &States3D| {
+        let dh = ( a.0[2] - b.0[2] ).abs() % ( 2.*PI );
+        ( a.0[0] - b.0[0] ).abs() < 1e-3 && ( a.0[1] - b.0[1] ).abs() < 1e-3 && dh.min( 2.*PI - dh ) < 1e-3
+    };
+
+    let mut types_found = vec![];
+
+    for _ in 0..500 {
+
+        let a = States3D([ rng.gen_range(0., 1.), rng.gen_range(0., 1.), rng.gen_range(0., 2.*PI) ]);
+        let b = States3D([ rng.gen_range(0., 1.), rng.gen_range(0., 1.), rng.gen_range(0., 2.*PI) ]);
+        let radius = rng.gen_range(0.05, 0.4);
+
+        for p in paths( &a, &b, radius ) {
+            assert!( close( &p.end(), &b ), "{:?} ends at {:?}, expected {:?}", p, p.end(), b );
+            let turns = p.segments.iter().map(|x| x.0 ).collect::<Vec<_>>();
+            if !types_found.contains( &turns ) {
+                types_found.push( turns );
+            }
+        }
+
+        let p = shortest_path( &a, &b, radius ).expect("no path");
+        let d = ( ( a.0[0] - b.0[0] ).powi(2) + ( a.0[1] - b.0[1] ).powi(2) ).sqrt();
+        assert!( p.length() >= d - 1e-4 );
+
+        //reversing can only shorten paths of a forward only car
+        let p_dubins = dubins::shortest_path( &a, &b, radius ).unwrap();
+        assert!( p.length() <= p_dubins.length() + 1e-3 );
+
+        let edges = p.edges( 1., Some(0.05) );
+        assert!( ( edges.iter().map(|x| x.1 ).sum::<f32>() - p.length() ).abs() < 1e-4 );
+        assert!( close( edges.last().unwrap().2.last().unwrap(), &b ) );
+    }
+
+    assert!( types_found.len() >= 12 );
+
+    //straight back
+    let p = shortest_path( &States3D([0.6, 0.1, 0.]), &States3D([0.1, 0.1, 0.]), 0.1 ).unwrap();
+    assert!( ( p.length() - 0.5 ).abs() < 1e-5 );
+    assert_eq!( p.cusps(), 0 );
+    assert!( p.edges( 1., None ).iter().all(|x| x.0 .0[0] < 0. ) );
+}
@@ -0,0 +1,260 @@
+//! Checkpoint and resume of SST trees
+//!
+//! A checkpoint holds the tree (nodes, edges, parent links, active / inactive sets, freelist), witnesses and their representatives,
+//! nearest neighbour query graphs and path optimization state, saved as json.
+//! Param, obstacles, ParamTree and PlannerConfig are supplied again on resume, so a tree may be shared for debugging or used to
+//! warm start a later query. Motion primitive lookups are not saved and are rebuilt after resuming.
+//! Random number generators are reseeded on resume from config seed and iteration count, or from entropy,
+//! so a resumed run is reproducible but differs from an uninterrupted run.
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use serde::{Serialize,Deserialize};
+
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::{Param,ParamObstacles,ParamTree,PlannerConfig};
+use crate::hash::{HashSet,HashMap};
+use crate::error::{PlannerError,Result};
+
+use super::sst::{SST,Gaussian};
+use super::tree::{Node,Edge};
+use super::nn_stochastic::NNGraph;
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States and Control
+pub struct Checkpoint<TS,TC> where TS: States, TC: Control {
+
+    pub nodes: Vec<Node<TS>>,
+    pub nodes_freelist: Vec<usize>,
+    pub nodes_active: HashSet<usize>,
+    pub nodes_inactive: HashSet<usize>,
+    pub link_parent: HashMap<usize,usize>,
+    ///((parent,child),edge)
+    pub edges: Vec<((usize,usize),Edge<TS,TC>)>,
+
+    pub witnesses: Vec<TS>,
+    pub witness_representative: HashMap<usize,usize>,
+
+    pub nn_query: NNGraph<TS>,
+    pub nn_query_witness: NNGraph<TS>,
+
+    pub idx_reached: Option<usize>,
+    pub nodes_goal: HashSet<usize>,
+    pub witness_disturbance: bool,
+    pub stat_witnesses_discovery_rate: f32,
+
+    pub sampling_mixture: Vec<Gaussian<TS>>,
+    pub sampling_mixture_prob: HashMap<usize,f32>,
+    pub saved_feasible_traj: Vec<TS>,
+    pub importance_samples: Vec<(f32,Vec<TS>)>,
+    ///None if not yet computed
+    pub importance_sample_gamma: Option<f32>,
+    pub optimization_iterations: u32,
+
+    pub iter_exec: u32,
+    ///accumulated planning time, counted toward time bound of a resumed run
+    pub stat_time_all: f64,
+    pub stat_pruned_nodes: u32,
+    pub stat_iter_no_change: u32,
+    pub stat_iter_collision: u32,
+    pub stat_iter_invalid: u32,
+    pub stat_goal_distance: Option<f32>,
+}
+
+impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    pub fn checkpoint( & self ) -> Checkpoint<TS,TC> {
+
+        let mut edges = self.edges.iter()
+            .map(|(k,v)| ( *k, v.clone() ) )
+            .collect::<Vec<_>>();
+
+        //stable output for identical trees
+        edges.sort_by_key(|x| x.0 );
+
+        Checkpoint {
+            nodes: self.nodes.clone(),
+            nodes_freelist: self.nodes_freelist.clone(),
+            nodes_active: self.nodes_active.clone(),
+            nodes_inactive: self.nodes_inactive.clone(),
+            link_parent: self.link_parent.clone(),
+            edges: edges,
+            witnesses: self.witnesses.clone(),
+            witness_representative: self.witness_representative.clone(),
+            nn_query: self.nn_query.graph(),
+            nn_query_witness: self.nn_query_witness.graph(),
+            idx_reached: self.idx_reached,
+            nodes_goal: self.nodes_goal.clone(),
+            witness_disturbance: self.witness_disturbance,
+            stat_witnesses_discovery_rate: self.stat_witnesses_discovery_rate,
+            sampling_mixture: self.sampling_mixture.clone(),
+            sampling_mixture_prob: self.sampling_mixture_prob.clone(),
+            saved_feasible_traj: self.saved_feasible_traj.clone(),
+            importance_samples: self.importance_samples.clone(),
+            importance_sample_gamma: if self.importance_sample_gamma.is_finite() { Some(self.importance_sample_gamma) } else { None },
+            optimization_iterations: self.optimization_iterations,
+            iter_exec: self.iter_exec,
+            stat_time_all: self.stat_time_all,
+            stat_pruned_nodes: self.stat_pruned_nodes,
+            stat_iter_no_change: self.stat_iter_no_change,
+            stat_iter_collision: self.stat_iter_collision,
+            stat_iter_invalid: self.stat_iter_invalid,
+            stat_goal_distance: self.stat_goal_distance,
+        }
+    }
+
+    pub fn save_checkpoint( & self, path: &str ) -> Result<()> {
+        let s = serde_json::to_string( &self.checkpoint() ).map_err(|e| PlannerError::json( path, e ) )?;
+        std::fs::write( path, s ).map_err(|e| PlannerError::io( path, e ) )
+    }
+
+    ///constructs a planner continuing from ``checkpoint``
+    pub fn resume( param: & Param<TS,TC,TObs>,
+                   obstacles_concrete: ParamObstacles<TObs>,
+                   param_tree: ParamTree,
+                   config: PlannerConfig,
+                   checkpoint: Checkpoint<TS,TC> ) -> Result<Self> {
+
+        if checkpoint.nodes.is_empty() {
+            return Err( PlannerError::InvalidParam( "checkpoint has no nodes".to_string() ) )
+        }
+
+        let num_nodes = checkpoint.nodes.len();
+        let num_witnesses = checkpoint.witnesses.len();
+
+        let out_of_range = checkpoint.idx_reached.iter()
+            .chain( checkpoint.nodes_active.iter() )
+            .chain( checkpoint.nodes_inactive.iter() )
+            .chain( checkpoint.nodes_freelist.iter() )
+            .chain( checkpoint.nodes_goal.iter() )
+            .chain( checkpoint.link_parent.iter().flat_map(|(a,b)| vec![a,b] ) )
+            .chain( checkpoint.edges.iter().flat_map(|((a,b),_)| vec![a,b] ) )
+            .chain( checkpoint.witness_representative.values() )
+            .any(|x| *x >= num_nodes );
+
+        if out_of_range || !checkpoint.nn_query.in_range( num_nodes ) {
+            return Err( PlannerError::InvalidParam( "checkpoint refers to nodes out of range".to_string() ) )
+        }
+
+        if checkpoint.witness_representative.keys().any(|x| *x >= num_witnesses ) ||
+            !checkpoint.nn_query_witness.in_range( num_witnesses ) {
+            return Err( PlannerError::InvalidParam( "checkpoint refers to witnesses out of range".to_string() ) )
+        }
+
+        if checkpoint.sampling_mixture_prob.keys().any(|x| *x >= checkpoint.sampling_mixture.len() ) {
+            return Err( PlannerError::InvalidParam( "checkpoint refers to sampling mixture components out of range".to_string() ) )
+        }
+
+        let mut s = SST::init( param, obstacles_concrete, param_tree, config )?;
+
+        let c = checkpoint;
+
+        s.nodes = c.nodes;
+        s.nodes_freelist = c.nodes_freelist;
+        s.nodes_active = c.nodes_active;
+        s.nodes_inactive = c.nodes_inactive;
+        s.link_parent = c.link_parent;
+        s.edges = c.edges.into_iter().collect();
+        s.witnesses = c.witnesses;
+        s.witness_representative = c.witness_representative;
+        s.nn_query.restore_graph( c.nn_query );
+        s.nn_query_witness.restore_graph( c.nn_query_witness );
+        s.idx_reached = c.idx_reached;
+        s.nodes_goal = c.nodes_goal;
+        s.witness_disturbance = c.witness_disturbance;
+        s.stat_witnesses_discovery_rate = c.stat_witnesses_discovery_rate;
+        s.sampling_mixture = c.sampling_mixture;
+        s.sampling_mixture_prob = c.sampling_mixture_prob;
+        s.saved_feasible_traj = c.saved_feasible_traj;
+        s.importance_samples = c.importance_samples;
+        s.importance_sample_gamma = c.importance_sample_gamma.unwrap_or( std::f32::INFINITY );
+        s.optimization_iterations = c.optimization_iterations;
+        s.iter_exec = c.iter_exec;
+        s.stat_time_all = c.stat_time_all;
+        s.stat_pruned_nodes = c.stat_pruned_nodes;
+        s.stat_iter_no_change = c.stat_iter_no_change;
+        s.stat_iter_collision = c.stat_iter_collision;
+        s.stat_iter_invalid = c.stat_iter_invalid;
+        s.stat_goal_distance = c.stat_goal_distance;
+
+        //continue with different random sequences than the start of the run
+        let mut rng = match s.config.seed {
+            Some(seed) => { StdRng::seed_from_u64( seed.wrapping_add( s.iter_exec as u64 ) ) },
+            _ => { StdRng::from_entropy() },
+        };
+
+        s.nn_query.rng = StdRng::seed_from_u64( rng.gen() );
+        s.nn_query_witness.rng = StdRng::seed_from_u64( rng.gen() );
+        s.rng = rng;
+
+        Ok( s )
+    }
+
+    pub fn load_checkpoint( param: & Param<TS,TC,TObs>,
+                            obstacles_concrete: ParamObstacles<TObs>,
+                            param_tree: ParamTree,
+                            config: PlannerConfig,
+                            path: &str ) -> Result<Self> {
+
+        let s = std::fs::read_to_string( path ).map_err(|e| PlannerError::io( path, e ) )?;
+        let checkpoint = serde_json::from_str( s.as_str() ).map_err(|e| PlannerError::json( path, e ) )?;
+
+        Self::resume( param, obstacles_concrete, param_tree, config, checkpoint )
+    }
+}
+
+#[test]
+fn test_checkpoint_resume(){
+
+    use crate::rrt::rrt::RRT;
+    use crate::test_fixture;
+
+    let config = PlannerConfig { seed: Some(5), ..PlannerConfig::default() };
+
+    let param = test_fixture::dubins( &config, 100_000 );
+    let obs = test_fixture::obstacles_empty();
+
+    let mut sst = SST::init( &param, obs.clone(), ParamTree::default(), config.clone() ).expect("planner init");
+    sst.iterate( Some(300) );
+
+    //checkpoint of a grown tree, not just the root
+    assert!( sst.edges.len() > 100 && sst.witness_representative.len() > 100 && !sst.nodes_inactive.is_empty() );
+
+    let c = sst.checkpoint();
+    let s = serde_json::to_string( &c ).unwrap();
+    let c2 : Checkpoint<_,_> = serde_json::from_str( s.as_str() ).unwrap();
+
+    //out of range indices are rejected instead of panicking later
+    let corrupt : Vec<Box<dyn Fn(&mut Checkpoint<_,_>)>> = vec![ Box::new(|x| { x.nodes_goal.insert( 1_000_000 ); } ),
+                                                                 Box::new(|x| { x.nodes_freelist.push( 1_000_000 ); } ),
+                                                                 Box::new(|x| { x.witness_representative.insert( 0, 1_000_000 ); } ),
+                                                                 Box::new(|x| { x.witness_representative.insert( 1_000_000, 0 ); } ),
+                                                                 Box::new(|x| { x.nn_query.nodes_map.insert( 1_000_000, 0 ); } ),
+                                                                 Box::new(|x| { x.nn_query_witness.lookup_alive.insert( 1_000_000 ); } ) ];
+    for f in corrupt.iter() {
+        let mut c3 = c2.clone();
+        f( &mut c3 );
+        assert!( SST::resume( &param, obs.clone(), ParamTree::default(), config.clone(), c3 ).is_err() );
+    }
+
+    let mut resumed = SST::resume( &param, obs, ParamTree::default(), config, c2 ).expect("resume");
+
+    assert_eq!( resumed.iter_exec, 300 );
+    assert_eq!( resumed.nodes.len(), sst.nodes.len() );
+    assert_eq!( resumed.edges.len(), sst.edges.len() );
+    assert_eq!( resumed.nodes_active, sst.nodes_active );
+    assert_eq!( resumed.nodes_inactive, sst.nodes_inactive );
+    assert!( sst.edges.keys().all(|x| resumed.edges.contains_key( x ) ) );
+    assert_eq!( resumed.witness_representative, sst.witness_representative );
+    assert_eq!( resumed.nn_query.nodes_map, sst.nn_query.nodes_map );
+    assert_eq!( resumed.nn_query_witness.lookup_alive, sst.nn_query_witness.lookup_alive );
+    assert_eq!( resumed.get_best_solution().map(|x| x.cost ), sst.get_best_solution().map(|x| x.cost ) );
+
+    //resumed tree keeps growing
+    resumed.iterate( Some(300) );
+    assert_eq!( resumed.iter_exec, 600 );
+    assert!( resumed.edges.len() > sst.edges.len() );
+    assert!( resumed.witness_representative.len() > sst.witness_representative.len() );
+}
@@ -0,0 +1,589 @@
+//! Kinodynamic RRT and RRT* baselines
+//!
+//! RRT extends the nearest node of a random state sample with a random control and duration, nodes are never pruned.
+//! RRT* extends the lowest cost node within delta_v of the sample instead, and rewires nodes within delta_v of a new node
+//! through it if the model provides a steering function (SystemModel::steer) and the steered connection lowers their cost.
+//! Steered connections are integrated with the model's dynamics and accepted only if they end within REWIRE_TOLERANCE of the rewired node,
+//! coarse Euler steps rarely do, so rewiring is effective with --runge_kutta or a fine collision resolution.
+//! Without a steering function RRT* differs from RRT in parent selection only.
+//! Tree storage and propagation (rrt::tree), nearest neighbour queries and collision checking are shared with SST for comparable results.
+
+extern crate pretty_env_logger;
+
+use std::cmp::Ordering;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::rrt::rrt::RRT;
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::*;
+use crate::hash::{HashSet,HashMap};
+use crate::instrumentation::*;
+use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::event::PlannerEvent;
+use crate::error::Result;
+use crate::collision::{self,CollisionChecker};
+
+use super::tree::{self,Tree,Node,Edge};
+use super::nn_stochastic::NN_Stochastic;
+
+///maximum state space distance between the end of an integrated steered connection and the node it rewires
+const REWIRE_TOLERANCE: f32 = 1e-3;
+
+pub struct KinoRRT<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    pub param: Param<TS,TC,TObs>,
+
+    ///obstacles and sub-state resolution of collision checking
+    pub collision: CollisionChecker<TObs>,
+
+    ///runtime planner behaviour switches
+    pub config: PlannerConfig,
+
+    ///RRT* parent selection and rewiring
+    pub star: bool,
+
+    ///set if the model provides a steering function, required for rewiring
+    pub steer_supported: bool,
+
+    pub nodes: Vec< Node<TS> >,
+    pub link_parent: HashMap< usize, usize >, //node -> node_parent
+    pub edges: HashMap< (usize,usize), Edge<TS,TC> >,
+
+    ///state space radius of the neighbourhood for parent selection and rewiring of RRT*
+    pub delta_v: f32,
+    pub monte_carlo_prop_l: f32,
+    pub monte_carlo_prop_h: f32,
+
+    ///stores nodes, unused if config.nn_naive is set
+    pub nn_query: NN_Stochastic<TS,TC,TObs>,
+
+    ///lowest cost node satisfying goal condition
+    pub idx_reached: Option<usize>,
+
+    ///all nodes in the tree satisfying goal condition
+    pub nodes_goal: HashSet<usize>,
+
+    ///events since last call to take_events
+    pub events: Vec<PlannerEvent>,
+
+    ///set when the run is over, see StopCondition and PlannerConfig::stop_on_solution
+    pub stop_reason: Option<StopReason>,
+
+    pub iter_exec: u32,
+    pub stat_iter_no_change: u32,
+    pub stat_iter_collision: u32,
+    pub stat_iter_invalid: u32,
+    pub stat_goal_biased_samples: u32,
+
+    ///nodes moved under a new parent by RRT* rewiring
+    pub stat_rewired: u32,
+
+    ///closest distance to goal region of states added to the tree
+    pub stat_goal_distance: Option<f32>,
+
+    pub stat_time_all: f64,
+
+    ///source of all randomness of the planner, seeded from config
+    pub rng: StdRng,
+}
+
+impl <TS,TC,TObs> KinoRRT<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    ///RRT* if ``star`` is set, RRT otherwise
+    pub fn init( param: & Param<TS,TC,TObs>,
+                 obstacles_concrete: ParamObstacles<TObs>,
+                 param_tree: ParamTree,
+                 config: PlannerConfig,
+                 star: bool ) -> Result<Self> {
+
+        let name = if star { "rrt_star" } else { "rrt" };
+
+        tree::validate( name, param, &param_tree, &config, false )?;
+
+        let mut rng = tree::rng( &config );
+
+        let steer_supported = param.model.steer( &param.states_init, &param.states_init, None ).is_some();
+
+        if star && !steer_supported {
+            info!("model has no steering function, {} does not rewire", name );
+        }
+
+        let mut s = Self {
+            param: param.clone(),
+            collision: CollisionChecker::init( obstacles_concrete, config.collision_step( param.sim_delta ) ),
+            star: star,
+            steer_supported: steer_supported,
+            nodes: vec![],
+            link_parent: HashMap::default(),
+            edges: HashMap::default(),
+            delta_v: param_tree.delta_v,
+            monte_carlo_prop_l: param_tree.prop_delta_low,
+            monte_carlo_prop_h: param_tree.prop_delta_high,
+            nn_query: NN_Stochastic::init( param.model.clone(), config.nn_sample_log, rng.gen() ),
+            idx_reached: None,
+            nodes_goal: HashSet::default(),
+            events: vec![],
+            stop_reason: None,
+            iter_exec: 0,
+            stat_iter_no_change: 0,
+            stat_iter_collision: 0,
+            stat_iter_invalid: 0,
+            stat_goal_biased_samples: 0,
+            stat_rewired: 0,
+            stat_goal_distance: None,
+            stat_time_all: 0.,
+            rng: rng,
+            config: config,
+        };
+
+        s.add_root();
+
+        Ok( s )
+    }
+
+    fn add_root( & mut self ) {
+
+        self.nodes.push( Node { id: 0,
+                                state: self.param.states_init.clone(),
+                                children: HashSet::default(),
+                                cost: 0. } );
+
+        if !self.config.nn_naive {
+            self.nn_query.add( self.param.states_init.clone(), 0, &*self.param.model );
+        }
+    }
+
+    pub fn reached_goal( & self, states: &TS ) -> bool {
+        self.param.goal_region.contains( &*self.param.model, states, &self.param.states_goal )
+    }
+
+    fn tree( & self ) -> Tree<'_,TS,TC> {
+        Tree { nodes: &self.nodes,
+               link_parent: &self.link_parent,
+               edges: &self.edges,
+               nodes_goal: &self.nodes_goal,
+               idx_reached: self.idx_reached }
+    }
+
+    ///duration resolution of propagation, see tree::resolution
+    fn resolution( & self ) -> Option<f32> {
+        tree::resolution( &self.param, &self.collision, &self.config )
+    }
+
+    ///state sample from the goal region with probability goal_bias of config, from the state space otherwise
+    fn sample_state( & mut self ) -> TS {
+
+        let goal_biased = self.config.goal_bias > 0. && self.rng.gen_range(0., 1.) < self.config.goal_bias;
+
+        if goal_biased {
+            self.stat_goal_biased_samples += 1;
+            self.param.goal_region.sample( &*self.param.model,
+                                          self.param.states_goal.clone(),
+                                          self.config.goal_bias_radius,
+                                          self.config.goal_bias_ignore_heading,
+                                          &mut self.rng )
+        } else {
+            self.param.model.sample_state( &mut self.rng )
+        }
+    }
+
+    ///index of the node nearest to ``state`` in state space
+    fn nearest( & mut self, state: &TS ) -> usize {
+
+        if self.config.nn_naive {
+            let model = &*self.param.model;
+            self.nodes.iter()
+                .map(|x| ( x.id, model.ss_metric( x.state.clone(), state.clone() ) ) )
+                .min_by(|a,b| a.1.partial_cmp( &b.1 ).unwrap_or( Ordering::Equal ) )
+                .expect("no nodes").0
+        } else {
+            self.nn_query.query_nearest( state.clone(), &*self.param.model ).expect("nn query failed to return a node").1
+        }
+    }
+
+    ///indices of nodes within ``radius`` of ``state`` in state space
+    fn near( & mut self, state: &TS, radius: f32 ) -> Vec<usize> {
+
+        if self.config.nn_naive {
+            let model = &*self.param.model;
+            self.nodes.iter()
+                .filter(|x| model.ss_metric( x.state.clone(), state.clone() ) < radius )
+                .map(|x| x.id )
+                .collect()
+        } else {
+            self.nn_query.query_nearest_threshold( state.clone(), &*self.param.model, radius ).into_iter()
+                .map(|x| x.1 )
+                .collect()
+        }
+    }
+
+    ///lowest cost node within delta_v of ``state``, or the nearest node if there is none
+    fn best_near( & mut self, state: &TS ) -> usize {
+
+        let delta_v = self.delta_v;
+
+        let best = self.near( state, delta_v ).into_iter()
+            .min_by(|a,b| self.nodes[*a].cost.partial_cmp( &self.nodes[*b].cost ).unwrap_or( Ordering::Equal ) );
+
+        match best {
+            Some(x) => { x },
+            _ => { self.nearest( state ) },
+        }
+    }
+
+    ///insert node into propagation tree and return index of the inserted node
+    fn insert_node( & mut self,
+                    idx_parent: usize,
+                    state: TS,
+                    control: TC,
+                    duration: f32,
+                    cost: f32,
+                    kind: u32,
+                    substates: &[TS] ) -> usize {
+
+        let idx_new = self.nodes.len();
+
+        self.nodes.push( Node { id: idx_new,
+                                state: state.clone(),
+                                children: HashSet::default(),
+                                cost: cost } );
+
+        self.nodes[idx_parent].children.insert( idx_new );
+        self.link_parent.insert( idx_new, idx_parent );
+
+        let e = Edge::init( &self.config, control, duration, kind, substates );
+        self.edges.insert( (idx_parent, idx_new), e );
+
+        if !self.config.nn_naive {
+            self.nn_query.add( state, idx_new, &*self.param.model );
+        }
+
+        idx_new
+    }
+
+    ///adds ``delta`` to the cost of ``idx`` and its descendants
+    fn update_subtree_cost( & mut self, idx: usize, delta: f32 ) {
+
+        let mut stack = vec![ idx ];
+
+        while let Some(i) = stack.pop() {
+            self.nodes[i].cost += delta;
+            stack.extend( self.nodes[i].children.iter().cloned() );
+        }
+    }
+
+    ///moves nodes within delta_v of ``idx_new`` under it if a steered connection from it is valid, collision free and lowers their cost
+    fn rewire( & mut self, idx_new: usize ) {
+
+        let model = self.param.model.clone();
+
+        let state_new = self.nodes[idx_new].state.clone();
+
+        let resolution = self.resolution();
+
+        //ancestors of the new node are never rewired through it
+        let mut ancestors = HashSet::default();
+        let mut idx = idx_new;
+        while let Some(parent) = self.link_parent.get( &idx ) {
+            ancestors.insert( *parent );
+            idx = *parent;
+        }
+
+        let delta_v = self.delta_v;
+
+        let mut candidates = self.near( &state_new, delta_v );
+        candidates.sort();
+
+        for idx in candidates {
+
+            if idx == idx_new || ancestors.contains( &idx ) {
+                continue;
+            }
+
+            let edges = match model.steer( &state_new, &self.nodes[idx].state, resolution ) {
+                Some(x) => { x },
+                _ => { continue; },
+            };
+
+            let mut s = state_new.clone();
+            let mut cost = self.nodes[idx_new].cost;
+            let mut checked = vec![];
+            let mut feasible = !edges.is_empty();
+
+            for (u,t,sub) in edges {
+
+                let end = match sub.last() {
+                    Some(x) => { x.clone() },
+                    _ => { feasible = false; break; },
+                };
+
+                if !self.param.states_valid( &sub ) ||
+                    self.collision.propagation_collision( &*model, &model.project_state_to_config( s.clone() ), &sub ) {
+                    feasible = false;
+                    break;
+                }
+
+                cost += self.param.cost.edge_cost( &*model, &s, &u, t, &end );
+                checked.push( (u,t,sub,cost) );
+                s = end;
+            }
+
+            //the integrated connection has to end at the rewired node, so that its subtree stays reachable
+            if !feasible ||
+                !( model.ss_metric( s.clone(), self.nodes[idx].state.clone() ) <= REWIRE_TOLERANCE ) ||
+                !( cost < self.nodes[idx].cost ) {
+                continue;
+            }
+
+            //intermediate states of the steered connection become nodes, the last edge ends at the rewired node
+            let (u_last, t_last, sub_last, _) = checked.pop().unwrap();
+
+            let mut parent = idx_new;
+
+            for (u,t,sub,c) in checked {
+                let end = sub.last().unwrap().clone();
+                parent = self.insert_node( parent, end, u, t, c, 2, &sub );
+            }
+
+            let parent_old = *self.link_parent.get( &idx ).expect("rewired node has no parent");
+
+            self.edges.remove( &(parent_old, idx) );
+            self.nodes[parent_old].children.remove( &idx );
+
+            let e = Edge::init( &self.config, u_last, t_last, 2, &sub_last );
+            self.edges.insert( (parent, idx), e );
+            self.nodes[parent].children.insert( idx );
+            self.link_parent.insert( idx, parent );
+
+            let delta = cost - self.nodes[idx].cost;
+            self.update_subtree_cost( idx, delta );
+
+            self.stat_rewired += 1;
+        }
+    }
+
+    ///sets the lowest cost goal node as the best, returns true if it improves on ``cost_previous``
+    fn update_best_goal( & mut self, cost_previous: Option<f32> ) -> bool {
+
+        let best = self.nodes_goal.iter()
+            .map(|x| ( *x, self.nodes[*x].cost ) )
+            .min_by(|a,b| a.1.partial_cmp( &b.1 ).unwrap_or( Ordering::Equal ).then( a.0.cmp( &b.0 ) ) );
+
+        match best {
+            Some((idx,cost)) if cost_previous.map_or( true, |c| cost < c ) => {
+
+                match cost_previous {
+                    None => { info!("found a path to goal on iteration: {}", self.iter_exec ); },
+                    Some(c) => { info!("found a better path to goal on iteration: {}, cost: {} -> {}", self.iter_exec, c, cost ); },
+                }
+
+                self.idx_reached = Some( idx );
+
+                self.events.push( PlannerEvent::NewBestSolution { iteration: self.iter_exec,
+                                                                  cost: cost,
+                                                                  cost_previous: cost_previous } );
+                true
+            },
+            _ => { false },
+        }
+    }
+
+}
+
+impl <TS,TC,TObs> RRT < TS,TC,TObs > for KinoRRT<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    fn iterate( & mut self, iteration: Option<u32> ) -> bool {
+
+        let iter_batch = match tree::batch( &self.param.stop_condition, &mut self.stop_reason, self.iter_exec, self.stat_time_all, iteration ) {
+            Some(x) => { x },
+            _ => { return false },
+        };
+
+        let mut timer_all = Timer::default();
+
+        for _ in 0..iter_batch {
+
+            if tree::stopped( &self.param.stop_condition, &mut self.stop_reason, self.iter_exec, self.stat_time_all + timer_all.dur_ms() ) {
+                break;
+            }
+
+            self.iter_exec += 1;
+
+            let sample = self.sample_state();
+
+            let idx_parent = if self.star { self.best_near( &sample ) } else { self.nearest( &sample ) };
+
+            let state_start = self.nodes[idx_parent].state.clone();
+
+            let ( duration, control ) = tree::monte_carlo_propagation( &self.param, self.monte_carlo_prop_l, self.monte_carlo_prop_h, &mut self.rng );
+
+            let substates = collision::integrate( &*self.param.model, state_start.clone(), control.clone(), duration, self.resolution() );
+
+            if tree::rejected( &self.param, &self.collision, &self.param.model.project_state_to_config( state_start.clone() ), &substates,
+                               &mut self.stat_iter_invalid, &mut self.stat_iter_collision ) {
+                self.stat_iter_no_change += 1;
+                continue;
+            }
+
+            let state_new = substates.last().unwrap().clone();
+
+            let cost = self.nodes[idx_parent].cost +
+                self.param.cost.edge_cost( &*self.param.model, &state_start, &control, duration, &state_new );
+
+            let cost_previous = self.idx_reached.map(|x| self.nodes[x].cost );
+
+            let idx_new = self.insert_node( idx_parent, state_new.clone(), control, duration, cost, 0, &substates );
+
+            let d = self.param.goal_region.distance( &*self.param.model, &state_new, &self.param.states_goal );
+            if self.stat_goal_distance.map_or( true, |x| d < x ) {
+                self.stat_goal_distance = Some(d);
+            }
+
+            if self.reached_goal( &state_new ) {
+                self.nodes_goal.insert( idx_new );
+            }
+
+            if self.star && self.steer_supported {
+                self.rewire( idx_new );
+            }
+
+            if self.update_best_goal( cost_previous ) && self.config.stop_on_solution {
+                self.stop_reason = Some( StopReason::SolutionFound );
+                break;
+            }
+        }
+
+        self.stat_time_all += timer_all.dur_ms();
+
+        self.print_stats();
+        true
+    }
+
+    fn get_trajectory_config_space( & self ) -> Vec<TObs> {
+        self.tree().nodes_config_space( &*self.param.model )
+    }
+
+    fn get_trajectory_edges_config_space( & self ) -> Vec<(Vec<TObs>,u32)> {
+        self.tree().edges_config_space( &*self.param.model )
+    }
+
+    fn get_best_trajectory_config_space( & self ) -> Vec<(Vec<TObs>,u32)> {
+        self.tree().best_path_config_space( &*self.param.model )
+    }
+
+    fn get_best_solution( & self ) -> Option<Solution<TS,TC>> {
+        self.tree().best_solution()
+    }
+
+    fn reset( & mut self ) {
+
+        self.nodes.clear();
+        self.link_parent.clear();
+        self.edges.clear();
+        self.idx_reached = None;
+        self.nodes_goal.clear();
+        self.events.clear();
+        self.stop_reason = None;
+        self.iter_exec = 0;
+        self.stat_iter_no_change = 0;
+        self.stat_iter_collision = 0;
+        self.stat_iter_invalid = 0;
+        self.stat_goal_biased_samples = 0;
+        self.stat_rewired = 0;
+        self.stat_goal_distance = None;
+        self.stat_time_all = 0.;
+
+        self.nn_query = NN_Stochastic::init( self.param.model.clone(), self.config.nn_sample_log, self.rng.gen() );
+
+        self.add_root();
+    }
+
+    fn print_stats( &self ) {
+        info!( "{}", if self.star { "rrt_star" } else { "rrt" } );
+        info!( "nodes: {}", self.nodes.len() );
+        info!( "iterations no change: {}/{}", self.stat_iter_no_change, self.iter_exec );
+        info!( "iterations collision: {}/{}", self.stat_iter_collision, self.iter_exec );
+        info!( "iterations invalid state: {}/{}", self.stat_iter_invalid, self.iter_exec );
+        info!( "stat_rewired: {}", self.stat_rewired );
+        info!( "stat_goal_biased_samples: {}", self.stat_goal_biased_samples );
+        info!( "stat_goal_distance: {:?}", self.stat_goal_distance );
+    }
+
+    fn get_stats( &self ) -> Stats {
+        Stats {
+            iterations: self.iter_exec,
+            iterations_collision: self.stat_iter_collision,
+            iterations_invalid: self.stat_iter_invalid,
+            duration_ms: self.stat_time_all,
+            goal_distance: self.stat_goal_distance,
+            stop_reason: self.stop_reason,
+            ..self.tree().stats()
+        }
+    }
+
+    fn get_sampling_distr( & self ) -> Vec<TObs> {
+        vec![]
+    }
+
+    fn take_events( & mut self ) -> Vec<PlannerEvent> {
+        std::mem::take( & mut self.events )
+    }
+
+    fn get_collision_checker( & self ) -> &CollisionChecker<TObs> {
+        &self.collision
+    }
+}
+
+#[test]
+fn test_kinorrt(){
+
+    use crate::test_fixture;
+
+    let run = |star: bool| {
+
+        //steered connections of rrt* have to end at the rewired node, which needs accurate integration
+        let config = PlannerConfig { seed: Some(2), goal_bias: 0.05, runge_kutta: true, ..PlannerConfig::default() };
+
+        let param = test_fixture::dubins( &config, 8_000 );
+        let obs = test_fixture::obstacles_empty();
+
+        //neighbourhood radius of scenario files, the default is tuned for sst
+        let param_tree = ParamTree { delta_v: 0.1, ..ParamTree::default() };
+
+        let mut rrt = KinoRRT::init( &param, obs, param_tree, config, star ).expect("planner init");
+        while rrt.iterate( Some(500) ) {}
+        rrt
+    };
+
+    //plain rrt keeps every propagation
+    let rrt = run( false );
+    assert_eq!( rrt.get_stats().stop_reason, Some(StopReason::SolutionFound) );
+    assert_eq!( rrt.nodes.len(), rrt.edges.len() + 1 );
+    assert_eq!( rrt.nodes.len() as u32, rrt.iter_exec - rrt.stat_iter_no_change + 1 );
+    assert_eq!( rrt.stat_rewired, 0 );
+
+    let sol = rrt.get_best_solution().expect("no solution");
+    assert!( rrt.reached_goal( sol.states.last().unwrap() ) );
+    assert_eq!( rrt.collision.check_solution( &rrt.param, &sol ), None );
+
+    //rrt* rewires with dubins curves and keeps costs consistent along the tree
+    let mut rrt_star = run( true );
+    assert_eq!( rrt_star.get_stats().stop_reason, Some(StopReason::SolutionFound) );
+    assert!( rrt_star.stat_rewired > 0 );
+    assert_eq!( rrt_star.nodes.len(), rrt_star.edges.len() + 1 );
+
+    let model = rrt_star.param.model.clone();
+    for ((a,b),e) in rrt_star.edges.iter() {
+        assert_eq!( rrt_star.link_parent.get( b ), Some(a) );
+        let c = rrt_star.param.cost.edge_cost( &*model, &rrt_star.nodes[*a].state, &e.control, e.duration, &rrt_star.nodes[*b].state );
+        assert!( ( rrt_star.nodes[*a].cost + c - rrt_star.nodes[*b].cost ).abs() < 1e-3 );
+    }
+
+    let events = rrt_star.take_events();
+    assert!( !events.is_empty() );
+    assert_eq!( rrt_star.get_best_solution().map(|x| x.cost ), rrt_star.get_stats().cost );
+}
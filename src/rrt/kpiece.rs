@@ -0,0 +1,559 @@
+//! KPIECE-style planner expanding from the boundary of a projection grid
+//!
+//! Nodes are binned into cells of a grid over the position dimensions of their config space projection (kpiece_cell_size of PlannerConfig).
+//! A cell is interior if all of its axis aligned neighbours exist, otherwise it is on the boundary of the explored region.
+//! Each iteration selects the most important boundary cell with probability BORDER_FRACTION (interior cell otherwise),
+//! a random node of that cell, and propagates it with a random control and duration.
+//! Boundary and interior cells are kept in queues ordered by importance, a cell is requeued whenever its importance or class changes.
+//! Importance favours cells with few nodes, selections and neighbours, and a score that drops when expansions from the cell
+//! fail or do not reach a new cell, so the tree grows along its frontier, eg: through narrow corridors, without state samples.
+//! On goal biased iterations (goal_bias of PlannerConfig) the node closest to the goal region is expanded instead.
+//! Tree storage and propagation (rrt::tree) and collision checking against the obstacle BVH are shared with SST.
+
+extern crate pretty_env_logger;
+
+use std::cmp::{Ordering,Reverse};
+use std::collections::BTreeSet;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::rrt::rrt::RRT;
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::*;
+use crate::hash::{HashSet,HashMap};
+use crate::instrumentation::*;
+use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::event::PlannerEvent;
+use crate::error::{PlannerError,Result};
+use crate::collision::{self,CollisionChecker};
+
+use super::tree::{self,Tree,Node,Edge};
+
+///probability of expanding from a boundary cell instead of an interior cell
+const BORDER_FRACTION: f32 = 0.8;
+
+///score factor of a cell after a valid expansion that does not reach a new cell
+const GOOD_SCORE_FACTOR: f32 = 0.9;
+
+///score factor of a cell after an invalid or colliding expansion
+const BAD_SCORE_FACTOR: f32 = 0.5;
+
+///cell of the projection grid
+#[derive(Clone,Debug)]
+pub struct Cell {
+    ///nodes projected into the cell, the coverage of the cell
+    pub nodes: Vec<usize>,
+    ///times the cell was selected for expansion
+    pub selections: u32,
+    pub score: f32,
+    ///existing axis aligned neighbour cells, the cell is interior once all 2 * dims exist
+    pub neighbours: u32,
+}
+
+impl Cell {
+    fn importance( & self ) -> f32 {
+        self.score / ( ( 1 + self.selections ) as f32 * ( 1 + self.neighbours ) as f32 * self.nodes.len() as f32 )
+    }
+
+    ///entry of cell ``key`` in a cell queue
+    fn entry( & self, key: &[i32] ) -> CellEntry {
+        ( Importance( self.importance() ), Reverse( key.to_vec() ) )
+    }
+}
+
+///cell importance with a total order for cell queues, never NaN as cells have at least one node
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct Importance(pub f32);
+
+impl Eq for Importance {}
+
+impl PartialOrd for Importance {
+    fn partial_cmp( & self, other: &Self ) -> Option<Ordering> {
+        Some( self.cmp( other ) )
+    }
+}
+
+impl Ord for Importance {
+    fn cmp( & self, other: &Self ) -> Ordering {
+        self.0.partial_cmp( &other.0 ).unwrap_or( Ordering::Equal )
+    }
+}
+
+///most important cell last, ties go to the lowest key
+pub type CellEntry = ( Importance, Reverse<Vec<i32>> );
+
+pub struct Kpiece<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    pub param: Param<TS,TC,TObs>,
+
+    ///obstacles and sub-state resolution of collision checking
+    pub collision: CollisionChecker<TObs>,
+
+    ///runtime planner behaviour switches
+    pub config: PlannerConfig,
+
+    pub nodes: Vec< Node<TS> >,
+    pub link_parent: HashMap< usize, usize >, //node -> node_parent
+    pub edges: HashMap< (usize,usize), Edge<TS,TC> >,
+
+    pub monte_carlo_prop_l: f32,
+    pub monte_carlo_prop_h: f32,
+
+    ///grid dimensions, leading position dimensions of the config space projection
+    pub dims: usize,
+
+    ///cell index along each grid dimension -> cell
+    pub cells: HashMap< Vec<i32>, Cell >,
+
+    ///cells with fewer than 2 * dims neighbours, ordered by importance
+    pub cells_boundary: BTreeSet<CellEntry>,
+
+    ///cells with all neighbours, ordered by importance
+    pub cells_interior: BTreeSet<CellEntry>,
+
+    ///node closest to goal region, expanded on goal biased iterations
+    pub idx_closest: usize,
+
+    ///lowest cost node satisfying goal condition
+    pub idx_reached: Option<usize>,
+
+    ///all nodes in the tree satisfying goal condition
+    pub nodes_goal: HashSet<usize>,
+
+    ///events since last call to take_events
+    pub events: Vec<PlannerEvent>,
+
+    ///set when the run is over, see StopCondition and PlannerConfig::stop_on_solution
+    pub stop_reason: Option<StopReason>,
+
+    pub iter_exec: u32,
+    pub stat_iter_no_change: u32,
+    pub stat_iter_collision: u32,
+    pub stat_iter_invalid: u32,
+    pub stat_goal_biased_samples: u32,
+
+    ///expansions reaching a cell without nodes
+    pub stat_new_cells: u32,
+
+    ///closest distance to goal region of states added to the tree
+    pub stat_goal_distance: Option<f32>,
+
+    pub stat_time_all: f64,
+
+    ///source of all randomness of the planner, seeded from config
+    pub rng: StdRng,
+}
+
+impl <TS,TC,TObs> Kpiece<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    pub fn init( param: & Param<TS,TC,TObs>,
+                 obstacles_concrete: ParamObstacles<TObs>,
+                 param_tree: ParamTree,
+                 config: PlannerConfig ) -> Result<Self> {
+
+        tree::validate( "kpiece", param, &param_tree, &config, false )?;
+
+        if !( config.kpiece_cell_size > 0. ) {
+            return Err( PlannerError::InvalidParam( format!("kpiece_cell_size {} not positive", config.kpiece_cell_size ) ) )
+        }
+
+        let rng = tree::rng( &config );
+
+        let dims = param.model.position_dims()
+            .min( param.model.project_state_to_config( param.states_init.clone() ).get_num_dims() as usize );
+
+        let mut s = Self {
+            param: param.clone(),
+            collision: CollisionChecker::init( obstacles_concrete, config.collision_step( param.sim_delta ) ),
+            nodes: vec![],
+            link_parent: HashMap::default(),
+            edges: HashMap::default(),
+            monte_carlo_prop_l: param_tree.prop_delta_low,
+            monte_carlo_prop_h: param_tree.prop_delta_high,
+            dims: dims,
+            cells: HashMap::default(),
+            cells_boundary: BTreeSet::new(),
+            cells_interior: BTreeSet::new(),
+            idx_closest: 0,
+            idx_reached: None,
+            nodes_goal: HashSet::default(),
+            events: vec![],
+            stop_reason: None,
+            iter_exec: 0,
+            stat_iter_no_change: 0,
+            stat_iter_collision: 0,
+            stat_iter_invalid: 0,
+            stat_goal_biased_samples: 0,
+            stat_new_cells: 0,
+            stat_goal_distance: None,
+            stat_time_all: 0.,
+            rng: rng,
+            config: config,
+        };
+
+        s.add_root();
+
+        Ok( s )
+    }
+
+    fn add_root( & mut self ) {
+
+        self.nodes.push( Node { id: 0,
+                                state: self.param.states_init.clone(),
+                                children: HashSet::default(),
+                                cost: 0. } );
+
+        let key = self.cell_key( &self.param.states_init );
+        self.add_to_cell( 0, key );
+    }
+
+    pub fn reached_goal( & self, states: &TS ) -> bool {
+        self.param.goal_region.contains( &*self.param.model, states, &self.param.states_goal )
+    }
+
+    fn tree( & self ) -> Tree<'_,TS,TC> {
+        Tree { nodes: &self.nodes,
+               link_parent: &self.link_parent,
+               edges: &self.edges,
+               nodes_goal: &self.nodes_goal,
+               idx_reached: self.idx_reached }
+    }
+
+    ///grid cell of the config space projection of ``state``
+    pub fn cell_key( & self, state: &TS ) -> Vec<i32> {
+        let q = self.param.model.project_state_to_config( state.clone() ).get_vals();
+        q[..self.dims].iter()
+            .map(|x| ( x / self.config.kpiece_cell_size ).floor() as i32 )
+            .collect()
+    }
+
+    ///applies ``f`` to cell ``key`` and requeues it by its new importance and class
+    fn update_cell<F>( & mut self, key: &[i32], f: F ) where F: FnOnce( & mut Cell ) {
+
+        let interior = 2 * self.dims as u32;
+
+        let c = match self.cells.get_mut( key ) {
+            Some(c) => { c },
+            _ => { return },
+        };
+
+        let queue = if c.neighbours < interior { & mut self.cells_boundary } else { & mut self.cells_interior };
+        queue.remove( &c.entry( key ) );
+
+        f( c );
+
+        let queue = if c.neighbours < interior { & mut self.cells_boundary } else { & mut self.cells_interior };
+        queue.insert( c.entry( key ) );
+    }
+
+    ///adds node ``idx`` to cell ``key``, returns true if the cell is new
+    fn add_to_cell( & mut self, idx: usize, key: Vec<i32> ) -> bool {
+
+        if self.cells.contains_key( &key ) {
+            self.update_cell( &key, |c| c.nodes.push( idx ) );
+            return false
+        }
+
+        let mut neighbours = 0;
+
+        for d in 0..self.dims {
+            for offset in [ -1, 1 ].iter() {
+                let mut k = key.clone();
+                k[d] += offset;
+                if self.cells.contains_key( &k ) {
+                    self.update_cell( &k, |c| c.neighbours += 1 );
+                    neighbours += 1;
+                }
+            }
+        }
+
+        let c = Cell { nodes: vec![ idx ],
+                       selections: 0,
+                       score: 1.,
+                       neighbours: neighbours };
+
+        if neighbours < 2 * self.dims as u32 {
+            self.cells_boundary.insert( c.entry( &key ) );
+        } else {
+            self.cells_interior.insert( c.entry( &key ) );
+        }
+
+        self.cells.insert( key, c );
+        true
+    }
+
+    ///most important cell on the boundary with probability BORDER_FRACTION, in the interior otherwise,
+    ///falls back to the other set if the chosen one is empty
+    fn select_cell( & mut self ) -> Vec<i32> {
+
+        let border = self.rng.gen_range(0., 1.) < BORDER_FRACTION;
+
+        let ( first, second ) = if border {
+            ( &self.cells_boundary, &self.cells_interior )
+        } else {
+            ( &self.cells_interior, &self.cells_boundary )
+        };
+
+        first.iter().next_back()
+            .or_else(|| second.iter().next_back() )
+            .map(|(_,k)| k.0.clone() )
+            .expect("no cells")
+    }
+
+    ///insert node into propagation tree and return index of the inserted node
+    fn insert_node( & mut self,
+                    idx_parent: usize,
+                    state: TS,
+                    control: TC,
+                    duration: f32,
+                    cost: f32,
+                    substates: &[TS] ) -> usize {
+
+        let idx_new = self.nodes.len();
+
+        self.nodes.push( Node { id: idx_new,
+                                state: state,
+                                children: HashSet::default(),
+                                cost: cost } );
+
+        self.nodes[idx_parent].children.insert( idx_new );
+        self.link_parent.insert( idx_new, idx_parent );
+
+        let e = Edge::init( &self.config, control, duration, 0, substates );
+        self.edges.insert( (idx_parent, idx_new), e );
+
+        idx_new
+    }
+
+}
+
+impl <TS,TC,TObs> RRT < TS,TC,TObs > for Kpiece<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
+
+    fn iterate( & mut self, iteration: Option<u32> ) -> bool {
+
+        let iter_batch = match tree::batch( &self.param.stop_condition, &mut self.stop_reason, self.iter_exec, self.stat_time_all, iteration ) {
+            Some(x) => { x },
+            _ => { return false },
+        };
+
+        let mut timer_all = Timer::default();
+
+        for _ in 0..iter_batch {
+
+            if tree::stopped( &self.param.stop_condition, &mut self.stop_reason, self.iter_exec, self.stat_time_all + timer_all.dur_ms() ) {
+                break;
+            }
+
+            self.iter_exec += 1;
+
+            let goal_biased = self.config.goal_bias > 0. && self.rng.gen_range(0., 1.) < self.config.goal_bias;
+
+            //cell of the expanded node, score is not updated on goal biased iterations
+            let ( idx_parent, key_parent ) = if goal_biased {
+                self.stat_goal_biased_samples += 1;
+                ( self.idx_closest, None )
+            } else {
+                let key = self.select_cell();
+                self.update_cell( &key, |c| c.selections += 1 );
+                let cell = self.cells.get( &key ).expect("cell not found");
+                let idx = cell.nodes[ self.rng.gen_range( 0, cell.nodes.len() ) ];
+                ( idx, Some(key) )
+            };
+
+            let state_start = self.nodes[idx_parent].state.clone();
+
+            let ( duration, control ) = tree::monte_carlo_propagation( &self.param, self.monte_carlo_prop_l, self.monte_carlo_prop_h, &mut self.rng );
+
+            let resolution = tree::resolution( &self.param, &self.collision, &self.config );
+
+            let substates = collision::integrate( &*self.param.model, state_start.clone(), control.clone(), duration, resolution );
+
+            if tree::rejected( &self.param, &self.collision, &self.param.model.project_state_to_config( state_start.clone() ), &substates,
+                               &mut self.stat_iter_invalid, &mut self.stat_iter_collision ) {
+                self.stat_iter_no_change += 1;
+                if let Some(k) = key_parent {
+                    self.update_cell( &k, |c| c.score *= BAD_SCORE_FACTOR );
+                }
+                continue;
+            }
+
+            let state_new = substates.last().unwrap().clone();
+
+            let cost = self.nodes[idx_parent].cost +
+                self.param.cost.edge_cost( &*self.param.model, &state_start, &control, duration, &state_new );
+
+            let idx_new = self.insert_node( idx_parent, state_new.clone(), control, duration, cost, &substates );
+
+            let key_new = self.cell_key( &state_new );
+
+            if self.add_to_cell( idx_new, key_new ) {
+                self.stat_new_cells += 1;
+            } else if let Some(k) = key_parent {
+                self.update_cell( &k, |c| c.score *= GOOD_SCORE_FACTOR );
+            }
+
+            let d = self.param.goal_region.distance( &*self.param.model, &state_new, &self.param.states_goal );
+            if self.stat_goal_distance.map_or( true, |x| d < x ) {
+                self.stat_goal_distance = Some(d);
+                self.idx_closest = idx_new;
+            }
+
+            if self.reached_goal( &state_new ) {
+
+                self.nodes_goal.insert( idx_new );
+
+                let cost_previous = self.idx_reached.map(|x| self.nodes[x].cost );
+
+                if cost_previous.map_or( true, |c| cost < c ) {
+
+                    match cost_previous {
+                        None => { info!("found a path to goal on iteration: {}", self.iter_exec ); },
+                        Some(c) => { info!("found a better path to goal on iteration: {}, cost: {} -> {}", self.iter_exec, c, cost ); },
+                    }
+
+                    self.idx_reached = Some( idx_new );
+
+                    self.events.push( PlannerEvent::NewBestSolution { iteration: self.iter_exec,
+                                                                      cost: cost,
+                                                                      cost_previous: cost_previous } );
+
+                    if self.config.stop_on_solution {
+                        self.stop_reason = Some( StopReason::SolutionFound );
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.stat_time_all += timer_all.dur_ms();
+
+        self.print_stats();
+        true
+    }
+
+    fn get_trajectory_config_space( & self ) -> Vec<TObs> {
+        self.tree().nodes_config_space( &*self.param.model )
+    }
+
+    fn get_trajectory_edges_config_space( & self ) -> Vec<(Vec<TObs>,u32)> {
+        self.tree().edges_config_space( &*self.param.model )
+    }
+
+    fn get_best_trajectory_config_space( & self ) -> Vec<(Vec<TObs>,u32)> {
+        self.tree().best_path_config_space( &*self.param.model )
+    }
+
+    fn get_best_solution( & self ) -> Option<Solution<TS,TC>> {
+        self.tree().best_solution()
+    }
+
+    fn reset( & mut self ) {
+
+        self.nodes.clear();
+        self.link_parent.clear();
+        self.edges.clear();
+        self.cells.clear();
+        self.cells_boundary.clear();
+        self.cells_interior.clear();
+        self.idx_closest = 0;
+        self.idx_reached = None;
+        self.nodes_goal.clear();
+        self.events.clear();
+        self.stop_reason = None;
+        self.iter_exec = 0;
+        self.stat_iter_no_change = 0;
+        self.stat_iter_collision = 0;
+        self.stat_iter_invalid = 0;
+        self.stat_goal_biased_samples = 0;
+        self.stat_new_cells = 0;
+        self.stat_goal_distance = None;
+        self.stat_time_all = 0.;
+
+        self.add_root();
+    }
+
+    fn print_stats( &self ) {
+        info!( "kpiece" );
+        info!( "nodes: {}", self.nodes.len() );
+        info!( "cells: {}, boundary: {}", self.cells.len(), self.cells_boundary.len() );
+        info!( "iterations no change: {}/{}", self.stat_iter_no_change, self.iter_exec );
+        info!( "iterations collision: {}/{}", self.stat_iter_collision, self.iter_exec );
+        info!( "iterations invalid state: {}/{}", self.stat_iter_invalid, self.iter_exec );
+        info!( "stat_new_cells: {}", self.stat_new_cells );
+        info!( "stat_goal_biased_samples: {}", self.stat_goal_biased_samples );
+        info!( "stat_goal_distance: {:?}", self.stat_goal_distance );
+    }
+
+    fn get_stats( &self ) -> Stats {
+        Stats {
+            iterations: self.iter_exec,
+            iterations_collision: self.stat_iter_collision,
+            iterations_invalid: self.stat_iter_invalid,
+            duration_ms: self.stat_time_all,
+            goal_distance: self.stat_goal_distance,
+            stop_reason: self.stop_reason,
+            ..self.tree().stats()
+        }
+    }
+
+    fn get_sampling_distr( & self ) -> Vec<TObs> {
+        vec![]
+    }
+
+    fn take_events( & mut self ) -> Vec<PlannerEvent> {
+        std::mem::take( & mut self.events )
+    }
+
+    fn get_collision_checker( & self ) -> &CollisionChecker<TObs> {
+        &self.collision
+    }
+}
+
+#[test]
+fn test_kpiece(){
+
+    use std::sync::Arc;
+    use crate::states::States3D;
+    use crate::validity::StateConstraint;
+    use crate::test_fixture;
+
+    let config = PlannerConfig { seed: Some(1), collision_resolution: Some(0.01), ..PlannerConfig::default() };
+
+    let mut param = test_fixture::dubins( &config, 30_000 );
+    param.states_init = States3D([ 0.2, 0.2, 0. ]);
+    param.states_goal = States3D([ 0.2, 0.8, 0. ]);
+    //wall across the workspace with a narrow gap, goal behind it
+    param.constraints = vec![ StateConstraint::Predicate( Arc::new(|x: &States3D| {
+        ( x.0[1] - 0.5 ).abs() > 0.02 || ( x.0[0] > 0.6 && x.0[0] < 0.66 )
+    }) ) ];
+    let obs = test_fixture::obstacles_empty();
+
+    let mut kpiece = Kpiece::init( &param, obs, ParamTree::default(), config ).expect("planner init");
+    while kpiece.iterate( Some(500) ) {}
+
+    assert_eq!( kpiece.get_stats().stop_reason, Some(StopReason::SolutionFound) );
+
+    //every node is in the cell of its projection, and neighbour counts match the grid
+    assert_eq!( kpiece.cells.values().map(|x| x.nodes.len() ).sum::<usize>(), kpiece.nodes.len() );
+    assert_eq!( kpiece.stat_new_cells as usize + 1, kpiece.cells.len() );
+    for (k,c) in kpiece.cells.iter() {
+        assert!( c.nodes.iter().all(|x| kpiece.cell_key( &kpiece.nodes[*x].state ) == *k ) );
+        let n = (0..kpiece.dims).flat_map(|d| [ -1, 1 ].iter().map(move |o| (d,*o) ) )
+            .filter(|(d,o)| { let mut k2 = k.clone(); k2[*d] += o; kpiece.cells.contains_key( &k2 ) } )
+            .count();
+        assert_eq!( c.neighbours as usize, n );
+        let queue = if n < 2 * kpiece.dims { &kpiece.cells_boundary } else { &kpiece.cells_interior };
+        assert!( queue.contains( &c.entry( k ) ) );
+    }
+
+    //queues hold each cell once, with its current importance
+    assert_eq!( kpiece.cells_boundary.len() + kpiece.cells_interior.len(), kpiece.cells.len() );
+    assert!( !kpiece.cells_interior.is_empty() );
+
+    let sol = kpiece.get_best_solution().expect("no solution");
+    assert!( kpiece.reached_goal( sol.states.last().unwrap() ) );
+    assert_eq!( kpiece.collision.check_solution( &kpiece.param, &sol ), None );
+}
@@ -1,4 +1,8 @@
 pub mod rrt;
 pub mod sst;
+pub mod tree;
 pub mod nn_naive;
 pub mod nn_stochastic;
+pub mod checkpoint;
+pub mod kinorrt;
+pub mod kpiece;
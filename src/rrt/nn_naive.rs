@@ -1,18 +1,17 @@
 extern crate pretty_env_logger;
 
-use std::collections::{HashSet,HashMap};
+use crate::hash::HashSet;
 use std::marker::PhantomData;
 use std::cmp::Ordering;
 
-use rand::Rng;
-
 use crate::states::States;
 use crate::control::Control;
-use crate::planner_param::{Param,ParamObstacles};
+use crate::planner_param::Param;
 
-use crate::rrt::sst::Node;
+use crate::rrt::tree::Node;
 
 #[derive(Default)]
+#[allow(non_camel_case_types)]
 pub struct NN_Naive<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub phantom_ts: PhantomData< TS >,
     pub phantom_tc: PhantomData< TC >,
@@ -24,7 +23,7 @@ impl<TS,TC,TObs> NN_Naive<TS,TC,TObs> where TS: States, TC: Control, TObs: State
     ///return the node index of the best node with respect to cost function within delta_v vicinity or else return the node index of the nearest node
     pub fn query_nearest_state_active( & self,
                                        sample_state: TS,
-                                       ns: & Vec< Node<TS> >,
+                                       ns: & [ Node<TS> ],
                                        nodes_active: & HashSet< usize >,
                                        param: & Param<TS,TC,TObs>,
                                        delta_v: f32 ) -> usize {
@@ -32,7 +31,7 @@ impl<TS,TC,TObs> NN_Naive<TS,TC,TObs> where TS: States, TC: Control, TObs: State
         //consider vicinity
         let node_best = nodes_active.iter()
             .map(|x| &ns[*x] )
-            .filter(|x| (param.ss_metric)( x.state.clone(), sample_state.clone() ) < delta_v )
+            .filter(|x| param.model.ss_metric( x.state.clone(), sample_state.clone() ) < delta_v )
             .min_by(|a,b| a.cost.partial_cmp( &b.cost ).unwrap_or(Ordering::Equal) );
 
         match node_best {
@@ -51,7 +50,7 @@ impl<TS,TC,TObs> NN_Naive<TS,TC,TObs> where TS: States, TC: Control, TObs: State
     ///return witness index within delta_s of query_state, or else return None
     pub fn query_nearest_witness( & self,
                                   query_state: TS,
-                                  witnesses: & Vec< TS >,
+                                  witnesses: & [ TS ],
                                   param: & Param<TS,TC,TObs>,
                                   delta_s: f32 ) -> Option<usize> {
 
@@ -60,7 +59,7 @@ impl<TS,TC,TObs> NN_Naive<TS,TC,TObs> where TS: States, TC: Control, TObs: State
         let witness_match = witnesses.iter().enumerate()
             .filter_map(|(idx,x)|
                         {
-                            let dist = (param.ss_metric)( x.clone(), query_state.clone() );
+                            let dist = param.model.ss_metric( x.clone(), query_state.clone() );
                             if dist< delta_s {
                                 Some( (idx,dist) )
                             } else {
@@ -68,13 +67,6 @@ impl<TS,TC,TObs> NN_Naive<TS,TC,TObs> where TS: States, TC: Control, TObs: State
                             }
                         } )
             .min_by(|a,b| a.1.partial_cmp(&b.1).unwrap_or(Equal) );
-        match witness_match {
-            Some(x) => {
-                Some(x.0)
-            },
-            _ => {
-                None
-            },
-        }
+        witness_match.map(|x| x.0 )
     }
 }
@@ -2,24 +2,50 @@
 //! Asymptotically Optimal Sampling-Based Kinodynnamic Planning paper
 extern crate pretty_env_logger;
 
-use std::collections::{HashSet,HashMap};
 use std::marker::PhantomData;
 use std::cmp::Ordering;
 use std::cmp;
+use std::sync::Arc;
 
 use rand::Rng;
 use rand::prelude::*;
-use rand::distributions::Standard;
+use rand::rngs::StdRng;
 
 use crate::states::States;
 use crate::control::Control;
-use crate::planner_param::{Param,ParamObstacles};
+use crate::system_model::SystemModel;
+use crate::hash::{HashSet,HashMap};
 
-use crate::rrt::sst::Node;
+use serde::{Serialize,Deserialize};
 
-use std::io::Read;
+///graph of a NN_Stochastic, without model and random number generator, for checkpoints
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States
+pub struct NNGraph<TS> where TS: States {
+    pub edges: HashMap< usize, HashSet<usize> >,
+    pub nodes: Vec<TS>,
+    pub nodes_map: HashMap< usize, usize >,
+    pub inverse_map: HashMap< usize, usize >,
+    pub lookup_alive: HashSet<usize>,
+    pub list_free: Vec<usize>,
+    pub list_valence_fixup: Vec<usize>,
+    pub stat_valence_fixups: usize,
+}
+
+impl<TS> NNGraph<TS> where TS: States {
+
+    ///true if local indices are within nodes and global indices are below ``num_global``
+    pub fn in_range( & self, num_global: usize ) -> bool {
+        let num_local = self.nodes.len();
+        self.edges.iter().all(|(a,x)| *a < num_local && x.iter().all(|b| *b < num_local ) ) &&
+            self.nodes_map.iter().all(|(a,b)| *a < num_global && *b < num_local ) &&
+            self.inverse_map.iter().all(|(a,b)| *a < num_local && *b < num_global ) &&
+            self.lookup_alive.iter().chain( self.list_free.iter() ).chain( self.list_valence_fixup.iter() ).all(|x| *x < num_local )
+    }
+}
 
 #[derive(Debug)]
+#[allow(non_camel_case_types)]
 pub struct NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub phantom_ts: PhantomData< TS >,
     pub phantom_tc: PhantomData< TC >,
@@ -38,44 +64,76 @@ pub struct NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs: States
 
     pub list_valence_fixup: Vec<usize>,
 
-    pub f_metric: fn(TS,TS)->f32,
+    ///provides distance function in state space
+    pub model: Arc<dyn SystemModel<TS,TC,TObs>>,
 
     pub stat_valence_fixups: usize,
+
+    ///use proportional to log(# nodes) for query samples, defaults to sqrt(# nodes)
+    pub sample_log: bool,
+
+    ///random number generator for query sampling
+    pub rng: StdRng,
 }
 
 impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
-    pub fn init( f: fn(TS,TS)->f32 ) -> Self {
+    pub fn init( model: Arc<dyn SystemModel<TS,TC,TObs>>, sample_log: bool, seed: u64 ) -> Self {
         Self {
             phantom_ts: PhantomData,
             phantom_tc: PhantomData,
             phantom_tobs: PhantomData,
 
-            edges: HashMap::new(),
+            edges: HashMap::default(),
             
             nodes: vec![],
-            nodes_map: HashMap::new(),
-            inverse_map: HashMap::new(),
+            nodes_map: HashMap::default(),
+            inverse_map: HashMap::default(),
 
-            lookup_alive: HashSet::new(),
+            lookup_alive: HashSet::default(),
             // list_alive: vec![],
             list_free: vec![],
 
             list_valence_fixup: vec![],
             
-            f_metric: f,
+            model: model,
 
             stat_valence_fixups: 0,
+
+            sample_log: sample_log,
+
+            rng: StdRng::seed_from_u64( seed ),
         }
     }
 
+    pub fn graph( & self ) -> NNGraph<TS> {
+        NNGraph {
+            edges: self.edges.clone(),
+            nodes: self.nodes.clone(),
+            nodes_map: self.nodes_map.clone(),
+            inverse_map: self.inverse_map.clone(),
+            lookup_alive: self.lookup_alive.clone(),
+            list_free: self.list_free.clone(),
+            list_valence_fixup: self.list_valence_fixup.clone(),
+            stat_valence_fixups: self.stat_valence_fixups,
+        }
+    }
+
+    ///replaces graph with ``g``, model and random number generator are kept
+    pub fn restore_graph( & mut self, g: NNGraph<TS> ){
+        self.edges = g.edges;
+        self.nodes = g.nodes;
+        self.nodes_map = g.nodes_map;
+        self.inverse_map = g.inverse_map;
+        self.lookup_alive = g.lookup_alive;
+        self.list_free = g.list_free;
+        self.list_valence_fixup = g.list_valence_fixup;
+        self.stat_valence_fixups = g.stat_valence_fixups;
+    }
+
     // #[inline]
     fn edge_add( & mut self, a: usize, b: usize ){
-        if !self.edges.contains_key( &a ) {
-            self.edges.insert( a, HashSet::new() );
-        }
-        let mut hs = self.edges.get_mut( &a ).unwrap();
-        hs.insert( b );
+        self.edges.entry( a ).or_default().insert( b );
     }
 
     // #[inline]
@@ -89,14 +147,11 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
         };
         
         connected.into_iter().for_each(|x|{
-            match self.edges.get_mut(&x) {
-                Some(i) => {
-                    i.remove(&idx_local);
-                    if i.is_empty() {
-                        self.edges.remove(&x);
-                    }
-                },
-                _ => {},
+            if let Some(i) = self.edges.get_mut(&x) {
+                i.remove(&idx_local);
+                if i.is_empty() {
+                    self.edges.remove(&x);
+                }
             }});
 
         self.edges.remove( &idx_local );
@@ -105,7 +160,7 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
     ///adds a new node by query for k (log(number of total nodes)) nearest nodes
     ///and adding edges to these nodes
     // #[inline]
-    pub fn add( & mut self, state: TS, idx_global: usize, f: fn(TS,TS)->f32 ) -> usize {
+    pub fn add( & mut self, state: TS, idx_global: usize, f: &dyn SystemModel<TS,TC,TObs> ) -> usize {
         
         let k = if self.lookup_alive.len() < 50 {
             self.lookup_alive.len()
@@ -113,11 +168,10 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
             cmp::min( 2 * ((self.lookup_alive.len() as f32).log2() as usize), self.lookup_alive.len() )
         };
 
-        debug_assert!( k >= 0 );
         
         let arr = self.query_nearest_k( state.clone(), f, k );
         
-        let node_idx_new = if self.list_free.len() > 0 {
+        let node_idx_new = if !self.list_free.is_empty() {
             let idx = self.list_free.pop().unwrap();
             self.nodes[idx] = state;
             debug_assert!( !self.lookup_alive.contains(&idx) );
@@ -135,7 +189,7 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
 
         debug_assert!( !self.edges.contains_key( &node_idx_new ) );
         
-        arr.iter().for_each(|(idx_l,idx_g)|{
+        arr.iter().for_each(|(idx_l,_idx_g)|{
             self.edge_add( node_idx_new, *idx_l );
             self.edge_add( *idx_l, node_idx_new );
         });
@@ -172,19 +226,17 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
     ///get valence number of nodes
     fn sample_valence(&self) -> usize {
 
-        let valence = if self.lookup_alive.len() < 50 {
+        if self.lookup_alive.len() < 50 {
             self.lookup_alive.len()
         } else {
-            cmp::min( (((self.lookup_alive.len() as f32).log2()) as usize), self.lookup_alive.len() )
-        };
-        
-        valence
+            cmp::min( ((self.lookup_alive.len() as f32).log2()) as usize, self.lookup_alive.len() )
+        }
     }
 
     fn query_sample_count(&self) -> usize {
         let n = self.lookup_alive.len();
-        let valence = if cfg!(feature="nn_sample_log") {
-            cmp::min(((n as f32).log2() as usize), n )
+        let _valence = if self.sample_log {
+            cmp::min((n as f32).log2() as usize, n )
         } else {
             (n as f32).sqrt() as usize
         };
@@ -201,12 +253,14 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
 
         self.stat_valence_fixups += v.len();
         
+        let model = self.model.clone();
+        
         v.into_iter()
             .for_each(|x|{
                 let state = self.nodes[x].clone();
                 let idx_global = *self.inverse_map.get( &x ).expect("inverse map of node not exist");
                 self.remove(idx_global);
-                self.add( state, idx_global, self.f_metric );
+                self.add( state, idx_global, &*model );
             });
         debug_assert!(self.list_valence_fixup.is_empty());
     }
@@ -217,15 +271,15 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
     ///iterate until it does not improve anymore.
     ///returns (idx_local,idx_global)
     // #[inline]
-    pub fn query_nearest( & mut self, state_query: TS, f: fn(TS,TS)->f32 ) -> Option<(usize,usize)> {
+    pub fn query_nearest( & mut self, state_query: TS, f: &dyn SystemModel<TS,TC,TObs> ) -> Option<(usize,usize)> {
         
         let n = self.lookup_alive.len();
 
         if n < 100 {
             
             let idx_local = self.lookup_alive.iter().min_by(|a,b| {
-                let cost_a = f(self.nodes[**a].clone(), state_query.clone());
-                let cost_b = f(self.nodes[**b].clone(), state_query.clone());
+                let cost_a = f.ss_metric(self.nodes[**a].clone(), state_query.clone());
+                let cost_b = f.ss_metric(self.nodes[**b].clone(), state_query.clone());
                 cost_a.partial_cmp( &cost_b ).unwrap_or(Ordering::Equal)
             });
             
@@ -242,19 +296,16 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
             let n_valence = self.sample_valence();
             let n_query_sample = self.query_sample_count();
             
-            let mut rng = rand::thread_rng();
-
             //valence fixups
             {
+                let sample_idxs : Vec<usize> = (0..n_query_sample).map(|_x|{ self.rng.gen_range(0, n) }).collect();
+                
                 let sample_list : Vec<_> = self.lookup_alive.iter().collect();
 
-                let mut candidate_rewire_nodes = HashSet::new();
+                let mut candidate_rewire_nodes = HashSet::default();
                 
-                (0..n_query_sample).map(|x|{ let i : usize = rng.gen_range(0, n); i })
-                    .map(|i|{
-                        let idx = *sample_list[i];
-                        idx
-                    }).for_each(|i|{
+                sample_idxs.into_iter()
+                    .map(|i| *sample_list[i] ).for_each(|i|{
                         match self.edges.get(&i){
                             Some(neighbours)=>{
                                 if neighbours.len() < n_valence *4/5 {
@@ -275,17 +326,16 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
                 self.valence_fixup();
             }
 
+            let sample_idxs : Vec<usize> = (0..n_query_sample).map(|_x|{ self.rng.gen_range(0, n) }).collect();
+            
             let sample_list : Vec<_> = self.lookup_alive.iter().collect();
             
             let mut idx_local = {
-                (0..n_query_sample).map(|x|{ let i : usize = rng.gen_range(0, n); i })
-                    .map(|i|{
-                        let idx = *sample_list[i];
-                        idx
-                    })
+                sample_idxs.into_iter()
+                    .map(|i| *sample_list[i] )
                     .min_by(|idx_a,idx_b| { 
-                        let cost_a = f(self.nodes[*idx_a].clone(), state_query.clone());
-                        let cost_b = f(self.nodes[*idx_b].clone(), state_query.clone());
+                        let cost_a = f.ss_metric(self.nodes[*idx_a].clone(), state_query.clone());
+                        let cost_b = f.ss_metric(self.nodes[*idx_b].clone(), state_query.clone());
                         cost_a.partial_cmp( &cost_b ).unwrap_or(Ordering::Equal)
                     }).expect("no nodes")
             };
@@ -303,8 +353,8 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
                     Some(x) => {
                         let temp = [idx_local].to_vec();
                         let idx_nearest = x.iter().chain(temp.iter()).min_by(|a,b|{
-                            let cost_a = f(self.nodes[**a].clone(), state_query.clone());
-                            let cost_b = f(self.nodes[**b].clone(), state_query.clone());
+                            let cost_a = f.ss_metric(self.nodes[**a].clone(), state_query.clone());
+                            let cost_b = f.ss_metric(self.nodes[**b].clone(), state_query.clone());
                             cost_a.partial_cmp( &cost_b ).unwrap_or(Ordering::Equal)
                         }).expect("no nodes");
                         *idx_nearest
@@ -328,15 +378,15 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
     ///until convergence of the list
     pub fn query_nearest_k( & mut self,
                               state_query: TS,
-                              f: fn(TS,TS)->f32,
+                              f: &dyn SystemModel<TS,TC,TObs>,
                               k: usize ) -> Vec<(usize,usize)> {
         
-        let (idx_local,idx_global) = match self.query_nearest( state_query.clone(), f ) {
+        let (idx_local,_idx_global) = match self.query_nearest( state_query.clone(), f ) {
             Some(x) => { x },
             _ => { return vec![] },
         };
 
-        let mut items_k = HashSet::new();
+        let mut items_k = HashSet::default();
         items_k.insert( idx_local );
         
         //loop until k nearest items converge
@@ -351,7 +401,7 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
                 break;
             }
             
-            let mut temp = HashSet::new();
+            let mut temp = HashSet::default();
             
             for i in items_k.iter() {
                 
@@ -367,8 +417,8 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
                         
                         arr.push(*i);                        
                         arr.sort_by(|a,b|{
-                            let cost_a = f(self.nodes[*a].clone(), state_query.clone());
-                            let cost_b = f(self.nodes[*b].clone(), state_query.clone());
+                            let cost_a = f.ss_metric(self.nodes[*a].clone(), state_query.clone());
+                            let cost_b = f.ss_metric(self.nodes[*b].clone(), state_query.clone());
                             cost_a.partial_cmp( &cost_b ).unwrap_or(Ordering::Equal)
                         });
                         arr.iter()
@@ -385,8 +435,8 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
 
             let mut temp_arr : Vec<_> = temp.into_iter().collect();
             temp_arr.sort_by(|a,b|{
-                let cost_a = f(self.nodes[*a].clone(), state_query.clone());
-                let cost_b = f(self.nodes[*b].clone(), state_query.clone());
+                let cost_a = f.ss_metric(self.nodes[*a].clone(), state_query.clone());
+                let cost_b = f.ss_metric(self.nodes[*b].clone(), state_query.clone());
                 cost_a.partial_cmp( &cost_b ).unwrap_or(Ordering::Equal)
             });
             
@@ -412,7 +462,7 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
     ///until convergence of the list
     pub fn query_nearest_threshold( & mut self,
                                       state_query: TS,
-                                      f: fn(TS,TS)->f32,
+                                      f: &dyn SystemModel<TS,TC,TObs>,
                                       threshold: f32 ) -> Vec<(usize,usize)> {
 
         let k = if self.lookup_alive.len() < 30 {
@@ -421,15 +471,15 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
             (self.lookup_alive.len() as f32).log2() as usize
         };
         
-        let mut candidates = self.query_nearest_k( state_query.clone(), f, k );
+        let candidates = self.query_nearest_k( state_query.clone(), f, k );
         
         let mut arr : Vec<_> = candidates.into_iter().filter(|(idx_local,_)|{
-            f(state_query.clone(),self.nodes[*idx_local].clone()) < threshold
+            f.ss_metric(state_query.clone(),self.nodes[*idx_local].clone()) < threshold
         }).collect();
         
         arr.sort_by(|(idx_a,_),(idx_b,_)|{
-            let cost_a = f(self.nodes[*idx_a].clone(), state_query.clone());
-            let cost_b = f(self.nodes[*idx_b].clone(), state_query.clone());
+            let cost_a = f.ss_metric(self.nodes[*idx_a].clone(), state_query.clone());
+            let cost_b = f.ss_metric(self.nodes[*idx_b].clone(), state_query.clone());
             cost_a.partial_cmp( &cost_b ).unwrap_or(Ordering::Equal)
         });
         
@@ -439,12 +489,12 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
     pub fn query_dist_node_neighbourhood_avg( & self,
                                                 state_query: TS,
                                                 node_idx_global: usize,
-                                                f_ss_metric: fn(TS,TS)->f32,
-                                                hop_dist: usize ) -> f32 {
+                                                f_ss_metric: &dyn SystemModel<TS,TC,TObs>,
+                                                _hop_dist: usize ) -> f32 {
         
         let idx_local = *self.nodes_map.get( &node_idx_global ).expect("node does not exit");
 
-        let dist = f_ss_metric( self.nodes[idx_local].clone(), state_query.clone() );
+        let dist = f_ss_metric.ss_metric( self.nodes[idx_local].clone(), state_query.clone() );
 
         let d = match self.edges.get( &idx_local ) {
             Some(x) => {
@@ -452,7 +502,7 @@ impl<TS,TC,TObs> NN_Stochastic<TS,TC,TObs> where TS: States, TC: Control, TObs:
                     dist
                 } else {
                     let d_surround = x.iter()
-                        .fold( 0., |acc,i| acc + f_ss_metric( self.nodes[*i].clone(), state_query.clone() ) );
+                        .fold( 0., |acc,i| acc + f_ss_metric.ss_metric( self.nodes[*i].clone(), state_query.clone() ) );
                     
                     ( d_surround + dist ) / ( x.len() + 1 ) as f32
                 }
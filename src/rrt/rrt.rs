@@ -1,17 +1,41 @@
 use crate::states::States;
 use crate::control::Control;
 
-use crate::planner_param::{Param,ParamObstacles};
-
-use zpatial::implement::bvh_median::Bvh;
-
+use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::event::PlannerEvent;
+use crate::collision::CollisionChecker;
+use crate::error::{PlannerError,Result};
 
 pub trait RRT < TS, TC, TObs > where TS: States, TC: Control, TObs: States {
     ///returns true if iteration induces change, false otherwise
     fn iterate( & mut self, iteration: Option<u32> ) -> bool;
-    fn get_best_trajectory_config_space( & self ) -> Vec<((TObs,TObs),u32)>;
+    ///config space projections of the nodes of the tree
+    fn get_trajectory_config_space( & self ) -> Vec<TObs>;
+    ///config space polylines of edges of the tree, with edge kind
+    fn get_trajectory_edges_config_space( & self ) -> Vec<(Vec<TObs>,u32)>;
+    ///config space polylines of edges of the best trajectory, with edge kind
+    fn get_best_trajectory_config_space( & self ) -> Vec<(Vec<TObs>,u32)>;
+    ///returns state space trajectory with controls and durations if goal is reached
+    fn get_best_solution( & self ) -> Option<Solution<TS,TC>>;
     fn reset( & mut self );
     fn print_stats( &self ){}
+    fn get_stats( &self ) -> Stats;
     fn get_sampling_distr( & self ) -> Vec<TObs>;
+    ///returns and clears events since last call
+    fn take_events( & mut self ) -> Vec<PlannerEvent>;
+    ///obstacles and collision resolution the tree is checked against
+    fn get_collision_checker( & self ) -> &CollisionChecker<TObs>;
+    ///pairs of (witness, witness representative), empty for planners without witnesses
+    fn get_witness_representatives_config_space( & self ) -> Vec<(TObs,TObs)> {
+        vec![]
+    }
+    ///accepted motion primitive candidates for display, empty for planners without motion primitives
+    fn get_last_motion_prim_candidates( & self ) -> Vec<(TObs,TObs)> {
+        vec![]
+    }
+    ///writes the tree to ``path``, see rrt::checkpoint
+    fn save_checkpoint( & self, _path: &str ) -> Result<()> {
+        Err( PlannerError::InvalidParam( "checkpoints not supported by planner".to_string() ) )
+    }
 }
-
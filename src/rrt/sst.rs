@@ -3,46 +3,38 @@
 
 extern crate pretty_env_logger;
 
-use std::collections::{HashSet,HashMap};
 use std::marker::PhantomData;
 use std::cmp::Ordering;
 
 use rand::Rng;
-use rand::prelude::*;
-use rand::distributions::Standard;
+use rand::rngs::StdRng;
 
 use crate::rrt::rrt::RRT;
 use crate::states::States;
 use crate::control::Control;
 use crate::planner_param::{Param,ParamObstacles};
+use crate::system_model::SystemModel;
+use crate::hash::{HashSet,HashMap};
 use crate::moprim::{MoPrim,Motion};
 
 use crate::instrumentation::*;
+use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::event::PlannerEvent;
+use crate::error::Result;
+use crate::collision::{self,CollisionChecker};
 
+use super::tree::{self,Tree,Node,Edge};
 use super::nn_naive::NN_Naive;
 use super::nn_stochastic::NN_Stochastic;
 
-use zpatial::implement::bvh_median::Bvh;
-use zpatial::interface::i_spatial_accel::ISpatialAccel;
-use zpatial::mazth::{
-    i_bound::IBound,
-    i_shape::ShapeType,
-    bound::AxisAlignedBBox,
-    bound_sphere::BoundSphere,
-    point::Point3,
-    line::Line3,
-};
-
-use zpatial::mazth::i_shape::IShape;
-use zpatial::mazth::{rbox::RecBox,triprism::TriPrism};
-
 use crate::planner_param::*;
 
 use rayon::prelude::*;
+use serde::{Serialize,Deserialize};
 
-use std::ops::{Add,Mul};
-
-#[derive(Debug)]
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States
 pub struct Gaussian<TS> where TS: States {
     pub mu: TS,
     pub vicinity_dist: f32,
@@ -59,16 +51,14 @@ impl <TS> Gaussian<TS> where TS: States {
         }
     }
 
-    pub fn update_params( & mut self, samples: & [TS],
-                            f_ss_dist: fn(TS,TS)->f32,
-                            ss_add: fn(TS,TS)->TS,
-                            ss_mul: fn(TS,f32)->TS ) {
+    pub fn update_params<TC,TObs>( & mut self, samples: & [TS],
+                                   model: &dyn SystemModel<TS,TC,TObs> ) where TC: Control, TObs: States {
 
         self.count_samples = 1; //dummy initialized count
         
         let items = samples.iter().filter_map(|i| {
             // if f_ss_dist( self.mu.clone(), i.clone() ) < self.vicinity_dist * 2. {
-            if f_ss_dist( self.mu.clone(), i.clone() ) < self.vicinity_dist * 2. {
+            if model.ss_metric( self.mu.clone(), i.clone() ) < self.vicinity_dist * 2. {
                 self.count_samples += 1;
                 Some( i.clone() )
             } else {
@@ -80,47 +70,22 @@ impl <TS> Gaussian<TS> where TS: States {
 
             let l = items.len();
             let sum = items.into_iter().fold( TS::default(),|acc,x|{
-                ss_add(acc, x)
+                model.ss_add(acc, x)
             });
 
-            let avg = ss_mul( sum, 1. / l as f32 );
-            self.mu = ss_add( ss_mul( self.mu.clone(), 0.9 ), ss_mul( avg, 0.1 ) );
+            let avg = model.ss_mul( sum, 1. / l as f32 );
+            self.mu = model.ss_add( model.ss_mul( self.mu.clone(), 0.9 ), model.ss_mul( avg, 0.1 ) );
         }
 
     }
 }
 
-#[derive(Debug)]
-pub struct Node<TS> {
-    
-    ///current node index
-    pub id: usize,
-
-    ///state space value
-    pub state: TS,
-
-    ///child node indices
-    pub children: HashSet<(usize)>,
-    
-    ///cost from the root of the propagation tree
-    pub cost: f32,
-}
-
-pub struct Edge <TC> {
-    
-    pub control: TC,
-
-    ///additional annotation for differentiating propagation type
-    pub kind: u32, //currently: 0: sst monte carlo propagation, 1: motion primitive propagation
-}
-
 pub struct SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     
     pub param: Param<TS,TC,TObs>,
     
-    pub obstacles: Bvh<usize>, //bvh contain indices to obstacles in obstacles_actual
-
-    pub obstacles_actual: ParamObstacles<TObs>,
+    ///obstacles and sub-state resolution of collision checking
+    pub collision: CollisionChecker<TObs>,
 
     pub witnesses: Vec<TS>,
 
@@ -138,7 +103,7 @@ pub struct SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub link_parent: HashMap< usize, usize >, //node -> node_parent
 
     ///storage for control input for the state space pair (parent node,child node)
-    pub edges: HashMap< (usize,usize), Edge<TC> >,
+    pub edges: HashMap< (usize,usize), Edge<TS,TC> >,
 
     pub delta_v: f32,
     pub delta_s: f32,
@@ -147,14 +112,15 @@ pub struct SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub monte_carlo_prop_l: f32,
     pub monte_carlo_prop_h: f32,
 
-    #[cfg(feature="nn_naive")]    
+    ///runtime planner behaviour switches
+    pub config: PlannerConfig,
+    
+    ///used if config.nn_naive is set
     pub nn_query_brute: NN_Naive<TS,TC,TObs>,
 
-    #[cfg(not(feature="nn_naive"))]
     ///stores nodes
     pub nn_query: NN_Stochastic<TS,TC,TObs>,
 
-    #[cfg(not(feature="nn_naive"))]
     ///stores only witnesses
     pub nn_query_witness: NN_Stochastic<TS,TC,TObs>,
 
@@ -163,18 +129,28 @@ pub struct SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
     pub stat_iter_collision: u32,
 
+    pub stat_iter_invalid: u32,
+
     pub iter_exec: u32,
 
     pub are_obstacles_boxes: bool,
 
-    ///motion primitive
-    #[cfg(feature="motion_primitives")]
-    pub mo_prim: MoPrim<TS,TC,TObs>,
-    #[cfg(feature="motion_primitives")]
+    ///motion primitive, available if config.motion_primitives is set
+    pub mo_prim: Option<MoPrim<TS,TC,TObs>>,
     pub stat_motion_prim_invoked: u32,
 
+    ///lowest cost node satisfying goal condition
     pub idx_reached: Option<usize>,
 
+    ///all nodes in the tree satisfying goal condition
+    pub nodes_goal: HashSet<usize>,
+
+    ///events since last call to take_events
+    pub events: Vec<PlannerEvent>,
+
+    ///set when the run is over, see StopCondition and PlannerConfig::stop_on_solution
+    pub stop_reason: Option<StopReason>,
+
     pub stat_time_all: f64,
     pub stat_time_mo_prim_query: f64,
     pub stat_time_witness_nn_query: f64,
@@ -191,6 +167,14 @@ pub struct SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub stat_witnesses_new: u32,
     
     pub stat_batch_prop_triggered: u32,
+
+    pub stat_goal_biased_samples: u32,
+
+    ///connections to goal made with the model's steering function
+    pub stat_steer_to_goal: u32,
+
+    ///closest distance to goal region of states added to the tree
+    pub stat_goal_distance: Option<f32>,
     
     pub witness_disturbance: bool,
 
@@ -207,36 +191,39 @@ pub struct SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     pub importance_sample_gamma: f32,
 
     pub optimization_iterations: u32,
+
+    ///source of all randomness of the planner, seeded from config
+    pub rng: StdRng,
 }
 
 impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     
-    pub fn init( param: & Param<TS,TC,TObs>, obstacles: Bvh<usize>, obstacles_concrete: ParamObstacles<TObs>, param_tree: ParamTree ) -> Self {
+    pub fn init( param: & Param<TS,TC,TObs>, obstacles_concrete: ParamObstacles<TObs>, param_tree: ParamTree, config: PlannerConfig ) -> Result<Self> {
         //todo process obstacles...
 
-        let box_obstacles = match obstacles_concrete.obstacles {
-            ObsVariant::RBOX(_) => true,
-            _ => false
-        };
+        tree::validate( "sst", param, &param_tree, &config, true )?;
+
+        let box_obstacles = matches!( obstacles_concrete.obstacles, ObsVariant::RBOX(_) );
+
+        let mut rng = tree::rng( &config );
         
         let mut s = Self {
             
             are_obstacles_boxes: box_obstacles,
             
             param: param.clone(),
-            obstacles: obstacles,
-            obstacles_actual: obstacles_concrete,
+            collision: CollisionChecker::init( obstacles_concrete, config.collision_step( param.sim_delta ) ),
             nodes: vec![ Node { id: 0,
                                 state: param.states_init.clone(),
-                                children: HashSet::new(),
+                                children: HashSet::default(),
                                 cost: 0. } ],
 
             nodes_freelist: vec![],
             
             witnesses: vec![],
-            witness_representative: HashMap::new(),
+            witness_representative: HashMap::default(),
             
-            edges: HashMap::new(),
+            edges: HashMap::default(),
             
             delta_v_orig: param_tree.delta_v,
             delta_s_orig: param_tree.delta_s,
@@ -246,38 +233,42 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
             monte_carlo_prop_h: param_tree.prop_delta_high,
             
             nodes_active: [0].to_vec().iter().cloned().collect(),
-            nodes_inactive: HashSet::new(),
-            link_parent: HashMap::new(),
+            nodes_inactive: HashSet::default(),
+            link_parent: HashMap::default(),
 
-            #[cfg(feature="nn_naive")]
             nn_query_brute: NN_Naive {
                 phantom_ts: PhantomData,
                 phantom_tc: PhantomData,
                 phantom_tobs: PhantomData,
             },
 
-            #[cfg(not(feature="nn_naive"))]
-            nn_query: NN_Stochastic::init( param.ss_metric ),
+            nn_query: NN_Stochastic::init( param.model.clone(), config.nn_sample_log, rng.gen() ),
             
-            #[cfg(not(feature="nn_naive"))]
-            nn_query_witness: NN_Stochastic::init( param.ss_metric ),
+            nn_query_witness: NN_Stochastic::init( param.model.clone(), config.nn_sample_log, rng.gen() ),
 
             stat_pruned_nodes: 0,
             stat_iter_no_change: 0,
             stat_iter_collision: 0,
+            stat_iter_invalid: 0,
 
             iter_exec: 0,
 
-            #[cfg(feature="motion_primitives")]
-            mo_prim: MoPrim::init( param.ss_metric,
-                                   param.motion_primitive_xform.expect("motion primitive transform"),
-                                   param.motion_primitive_xform_inv.expect("motion primitive transform inverse") ),
+            mo_prim: if config.motion_primitives {
+                Some( MoPrim::init( param.model.clone() ) )
+            } else {
+                None
+            },
             
-            #[cfg(feature="motion_primitives")]
             stat_motion_prim_invoked: 0,
 
             idx_reached: None,
 
+            nodes_goal: HashSet::default(),
+
+            events: vec![],
+
+            stop_reason: None,
+
             stat_time_all: 0.,
             stat_time_mo_prim_query: 0.,
             stat_time_witness_nn_query: 0.,
@@ -297,78 +288,53 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
             stat_batch_prop_triggered: 0,
 
+            stat_goal_biased_samples: 0,
+            stat_steer_to_goal: 0,
+
+            stat_goal_distance: None,
+
             sampling_mixture: vec![],
             saved_feasible_traj: vec![],
-            sampling_mixture_prob: HashMap::new(),
+            sampling_mixture_prob: HashMap::default(),
 
             importance_samples: vec![],
 
             importance_sample_gamma: std::f32::INFINITY,
 
             optimization_iterations: 0,
+
+            rng: rng,
+
+            config: config,
         };
 
-        #[cfg(not(feature="nn_naive"))]
-        {
+        if !s.config.nn_naive {
             s.create_new_witness( param.states_init.clone() );
             s.add_propagated_state_to_nn_query( param.states_init.clone(), 0 );
         }
         
         s.witness_representative.insert( 0, 0 );        
-        s
-    }
-
-
-    pub fn get_trajectory_config_space( & self ) -> Vec<TObs> {
-        self.nodes.iter()
-            .map(|x| (self.param.project_state_to_config)(x.state.clone()) )
-            .collect()
-    }
-
-    ///returns pairs of (witness, witness representative)
-
-    pub fn get_witness_representatives_config_space( & self ) -> Vec<(TObs,TObs)> {
-        self.witness_representative.iter()
-            .map( |(idx_witness,idx_repr)| {
-                let state_witness = self.witnesses[*idx_witness].clone();
-                let state_repr = self.nodes[*idx_repr].state.clone();
-                ( (self.param.project_state_to_config)(state_witness),
-                   (self.param.project_state_to_config)(state_repr) )
-            })
-            .collect()
+        Ok( s )
     }
 
 
-    pub fn get_trajectory_edges_config_space( & self ) -> Vec<((TObs,TObs),u32)> {
-        
-        self.edges.iter()
-            .filter(|x| {
-                (self.nodes_active.contains(&(x.0).0) ||
-                 self.nodes_inactive.contains(&(x.0).0)) &&
-                (self.nodes_active.contains(&(x.0).1) ||
-                 self.nodes_inactive.contains(&(x.0).1)) })
-            .map(|x| {
-                let id_a = (x.0).0;
-                let id_b = (x.0).1;
-                let state_a = &self.nodes[id_a].state;
-                let state_b = &self.nodes[id_b].state;
-                ( ( (self.param.project_state_to_config)(state_a.clone()),
-                    (self.param.project_state_to_config)(state_b.clone()) ), (x.1).kind )
-            })
-            .collect()
+    fn tree( & self ) -> Tree<'_,TS,TC> {
+        Tree { nodes: &self.nodes,
+               link_parent: &self.link_parent,
+               edges: &self.edges,
+               nodes_goal: &self.nodes_goal,
+               idx_reached: self.idx_reached }
     }
-    
 
     pub fn reached_goal( & self, states: TS ) -> bool {
-        let config_states = (self.param.project_state_to_config)(states.clone());
-        (self.param.stop_cond)( states, config_states, self.param.states_goal.clone() )
+        self.param.goal_region.contains( &*self.param.model, &states, &self.param.states_goal )
     }
 
-    pub fn get_last_motion_prim_candidates( & mut self ) -> Vec<(TObs,TObs)>{
-        self.last_moprim_candidates.clone()
+    ///distance of ``states`` to goal region, 0 if inside
+    pub fn distance_to_goal( & self, states: TS ) -> f32 {
+        self.param.goal_region.distance( &*self.param.model, &states, &self.param.states_goal )
     }
 
-
     fn prune_nodes( & mut self, node_inactive: usize ){
         
         //remove leaf nodes and branches from propagation tree if possible
@@ -376,13 +342,14 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         
         loop {
             if self.nodes[ node_prune ].children.is_empty() &&
-                !self.nodes_active.contains( & node_prune ) {
+                !self.nodes_active.contains( & node_prune ) &&
+                self.idx_reached != Some( node_prune ) {
                     
                     self.nodes_inactive.remove( & node_prune );
+                    self.nodes_goal.remove( & node_prune );
                     self.nodes_freelist.push( node_prune );
 
-                    #[cfg(not(feature="nn_naive"))]
-                    {
+                    if !self.config.nn_naive {
                         //remove node from nn_query
                         self.nn_query.remove( node_prune );
                     }
@@ -412,15 +379,17 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                       idx_node_nearest: usize,
                       state_propagate: TS,
                       control_propagate: TC,
+                      propagation_duration: f32,
                       propagation_cost: f32,
-                      is_using_motion_prim: bool ) -> usize {
+                      kind: u32,
+                      substates: &[TS] ) -> usize {
         
         //use freelist if possible
         let idx_node_new = match self.nodes_freelist.pop() {
             Some(slot) => {
                 self.nodes[slot] = Node { id: slot,
                                           state: state_propagate,
-                                          children: HashSet::new(),
+                                          children: HashSet::default(),
                                           cost: propagation_cost };
                 slot
             },
@@ -428,7 +397,7 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                 let idx_node_new = self.nodes.len();
                 self.nodes.push( Node { id: idx_node_new,
                                         state: state_propagate,
-                                        children: HashSet::new(),
+                                        children: HashSet::default(),
                                         cost: propagation_cost } );
                 idx_node_new
             },
@@ -440,8 +409,8 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         
         self.link_parent.insert( idx_node_new, idx_node_nearest );
         
-        self.edges.insert( (idx_node_nearest, idx_node_new), Edge { control: control_propagate,
-                                                                    kind: if is_using_motion_prim { 1 } else { 0 } } );
+        let e = Edge::init( &self.config, control_propagate, propagation_duration, kind, substates );
+        self.edges.insert( (idx_node_nearest, idx_node_new), e );
 
         idx_node_new
     }
@@ -452,49 +421,108 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         self.nodes_inactive.insert( idx_node );
     }
 
-    ///return true if there is a collision
+    ///duration resolution of propagation, see tree::resolution
+    fn resolution( & self ) -> Option<f32> {
+        tree::resolution( &self.param, &self.collision, &self.config )
+    }
 
-    fn collision_check( & mut self, config_space_state_before: &TObs, config_space_state_after: &TObs ) -> bool {
-        
-        let v0 = config_space_state_before.get_vals_3();
-        let v1 = config_space_state_after.get_vals_3();
+    ///propagates ``state`` at the propagation resolution, returns sub-states after ``state``, the last one being the propagated state
+    fn integrate( & self, state: TS, control: TC, delta: f32 ) -> Vec<TS> {
+        collision::integrate( &*self.param.model, state, control, delta, self.resolution() )
+    }
+
+    ///cost of node ``idx_parent`` extended by a propagation reaching ``state_end``, using cost function of Param
+    fn propagation_cost( & self, idx_parent: usize, control: &TC, duration: f32, state_end: &TS ) -> f32 {
+        let parent = &self.nodes[idx_parent];
+        parent.cost + self.param.cost.edge_cost( &*self.param.model, &parent.state, control, duration, state_end )
+    }
+
+    ///return true if all ``substates`` satisfy the model's state validity and constraints of Param
+    fn states_valid( & self, substates: &[TS] ) -> bool {
+        self.param.states_valid( substates )
+    }
+
+    ///return true if there is a collision on any segment from ``config_space_state_before`` through ``substates``
+    fn collision_check( & self, config_space_state_before: &TObs, substates: &[TS] ) -> bool {
+        self.collision.propagation_collision( &*self.param.model, config_space_state_before, substates )
+    }
+
+    ///connects node ``idx`` exactly to the goal state with the model's steering function if it is within steer_to_goal of config,
+    ///returns the goal node if the steered edges are valid, collision free and improve on the current solution
+    fn try_steer_to_goal( & mut self, idx: usize ) -> Option<usize> {
 
-        let query_line = Line3::init( &[v0[0] as _, v0[1] as _, v0[2] as _],
-                                      &[v1[0] as _, v1[1] as _, v1[2] as _] );
+        let thresh = self.config.steer_to_goal?;
+
+        let model = self.param.model.clone();
+
+        let state = self.nodes[idx].state.clone();
+        let config_space_goal = model.project_state_to_config( self.param.states_goal.clone() );
         
-        let candidate_collisions = self.obstacles.query_intersect( &query_line._bound ).unwrap();
+        if !( model.cs_metric( model.project_state_to_config( state.clone() ), config_space_goal ) < thresh ) {
+            return None
+        }
+
+        //steered edges are integrated like propagations, so replaying the solution's controls reproduces them
+        let edges = model.steer( &state, &self.param.states_goal, self.resolution() )?;
+
+        let mut s = state;
+        let mut cost = self.nodes[idx].cost;
+        let mut checked = vec![];
+
+        for (u,t,sub) in edges {
+            
+            let end = sub.last()?.clone();
+            
+            if !self.states_valid( &sub ) || self.collision_check( &model.project_state_to_config( s.clone() ), &sub ) {
+                return None
+            }
+            
+            cost += self.param.cost.edge_cost( &*model, &s, &u, t, &end );
+            checked.push( (u,t,sub,cost) );
+            s = end;
+        }
+
+        if checked.is_empty() ||
+            !self.reached_goal( s ) ||
+            self.idx_reached.map_or( false, |x| cost >= self.nodes[x].cost ) {
+            return None
+        }
+
+        let mut parent = idx;
         
-        let collision = if candidate_collisions.is_empty() {
-            false
-        }else{
-            match self.obstacles_actual.obstacles {
-                ObsVariant::TRIPRISM(ref x) => {
-                    //narrow stage collision test for tri prisms
-                    candidate_collisions.iter().any(|idx| x[*idx].get_intersect( &query_line ).0 )
-                },
-                _ => { true }, //box same as aabb box
+        for (u,t,sub,c) in checked {
+            let end = sub.last().unwrap().clone();
+            parent = self.insert_node( parent, end.clone(), u, t, c, 2, &sub );
+            //steered nodes have no witness, so they stay out of the active set and are pruned once a better solution replaces them
+            self.inactivate_node( parent );
+            if !self.config.nn_naive {
+                self.add_propagated_state_to_nn_query( end, parent );
             }
-        };
+        }
 
-        collision
+        self.stat_steer_to_goal += 1;
+        
+        Some( parent )
     }
 
     ///attempts to use a suitable motion primitive, returning time duration and control if successful
-    #[cfg(feature="motion_primitives")]
     fn try_motion_primitive_control( & mut self, state_space_nearest: TS, config_space_coord_before: TObs ) -> Option<(f32, TC)> {
 
         let mut timer = Timer::default();
+
+        let lookup_len = match self.mo_prim {
+            Some(ref m) => { m.lookup.len() },
+            _ => { return None },
+        };
         
-        if self.mo_prim.lookup.len() >= 500 {
+        if lookup_len >= 500 {
 
-            let cost_threshold = if cfg!(feature="mo_prim_thresh_low"){ 0.1 }
-                                 else if cfg!(feature="mo_prim_thresh_high"){ 0.4 }
-                                 else { 0.25 };
+            let cost_threshold = self.config.mo_prim_thresh;
 
 
-            let config_space_goal = (self.param.project_state_to_config)(self.param.states_goal.clone());
+            let config_space_goal = self.param.model.project_state_to_config(self.param.states_goal.clone());
                                                  
-            let d = (self.param.cs_metric)( config_space_coord_before.clone(), config_space_goal.clone() );
+            let d = self.param.model.cs_metric( config_space_coord_before.clone(), config_space_goal.clone() );
                                             
             
             if d < cost_threshold {
@@ -503,9 +531,9 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                 
                 let q_query_mo_prim = self.param.states_goal.clone();
                 
-                let motions : Vec<Motion<_,_>> = self.mo_prim.query_motion( state_space_nearest.clone(),
-                                                                            q_query_mo_prim,
-                                                                            cost_threshold );                        
+                let motions : Vec<Motion<_,_>> = self.mo_prim.as_mut().unwrap().query_motion( state_space_nearest.clone(),
+                                                                                             q_query_mo_prim,
+                                                                                             cost_threshold );                        
                 
                 let start_point = &config_space_coord_before;
 
@@ -514,21 +542,20 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                     let control = m.u.clone();
                     let time_dur = m.t.clone();
                     
-                    let propagate_motion = (self.param.dynamics)( state_space_nearest.clone(),
-                                                                  control,
-                                                                  time_dur );
+                    let substates = self.integrate( state_space_nearest.clone(),
+                                                    control,
+                                                    time_dur );
                     
-                    let end_point = (self.param.project_state_to_config)(propagate_motion.clone());
+                    let end_point = self.param.model.project_state_to_config( substates.last().unwrap().clone() );
 
-                    let collision = self.collision_check( &start_point, &end_point );
+                    let collision = !self.states_valid( &substates ) || self.collision_check( start_point, &substates );
                         
-                    let d_diff = (self.param.cs_metric)( end_point.clone(), config_space_goal.clone() );
+                    let d_diff = self.param.model.cs_metric( end_point.clone(), config_space_goal.clone() );
                     
                     if collision || d_diff > d {
                         None
                     } else {
-                        #[cfg(feature="mo_prim_debug")]
-                        {
+                        if self.config.mo_prim_debug {
                             self.last_moprim_candidates.push( (start_point.clone(),
                                                                end_point.clone()) ); //debugging purpose
                         }
@@ -540,11 +567,8 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
                 let motion = sel_motion.min_by(|a,b| a.0.partial_cmp( &b.0 ).unwrap_or(Ordering::Equal) );
                 
-                match motion {
-                    Some((d,Motion{u,t,..})) => {
-                        return Some(( *t, u.clone() ))
-                    },
-                    _ => {},
+                if let Some((_,Motion{u,t,..})) = motion {
+                    return Some(( *t, u.clone() ))
                 }
             }   
         }
@@ -557,77 +581,56 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     }
 
     ///propagation with random time delta and control
-
     fn generate_monte_carlo_propagation( & mut self ) -> (f32, TC) {
-
-        //enforce bounds
-        let mut val: f32 = SmallRng::from_entropy().sample(Standard);
-        
-        val = if val < self.monte_carlo_prop_l { self.monte_carlo_prop_l } else { val };
-        val = if val > self.monte_carlo_prop_h { self.monte_carlo_prop_h } else { val };
-        
-        let monte_carlo_prop_delta = val * self.param.sim_delta;
-        
-        //sampler for control space
-        let control_sample = (self.param.param_sampler)( monte_carlo_prop_delta );
-
-        ( monte_carlo_prop_delta, control_sample )
+        tree::monte_carlo_propagation( &self.param, self.monte_carlo_prop_l, self.monte_carlo_prop_h, &mut self.rng )
     }
 
-    #[cfg(not(feature="nn_naive"))]
     fn create_new_witness( & mut self, state: TS ) -> usize {
 
         let idx_new = self.witnesses.len();
         
         self.witnesses.push( state.clone() );
         
-        self.nn_query_witness.add( state, idx_new, self.param.ss_metric );
+        self.nn_query_witness.add( state, idx_new, &*self.param.model );
         
         idx_new
     }
 
-    #[cfg(not(feature="nn_naive"))]
     fn add_propagated_state_to_nn_query( & mut self, state: TS, id: usize ) {
 
-        self.nn_query.add( state, id, self.param.ss_metric );
+        self.nn_query.add( state, id, &*self.param.model );
     }
 
     ///return id of the nearest existing propagation node in state space and return a possibly modified state space sample
-
     fn get_best_vicinity( & mut self, ss_sample: TS ) -> ( usize, TS ) {
         
-        #[cfg(feature="nn_naive")]
-        {
+        if self.config.nn_naive {
             let idx_ret = self.nn_query_brute.query_nearest_state_active( ss_sample.clone(),
                                                                           & self.nodes,
                                                                           & self.nodes_active,
                                                                           & self.param,
                                                                           self.delta_v );
             ( idx_ret, ss_sample )
-        }
-        #[cfg(not(feature="nn_naive"))]
-        {
-            let mut rng = rand::thread_rng();
-            let prob_use_state_prop_sample = rng.gen_range(0., 1.);            
-            if cfg!(feature="state_propagate_sample") && prob_use_state_prop_sample > 0.5
-            // if cfg!(feature="state_propagate_sample")
+        } else {
+            let prob_use_state_prop_sample = self.rng.gen_range(0., 1.);            
+            if self.config.state_propagate_sample && prob_use_state_prop_sample > 0.5
             {
                 
-                let ss_samples = (0..10).map( |_| (self.param.ss_sampler)() ).collect::<Vec<_>>();
+                let ss_samples = (0..10).map( |_| self.param.model.sample_state( &mut self.rng ) ).collect::<Vec<_>>();
                 
                 let sample_nearest_pairs = ss_samples.into_iter()
                     .map(|sample|{
                         let r0 = self.nn_query.query_nearest_threshold( sample.clone(),
-                                                                        self.param.ss_metric,
+                                                                        &*self.param.model,
                                                                         self.delta_v );
                         let idx_nearest = if r0.is_empty(){
                             let r1 = self.nn_query.query_nearest_k( sample.clone(),
-                                                                    self.param.ss_metric,
+                                                                    &*self.param.model,
                                                                     1 );
-                            let (_,idx_ret) = *r1.iter().nth(0).unwrap();
+                            let (_,idx_ret) = *r1.first().unwrap();
                             idx_ret
                         }else{
-                            let (_,idx_ret) = *r0.iter().nth(0).unwrap();
+                            let (_,idx_ret) = *r0.first().unwrap();
                             idx_ret
                         };
                         
@@ -639,12 +642,12 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                         
                         let dist_a = self.nn_query.query_dist_node_neighbourhood_avg( sample_a.clone(),
                                                                                       *idx_nearest_a,
-                                                                                      self.param.ss_metric,
+                                                                                      &*self.param.model,
                                                                                       1 );
 
                         let dist_b = self.nn_query.query_dist_node_neighbourhood_avg( sample_b.clone(),
                                                                                       *idx_nearest_b,
-                                                                                      self.param.ss_metric,
+                                                                                      &*self.param.model,
                                                                                       1 );
                         dist_a.partial_cmp( & dist_b ).unwrap_or( Ordering::Equal )
                     }).unwrap();
@@ -654,15 +657,15 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
             } else {
                 let mut ret = self.nn_query.query_nearest_threshold( ss_sample.clone(),
-                                                                     self.param.ss_metric,
+                                                                     &*self.param.model,
                                                                      self.delta_v );
                 if ret.is_empty(){
                     ret = self.nn_query.query_nearest_k( ss_sample.clone(),
-                                                         self.param.ss_metric,
+                                                         &*self.param.model,
                                                          1 );
                 }
                 
-                let (_,idx_ret) = *ret.iter().nth(0).expect("nn query failed to return a node");
+                let (_,idx_ret) = *ret.first().expect("nn query failed to return a node");
                 
                 ( idx_ret, ss_sample )
             }
@@ -670,12 +673,9 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     }
 
     ///returns ( propagation delta, control, is_using_motion_primitive )
-
     fn select_propagation_params( & mut self, state_space_start: TS, state_config_start: TObs ) -> ( f32, TC, bool ) {
-        #[cfg(feature="motion_primitives")]
-        {
-            let mut rng = rand::thread_rng();
-            let rand_prob = rng.gen_range(0., 1.);
+        if self.mo_prim.is_some() {
+            let rand_prob = self.rng.gen_range(0., 1.);
             if rand_prob > 0.5 {
                 match self.try_motion_primitive_control( state_space_start, state_config_start ) {
                     Some((t, u)) => {
@@ -691,19 +691,15 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                 let ( t, u ) = self.generate_monte_carlo_propagation();
                 ( t, u, false )
             }
-        }
-        #[cfg(not(feature="motion_primitives"))]
-        {
+        } else {
             let ( t, u ) = self.generate_monte_carlo_propagation();
             ( t, u, false )
         }
     }
 
     ///returns ( idx of witness, is new witness ) associated with the propagated node
-
     fn get_witness_neighbourhood( & mut self, state: TS ) -> ( usize, bool ) {
-        #[cfg(feature="nn_naive")]
-        {
+        if self.config.nn_naive {
             match self.nn_query_brute.query_nearest_witness( state.clone(),
                                                              & self.witnesses, 
                                                              & self.param,
@@ -718,13 +714,11 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                     ( idx_new, true )
                 },
             }
-        }
-        #[cfg(not(feature="nn_naive"))]
-        {
+        } else {
             let ret = self.nn_query_witness.query_nearest_threshold( state.clone(),
-                                                                     self.param.ss_metric,
+                                                                     &*self.param.model,
                                                                      self.delta_s );
-            match ret.iter().nth(0) {
+            match ret.first() {
                 Some((_,idx_global)) => {
                     //found witness
                     ( *idx_global,false )
@@ -739,25 +733,18 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     }
 
     ///disturbance injection for witness representative replacement
-
     fn witness_representative_disturbance_inject( & mut self ) {
         
-        let iter_propagated = self.iter_exec - self.stat_iter_no_change;
-
         //sliding window
         if self.iter_exec % 200 == 0 {
 
-            self.stat_witnesses_discovery_rate = self.stat_witnesses_new as f32 / 200 as f32;
+            self.stat_witnesses_discovery_rate = self.stat_witnesses_new as f32 / 200.;
             self.stat_witnesses_new = 0;
             
             if self.iter_exec > 1000 {
 
                 //trigger disturbance injection if witness discovery rate is low
-                if self.stat_witnesses_discovery_rate <= 0.1 {
-                    self.witness_disturbance = true;
-                } else {
-                    self.witness_disturbance = false;
-                }
+                self.witness_disturbance = self.stat_witnesses_discovery_rate <= 0.1;
             }
         }
     }
@@ -765,36 +752,48 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
 
     fn propagate( & mut self, state_start: TS, idx_state_best_nearest: usize ) -> ( f32, TC, bool ) {
         
-        let config_space_coord_before = (self.param.project_state_to_config)( state_start.clone() );
+        let config_space_coord_before = self.param.model.project_state_to_config( state_start.clone() );
 
-        let mut rng = rand::thread_rng();
-        let rand_prob = rng.gen_range(0., 1.);
+        let rand_prob = self.rng.gen_range(0., 1.);
         
-        if cfg!(feature="batch_propagate_sample") && rand_prob > 0.5
+        if self.config.batch_propagate_sample && rand_prob > 0.5
         {
             
-            let batch_prop = (0..10).filter_map(|_|{
-                
-                let( monte_carlo_prop_delta,
-                     param_sample,
-                     is_using_motion_prim ) = self.select_propagation_params( state_start.clone(),
-                                                                              config_space_coord_before.clone() );
-                
-                let state_propagate_cost = self.nodes[idx_state_best_nearest].cost + monte_carlo_prop_delta;
+            //draw candidates sequentially so that random number consumption does not depend on thread scheduling
+            let candidates = (0..10).map(|_| self.select_propagation_params( state_start.clone(),
+                                                                             config_space_coord_before.clone() ) )
+                .collect::<Vec<_>>();
 
-                let state_propagate = (self.param.dynamics)( state_start.clone(),
-                                                             param_sample.clone(),
-                                                             monte_carlo_prop_delta );
-                
-                let config_space_coord_after = (self.param.project_state_to_config)(state_propagate.clone());
-                
-                if self.collision_check( &config_space_coord_before, &config_space_coord_after ) {
-                    None
-                } else {
-                    Some( ( monte_carlo_prop_delta, param_sample, is_using_motion_prim, state_propagate_cost ) )
-                }   
-            // }).max_by(|a,b| a.3.partial_cmp( & b.3 ).unwrap_or( Ordering::Equal ) );
-            }).max_by(|a,b| a.0.partial_cmp( & b.0 ).unwrap_or( Ordering::Equal ) );
+            let resolution = self.resolution();
+            let cost_start = self.nodes[idx_state_best_nearest].cost;
+            let param = &self.param;
+            let collision = &self.collision;
+
+            //propagate and collision check candidates in parallel, results are kept in candidate order
+            let evaluated = candidates.into_par_iter()
+                .map(|( monte_carlo_prop_delta, param_sample, is_using_motion_prim )| {
+                    
+                    let substates = collision::integrate( &*param.model,
+                                                          state_start.clone(),
+                                                          param_sample.clone(),
+                                                          monte_carlo_prop_delta,
+                                                          resolution );
+                    
+                    if !param.states_valid( &substates ) ||
+                        collision.propagation_collision( &*param.model, &config_space_coord_before, &substates ) {
+                        None
+                    } else {
+                        let state_propagate_cost = cost_start +
+                            param.cost.edge_cost( &*param.model, &state_start, &param_sample, monte_carlo_prop_delta, substates.last().unwrap() );
+                        Some( ( monte_carlo_prop_delta, param_sample, is_using_motion_prim, state_propagate_cost ) )
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let batch_prop = evaluated.into_iter()
+                .flatten()
+            // .max_by(|a,b| a.3.partial_cmp( & b.3 ).unwrap_or( Ordering::Equal ) );
+                .max_by(|a,b| a.0.partial_cmp( & b.0 ).unwrap_or( Ordering::Equal ) );
 
             match batch_prop {
                 Some( item ) => {
@@ -817,6 +816,18 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         }
     }
 
+    ///sets ``idx_goal`` as the best goal node, previous best is released for pruning if it has been inactivated
+    fn replace_best_goal( & mut self, idx_goal: usize ) {
+        
+        let idx_prev = self.idx_reached.replace( idx_goal );
+
+        if let Some(x) = idx_prev {
+            if !self.config.disable_pruning && self.nodes_inactive.contains( &x ) {
+                self.prune_nodes( x );
+            }
+        }
+    }
+
     fn save_feasible_trajectory_state_space( & mut self ) {
         
         let mut nodes = vec![];
@@ -825,28 +836,25 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         
         let lim = 1000000;
         let mut count = 0;
-        match self.idx_reached {
-            Some(x) => {
-                let mut idx = x;
-                fitness_score += self.nodes[idx].cost;
-                loop {
-                    count += 1;
-                    if count >= lim {
-                        panic!("looping");
-                    }
-
-                    nodes.push( self.nodes[idx].state.clone() );
-                    // fitness_score += self.nodes[idx].cost;
-                        
-                    idx = match self.link_parent.get( &idx ) {
-                        Some(parent) => {
-                            *parent
-                        },
-                        _ => { break; },
-                    };
+        if let Some(x) = self.idx_reached {
+            let mut idx = x;
+            fitness_score += self.nodes[idx].cost;
+            loop {
+                count += 1;
+                if count >= lim {
+                    panic!("looping");
                 }
-            },
-            _ => {},
+
+                nodes.push( self.nodes[idx].state.clone() );
+                // fitness_score += self.nodes[idx].cost;
+                    
+                idx = match self.link_parent.get( &idx ) {
+                    Some(parent) => {
+                        *parent
+                    },
+                    _ => { break; },
+                };
+            }
         }
 
         nodes.reverse();
@@ -865,9 +873,7 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
             self.sampling_mixture = self.saved_feasible_traj.iter().map(|x|{
                 let mut g = Gaussian::init( x.clone(), self.delta_s_orig );
                 g.update_params( self.saved_feasible_traj.as_slice(),
-                                 self.param.ss_metric,
-                                 self.param.ss_add,
-                                 self.param.ss_mul );
+                                 &*self.param.model );
                 g
             }).collect();
                 
@@ -887,7 +893,7 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
             let mut filtered = self.importance_samples.iter()
                 .enumerate()
                 .map(|(idx,x)| (idx,x.0) )
-                .filter(|(idx,x)| *x < self.importance_sample_gamma )
+                .filter(|(_idx,x)| *x < self.importance_sample_gamma )
                 .collect::<Vec<_>>();
 
             //get a quantile of the worst filtered samples
@@ -916,29 +922,24 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
                     //update parameter of the sampling distribution using elite set
                     
                     let sample_idxs = filtered.iter().skip(idx_sel+1)
-                        .map(|(idx,x)| *idx)
+                        .map(|(idx,_x)| *idx)
                         .collect::<Vec<_>>();
 
-                    if sample_idxs.len() > 0 {
+                    if !sample_idxs.is_empty() {
                         let elite_sample_regions : Vec<TS> = sample_idxs.iter()
-                            .map(|idx| self.importance_samples[*idx].1.clone() )
-                            .flatten()
+                            .flat_map(|idx| self.importance_samples[*idx].1.clone() )
                             .collect();
                         
                         self.sampling_mixture = elite_sample_regions.iter().map(|x|{
-                            let mut g = Gaussian::init( x.clone(), self.delta_s_orig );
+                            let g = Gaussian::init( x.clone(), self.delta_s_orig );
                             // g.update_params( self.saved_feasible_traj.as_slice(),
-                            //                  self.param.ss_metric,
-                            //                  self.param.ss_add,
-                            //                  self.param.ss_mul );
+                            //                  &*self.param.model );
                             g
                         }).collect();
 
                         for i in self.sampling_mixture.iter_mut(){
                             i.update_params( elite_sample_regions.as_slice(),
-                                             self.param.ss_metric,
-                                             self.param.ss_add,
-                                             self.param.ss_mul );
+                                             &*self.param.model );
                         }
                         
                         self.generate_sampling_mixture_prob();
@@ -971,13 +972,13 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         //     // .inspect(|x|{ info!("mixture prob: {}",x.1 ); })
         //     .collect();
 
-        let count_total = self.sampling_mixture.iter().fold(0,|acc,x|{
+        let count_total = self.sampling_mixture.iter().fold(0,|acc,_x|{
             acc + 1
         });
 
         self.sampling_mixture_prob = self.sampling_mixture.iter()
             .enumerate()
-            .map(|(idx,x)|{
+            .map(|(idx,_x)|{
                 ( idx, 1. / count_total as f32 )
             })
             .collect();
@@ -986,14 +987,13 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
     }
 
     fn sample_ss_from_mixture_model( & mut self ) -> TS {
-        let mut rng = rand::thread_rng();
-        let rand_prob = rng.gen_range(0., 1.);
+        let rand_prob = self.rng.gen_range(0., 1.);
         let mut cumulative = 0.;
         
         let max_len = self.sampling_mixture_prob.len();
         assert!( max_len > 0 );
         
-        let found_idx = match self.sampling_mixture_prob.iter().find(|(idx,ref x)|{
+        let found_idx = match self.sampling_mixture_prob.iter().find(|(_idx,x)|{
             // info!("rand_prob: {}, cumulative: {}", rand_prob, cumulative );
             if rand_prob < cumulative {
                 true
@@ -1018,7 +1018,7 @@ impl <TS,TC,TObs> SST<TS,TC,TObs> where TS: States, TC: Control, TObs: States {
         
         let vals = (0..mu.len()).map(|x|{
             let n = Normal::new( mu[x] as f64, d as f64 );
-            n.sample(&mut rand::thread_rng()) as f32
+            n.sample(&mut self.rng) as f32
         }).collect::<Vec<_>>();
         
         sample.set_vals( vals.as_slice() );
@@ -1031,7 +1031,7 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
 
     fn get_sampling_distr( & self ) -> Vec<TObs> {
         self.sampling_mixture.iter()
-            .map(|x| (self.param.project_state_to_config)(x.mu.clone()) )
+            .map(|x| self.param.model.project_state_to_config(x.mu.clone()) )
             .collect()
     }
         
@@ -1039,13 +1039,13 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         
         self.nodes = vec![ Node { id: 0,
                                   state: self.param.states_init.clone(),
-                                  children: HashSet::new(),
+                                  children: HashSet::default(),
                                   cost: 0. } ];
 
-        self.edges = HashMap::new();
+        self.edges = HashMap::default();
         self.witness_representative.clear();
         self.witnesses.clear();
-        self.nodes_active = HashSet::new();
+        self.nodes_active = HashSet::default();
         self.nodes_active.insert( 0 );
         self.nodes_inactive.clear();
         self.link_parent.clear();
@@ -1053,8 +1053,12 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         self.stat_pruned_nodes = 0;
         self.stat_iter_no_change = 0;
         self.stat_iter_collision = 0;
+        self.stat_iter_invalid = 0;
         self.iter_exec = 0;
         self.idx_reached = None;
+        self.nodes_goal.clear();
+        self.events.clear();
+        self.stop_reason = None;
         self.stat_time_mo_prim_query = 0.;
         self.stat_time_witness_nn_query = 0.;
         self.stat_time_vicinity_best_nn_query = 0.;
@@ -1063,21 +1067,15 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         self.stat_count_nn_witness_queries = 0;
         self.stat_count_nn_node_queries = 0;
         self.stat_batch_prop_triggered = 0;
+        self.stat_goal_biased_samples = 0;
+        self.stat_steer_to_goal = 0;
+        self.stat_goal_distance = None;
 
         self.last_moprim_candidates = vec![];
 
-        #[cfg(not(feature="nn_naive"))]
-        {
-            self.nn_query = NN_Stochastic::init( self.param.ss_metric );
-        }
-        
-        #[cfg(not(feature="nn_naive"))]
-        {
-            self.nn_query_witness = NN_Stochastic::init( self.param.ss_metric );
-        }
-        
-        #[cfg(not(feature="nn_naive"))]
-        {
+        if !self.config.nn_naive {
+            self.nn_query = NN_Stochastic::init( self.param.model.clone(), self.config.nn_sample_log, self.rng.gen() );
+            self.nn_query_witness = NN_Stochastic::init( self.param.model.clone(), self.config.nn_sample_log, self.rng.gen() );
             self.create_new_witness( self.param.states_init.clone() );
             self.add_propagated_state_to_nn_query( self.param.states_init.clone(), 0 );
         }
@@ -1086,24 +1084,20 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
     
     fn iterate( & mut self, iteration: Option<u32> ) -> bool {
 
-        let mut rng = rand::thread_rng();
-        
-        if self.idx_reached.is_some() || self.iter_exec >= self.param.iterations_bound {
-            return false
-        }
-
-        let mut timer_all = Timer::default();
-
-        let iter_batch = match iteration {
+        let iter_batch = match tree::batch( &self.param.stop_condition, &mut self.stop_reason, self.iter_exec, self.stat_time_all, iteration ) {
             Some(x) => { x },
-            _ => { self.param.iterations_bound },
+            _ => { return false },
         };
 
-        let config_space_goal = (self.param.project_state_to_config)(self.param.states_goal.clone());
+        let mut timer_all = Timer::default();
+
+        let config_space_goal = self.param.model.project_state_to_config(self.param.states_goal.clone());
         
-        'l_outer: for i in 0..iter_batch {
+        for _ in 0..iter_batch {
 
-            use std::f32::consts::PI;
+            if tree::stopped( &self.param.stop_condition, &mut self.stop_reason, self.iter_exec, self.stat_time_all + timer_all.dur_ms() ) {
+                break;
+            }
                 
             // self.delta_v = self.delta_v_orig * (1. + ( self.iter_exec as f32 / 4000. * 2. * PI ).cos() * 0.75 );
             // self.delta_s = self.delta_s_orig * (1. + ( self.iter_exec as f32 / 4000. * 2. * PI ).cos() * 0.75 );
@@ -1113,10 +1107,19 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
             
             self.iter_exec += 1;
 
-            let ( idx_state_best_nearest, ss_sample ) = {
+            let ( idx_state_best_nearest, _ss_sample ) = {
 
-                let ss_sample_seed = if self.sampling_mixture_prob.is_empty(){
-                    (self.param.ss_sampler)()
+                let goal_biased = self.config.goal_bias > 0. && self.rng.gen_range(0., 1.) < self.config.goal_bias;
+                
+                let ss_sample_seed = if goal_biased {
+                    self.stat_goal_biased_samples += 1;
+                    self.param.goal_region.sample( &*self.param.model,
+                                                  self.param.states_goal.clone(),
+                                                  self.config.goal_bias_radius,
+                                                  self.config.goal_bias_ignore_heading,
+                                                  &mut self.rng )
+                } else if self.sampling_mixture_prob.is_empty(){
+                    self.param.model.sample_state( &mut self.rng )
                 } else {
                     self.sample_ss_from_mixture_model()
                 };
@@ -1135,28 +1138,33 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
             };
             
             let state_start = self.nodes[idx_state_best_nearest].state.clone();
-            let config_space_coord_before = (self.param.project_state_to_config)( state_start.clone() );
+            let config_space_coord_before = self.param.model.project_state_to_config( state_start.clone() );
 
             let( monte_carlo_prop_delta, param_sample, is_using_motion_prim ) = self.propagate( state_start.clone(), idx_state_best_nearest );
 
-            let state_propagate_cost = self.nodes[idx_state_best_nearest].cost + monte_carlo_prop_delta;
+            let substates = self.integrate( state_start.clone(),
+                                            param_sample.clone(),
+                                            monte_carlo_prop_delta );
 
-            let state_propagate = (self.param.dynamics)( state_start.clone(),
-                                                         param_sample.clone(),
-                                                         monte_carlo_prop_delta );
+            let state_propagate = substates.last().unwrap().clone();
 
-            let config_space_coord_after = (self.param.project_state_to_config)(state_propagate.clone());
+            let state_propagate_cost = self.propagation_cost( idx_state_best_nearest,
+                                                              &param_sample,
+                                                              monte_carlo_prop_delta,
+                                                              &state_propagate );
 
-            #[cfg(feature="motion_primitives")]
-            {
-                let rand_prob = rng.gen_range(0., 1.);
-                if rand_prob > 0.85 || self.mo_prim.lookup.len() < self.mo_prim.capacity {
+            let config_space_coord_after = self.param.model.project_state_to_config(state_propagate.clone());
+
+            if let Some(ref mut mo_prim) = self.mo_prim {
+                let rand_prob = self.rng.gen_range(0., 1.);
+                if rand_prob > 0.85 || mo_prim.lookup.len() < mo_prim.capacity {
                     //no matter what obstructions are out there, we can still record the motion
-                    self.mo_prim.add_motion( state_start,
-                                             state_propagate.clone(),
-                                             param_sample.clone(),
-                                             monte_carlo_prop_delta,
-                                             monte_carlo_prop_delta );
+                    mo_prim.add_motion( state_start,
+                                        state_propagate.clone(),
+                                        param_sample.clone(),
+                                        monte_carlo_prop_delta,
+                                        monte_carlo_prop_delta,
+                                        &mut self.rng );
 
                 }
             }
@@ -1171,8 +1179,7 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
             self.stat_time_witness_nn_query += t_delta;
             self.stat_count_nn_witness_queries += 1;
             
-            #[cfg(not(feature="disable_witness_disturbance"))]
-            {
+            if !self.config.disable_witness_disturbance {
                 if is_new_witness {
                     self.stat_witnesses_new += 1;
                 }
@@ -1181,38 +1188,38 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
 
             let reached = self.reached_goal( state_propagate.clone() );
             
-            let witness_repr = match self.witness_representative.get( &witness_idx ) {
-                Some(x) => { Some(*x) },
-                _ => None,
-            };
+            let witness_repr = self.witness_representative.get( &witness_idx ).cloned();
             
             let mut timer2 = Timer::default();
             
             let idx_node = match witness_repr {
                 Some( repr ) => {
                     
-                    let witness_distrubance_prob = rng.gen_range(0., 1.);
+                    let witness_distrubance_prob = self.rng.gen_range(0., 1.);
 
+                    let reached_better = reached && self.idx_reached.map_or( true, |x| state_propagate_cost < self.nodes[x].cost );
+                    
                     if state_propagate_cost < self.nodes[ repr ].cost ||
-                        reached  ||
+                        reached_better  ||
                         ( self.witness_disturbance && witness_distrubance_prob > 0.5 ) {
 
-                        if self.collision_check( &config_space_coord_before, &config_space_coord_after ) {
+                        if tree::rejected( &self.param, &self.collision, &config_space_coord_before, &substates,
+                                           &mut self.stat_iter_invalid, &mut self.stat_iter_collision ) {
                             self.stat_iter_no_change += 1;
-                            self.stat_iter_collision += 1;
                             None
                         } else {
 
                             let idx_inserted = self.insert_node( idx_state_best_nearest.clone(),
                                                                  state_propagate.clone(),
                                                                  param_sample.clone(),
+                                                                 monte_carlo_prop_delta,
                                                                  state_propagate_cost.clone(),
-                                                                 is_using_motion_prim );
+                                                                 is_using_motion_prim as u32,
+                                                                 &substates );
 
                             let node_inactive = repr;
                             
-                            #[cfg(not(feature="disable_pruning"))]
-                            {
+                            if !self.config.disable_pruning {
                                 self.inactivate_node( node_inactive.clone() );
                                 
                                 self.prune_nodes( node_inactive ); //remove states from nn query as well
@@ -1221,17 +1228,13 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
                             //save new representative state idx for current witness
                             *self.witness_representative.get_mut( &witness_idx ).unwrap() = idx_inserted;
 
-                            #[cfg(not(feature="nn_naive"))]
-                            {
+                            if !self.config.nn_naive {
                                 //add propagated state to nn_query
                                 self.add_propagated_state_to_nn_query( state_propagate.clone(), idx_inserted );
                             }
                             
-                            #[cfg(feature="motion_primitives")]
-                            {
-                                if is_using_motion_prim {
-                                    self.stat_motion_prim_invoked += 1;
-                                }
+                            if is_using_motion_prim {
+                                self.stat_motion_prim_invoked += 1;
                             }
                             Some(idx_inserted)
                         }
@@ -1242,9 +1245,9 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
                 },
                 _ => {
                     
-                    if self.collision_check( &config_space_coord_before, &config_space_coord_after ) {
+                    if tree::rejected( &self.param, &self.collision, &config_space_coord_before, &substates,
+                                       &mut self.stat_iter_invalid, &mut self.stat_iter_collision ) {
                         self.stat_iter_no_change += 1;
-                        self.stat_iter_collision += 1;
                         None
                     } else {
 
@@ -1253,22 +1256,20 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
                         let idx_inserted = self.insert_node( idx_state_best_nearest.clone(),
                                                              state_propagate.clone(),
                                                              param_sample.clone(),
+                                                             monte_carlo_prop_delta,
                                                              state_propagate_cost.clone(),
-                                                             is_using_motion_prim );
+                                                             is_using_motion_prim as u32,
+                                                             &substates );
 
                         self.witness_representative.insert( witness_idx, idx_inserted );
 
-                        #[cfg(not(feature="nn_naive"))]
-                        {
+                        if !self.config.nn_naive {
                             //add propagated state to nn_query
                             self.add_propagated_state_to_nn_query( state_propagate.clone(), idx_inserted );
                         }
 
-                        #[cfg(feature="motion_primitives")]
-                        {
-                            if is_using_motion_prim {
-                                self.stat_motion_prim_invoked += 1;
-                            }
+                        if is_using_motion_prim {
+                            self.stat_motion_prim_invoked += 1;
                         }
                         
                         //no node is made inactive, hence no pruning necessary
@@ -1279,16 +1280,55 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
 
             let t_delta2 = timer2.dur_ms();
             self.stat_time_main_prop_check += t_delta2;
+
+            //exact connection from the new node to goal
+            let ( idx_node, reached, config_space_coord_after ) = match ( idx_node, reached ) {
+                ( Some(x), false ) => {
+                    match self.try_steer_to_goal( x ) {
+                        Some(idx_goal) => { ( Some(idx_goal), true, config_space_goal.clone() ) },
+                        _ => { ( idx_node, reached, config_space_coord_after ) },
+                    }
+                },
+                _ => { ( idx_node, reached, config_space_coord_after ) },
+            };
+
+            if idx_node.is_some() {
+                let d = if reached { 0. } else { self.distance_to_goal( state_propagate.clone() ) };
+                if self.stat_goal_distance.map_or( true, |x| d < x ) {
+                    self.stat_goal_distance = Some(d);
+                }
+            }
             
-            match (idx_node, reached ) {
-                (Some(x),true) => {
-                    let d_goal = (self.param.cs_metric)( config_space_coord_after.clone(), config_space_goal );
-                    info!("found a path to goal on iteration: {}, diff: {}", self.iter_exec, d_goal );
-                    self.idx_reached = Some(x);
-                    self.save_feasible_trajectory_state_space();
+            if let (Some(x),true) = (idx_node, reached ) {
+                
+                self.nodes_goal.insert( x );
+                
+                let cost_previous = self.idx_reached.map(|y| self.nodes[y].cost );
+
+                match cost_previous {
+                    None => {
+                        let d_goal = self.param.model.cs_metric( config_space_coord_after.clone(), config_space_goal.clone() );
+                        info!("found a path to goal on iteration: {}, diff: {}", self.iter_exec, d_goal );
+                        self.idx_reached = Some(x);
+                        self.save_feasible_trajectory_state_space();
+                    },
+                    Some(c) if self.nodes[x].cost < c => {
+                        info!("found a better path to goal on iteration: {}, cost: {} -> {}", self.iter_exec, c, self.nodes[x].cost );
+                        self.replace_best_goal( x );
+                    },
+                    _ => {},
+                }
+
+                if self.idx_reached == Some(x) {
+                    self.events.push( PlannerEvent::NewBestSolution { iteration: self.iter_exec,
+                                                                      cost: self.nodes[x].cost,
+                                                                      cost_previous: cost_previous } );
+                }
+                
+                if self.config.stop_on_solution {
+                    self.stop_reason = Some( StopReason::SolutionFound );
                     break;
-                },
-                _ => {},
+                }
             }
         }
         
@@ -1299,43 +1339,79 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         true
     }
     
-    fn get_best_trajectory_config_space( & self ) -> Vec<((TObs,TObs),u32)> {
-        
-        let mut edges = vec![];
+    fn get_trajectory_config_space( & self ) -> Vec<TObs> {
+        self.nodes.iter()
+            .map(|x| self.param.model.project_state_to_config(x.state.clone()) )
+            .collect()
+    }
 
-        let lim = 1000000;
-        let mut count = 0;
-        match self.idx_reached {
-            Some(x) => {
-                let mut idx = x;
-                loop {
-                    count += 1;
-                    if count >= lim {
-                        panic!("looping");
-                    }
-                    idx = match self.link_parent.get( &idx ) {
-                        Some(parent) => {
-                            edges.push( (*parent, idx) );
-                            *parent
-                        },
-                        _ => { break; },
-                    };
-                }
-            },
-            _ => {},
-        }
+    fn get_trajectory_edges_config_space( & self ) -> Vec<(Vec<TObs>,u32)> {
+        
+        self.edges.iter()
+            .filter(|x| {
+                (self.nodes_active.contains(&(x.0).0) ||
+                 self.nodes_inactive.contains(&(x.0).0)) &&
+                (self.nodes_active.contains(&(x.0).1) ||
+                 self.nodes_inactive.contains(&(x.0).1)) })
+            .map(|x| {
+                let id_a = (x.0).0;
+                let id_b = (x.0).1;
+                ( self.tree().edge_polyline( &*self.param.model, id_a, id_b, x.1 ), (x.1).kind )
+            })
+            .collect()
+    }
 
-        edges.iter()
-            .map(|(parent,child)| {
-                let e = self.edges.get( &(*parent,*child) ).expect("edge not found");
-                let state_a = &self.nodes[*parent].state;
-                let state_b = &self.nodes[*child].state;
-                ( ( (self.param.project_state_to_config)(state_a.clone()),
-                     (self.param.project_state_to_config)(state_b.clone()) ), e.kind )
+    fn get_witness_representatives_config_space( & self ) -> Vec<(TObs,TObs)> {
+        self.witness_representative.iter()
+            .map( |(idx_witness,idx_repr)| {
+                let state_witness = self.witnesses[*idx_witness].clone();
+                let state_repr = self.nodes[*idx_repr].state.clone();
+                ( self.param.model.project_state_to_config(state_witness),
+                   self.param.model.project_state_to_config(state_repr) )
             })
             .collect()
     }
 
+    fn get_last_motion_prim_candidates( & self ) -> Vec<(TObs,TObs)>{
+        self.last_moprim_candidates.clone()
+    }
+
+    fn get_collision_checker( & self ) -> &CollisionChecker<TObs> {
+        &self.collision
+    }
+
+    fn save_checkpoint( & self, path: &str ) -> Result<()> {
+        SST::save_checkpoint( self, path )
+    }
+
+    fn get_best_trajectory_config_space( & self ) -> Vec<(Vec<TObs>,u32)> {
+        self.tree().best_path_config_space( &*self.param.model )
+    }
+
+    fn get_best_solution( & self ) -> Option<Solution<TS,TC>> {
+        self.tree().best_solution()
+    }
+
+    fn take_events( & mut self ) -> Vec<PlannerEvent> {
+        std::mem::take( & mut self.events )
+    }
+
+    fn get_stats( &self ) -> Stats {
+        Stats {
+            num_nodes: self.nodes.len() - self.nodes_freelist.len(),
+            num_nodes_active: self.nodes_active.len(),
+            num_witnesses: self.witnesses.len(),
+            num_pruned_nodes: self.stat_pruned_nodes,
+            iterations: self.iter_exec,
+            iterations_collision: self.stat_iter_collision,
+            iterations_invalid: self.stat_iter_invalid,
+            duration_ms: self.stat_time_all,
+            goal_distance: self.stat_goal_distance,
+            stop_reason: self.stop_reason,
+            ..self.tree().stats()
+        }
+    }
+    
     fn print_stats( &self ){
         info!( "witnesses: {}", self.witnesses.len() );
         info!( "nodes: {}", self.nodes.len() );
@@ -1346,6 +1422,7 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         info!( "disturbance active: {}", if self.witness_disturbance { "Y" } else { "N" } );
         info!( "iterations no change: {}/{}, {:.2}%", self.stat_iter_no_change, self.iter_exec, self.stat_iter_no_change as f32/self.iter_exec as f32 * 100. );
         info!( "iterations collision: {}/{}, {:.2}%", self.stat_iter_collision, self.iter_exec, self.stat_iter_collision as f32/self.iter_exec as f32 * 100. );
+        info!( "iterations invalid state: {}/{}, {:.2}%", self.stat_iter_invalid, self.iter_exec, self.stat_iter_invalid as f32/self.iter_exec as f32 * 100. );
 
         info!( "stat_time_mo_prim_query: {} ms / {}%", self.stat_time_mo_prim_query, self.stat_time_mo_prim_query / self.stat_time_all * 100. );
         
@@ -1361,16 +1438,17 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         
         info!( "stat_time_main_prop_check: {} ms / {}%", self.stat_time_main_prop_check, self.stat_time_main_prop_check / self.stat_time_all * 100. );
         
-        #[cfg(feature="motion_primitives")]
-        {
-            self.mo_prim.print_stats();
+        if let Some(ref mo_prim) = self.mo_prim {
+            mo_prim.print_stats();
             info!( "stat_motion_prim_invoked: {}", self.stat_motion_prim_invoked );
         }
 
         info!( "stat_batch_prop_triggered: {}", self.stat_batch_prop_triggered );
+        info!( "stat_goal_biased_samples: {}", self.stat_goal_biased_samples );
+        info!( "stat_steer_to_goal: {}", self.stat_steer_to_goal );
+        info!( "stat_goal_distance: {:?}", self.stat_goal_distance );
         
-        #[cfg(not(feature="nn_naive"))]
-        {
+        if !self.config.nn_naive {
             self.nn_query_witness.print_stats();
         }
         info!( "delta_v: {}", self.delta_v );
@@ -1379,51 +1457,35 @@ impl <TS,TC,TObs> RRT < TS,TC,TObs > for SST<TS,TC,TObs> where TS: States, TC: C
         info!( "importance_samples: {}", self.importance_samples.len() );
         info!( "optimization iterations: {}", self.optimization_iterations );
         info!( "fitness threshold: {}", self.importance_sample_gamma );
+    }
+}
 
-        let temp = true;
+#[test]
+fn test_steer_to_goal_inactive(){
 
-        if self.idx_reached.is_some() || temp {
-            use std::fs::OpenOptions;
-            let mut file = OpenOptions::new()
-                .read(true)
-                .append(true)
-                .create(true)
-                .open("stat.txt")
-                .expect("file for stat cannot be opened");
+    use crate::test_fixture;
+    use crate::planner_param::{ParamTree,PlannerConfig,StopReason};
 
-            use std::io::Write;
+    let config = PlannerConfig { seed: Some(4), steer_to_goal: Some(0.3), runge_kutta: true, stop_on_solution: false, ..PlannerConfig::default() };
 
-            let num_mo_prims = {#[cfg(feature="motion_primitives")]{
-                self.stat_motion_prim_invoked
-            }
-            #[cfg(not(feature="motion_primitives"))]{
-                0
-            }};
-            
-            writeln!( file, "{}, {}, {}, {}, {}, {}, {}, {}, {}, {}",
-                      self.delta_s,
-                      self.delta_v,
-                      self.nodes.len(),
-                      self.stat_pruned_nodes,
-                      self.witnesses.len(),
-                      self.iter_exec,
-                      self.iter_exec - self.stat_iter_no_change,
-                      self.stat_iter_no_change,
-                      self.stat_iter_collision,
-                      num_mo_prims
-            );
-
-
-            let mut file_opt = OpenOptions::new()
-                .read(true)
-                .append(true)
-                .create(true)
-                .open("optimize_log.txt")
-                .expect("file for optimize_log cannot be opened");
-            
-            writeln!(file_opt,"{}", self.importance_sample_gamma );
-            
+    let param = test_fixture::dubins( &config, 1_000 );
+    let obs = test_fixture::obstacles_empty();
+
+    let mut sst = SST::init( &param, obs, ParamTree::default(), config ).expect("planner init");
+    while sst.iterate( Some(500) ) {}
+
+    assert_eq!( sst.get_stats().stop_reason, Some(StopReason::IterationBound) );
+    assert!( sst.stat_steer_to_goal > 1 );
+
+    //every active node represents a witness, steered nodes are inactive
+    assert!( sst.nodes_active.iter().all(|x| sst.witness_representative.values().any(|y| y == x ) ) );
+    for ((_,b),e) in sst.edges.iter() {
+        if e.kind == 2 {
+            assert!( sst.nodes_inactive.contains( b ) );
         }
-        
     }
+
+    //steered goal connections of replaced solutions are pruned, a remaining one leads to the best goal node
+    let idx_reached = sst.idx_reached.expect("no solution");
+    assert!( sst.edges.iter().all(|((_,b),e)| e.kind != 2 || !sst.nodes[*b].children.is_empty() || *b == idx_reached ) );
 }
@@ -0,0 +1,305 @@
+//! Propagation tree storage and helpers shared by SST, KinoRRT and Kpiece
+//!
+//! Planners own their nodes, parent links and edges and lend them to Tree for solution extraction, display and stats,
+//! so that every planner reports trees, solutions and stop conditions the same way.
+//! Parameter validation, propagation sampling, resolution and rejection of propagations are shared as functions.
+
+use rand::prelude::*;
+use rand::distributions::Standard;
+use rand::rngs::StdRng;
+
+use serde::{Serialize,Deserialize};
+
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::{Param,ParamTree,PlannerConfig,StopCondition,StopReason};
+use crate::system_model::SystemModel;
+use crate::hash::{HashSet,HashMap};
+use crate::stats::Stats;
+use crate::solution::Solution;
+use crate::error::{PlannerError,Result};
+use crate::collision::CollisionChecker;
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "TS: States")]
+pub struct Node<TS> {
+
+    ///current node index
+    pub id: usize,
+
+    ///state space value
+    pub state: TS,
+
+    ///child node indices
+    pub children: HashSet<usize>,
+
+    ///cost from the root of the propagation tree
+    pub cost: f32,
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "TS: States, TC: Control")]
+pub struct Edge <TS,TC> {
+
+    pub control: TC,
+
+    ///duration of the control input
+    pub duration: f32,
+
+    ///integrated states strictly between parent and child, empty unless edge_substates of PlannerConfig is set
+    pub substates: Vec<TS>,
+
+    ///additional annotation for differentiating propagation type
+    pub kind: u32, //currently: 0: monte carlo propagation, 1: motion primitive propagation, 2: steering function connection
+}
+
+impl <TS,TC> Edge<TS,TC> where TS: States, TC: Control {
+    ///edge keeping ``substates`` of a propagation without its end state if edge_substates of ``config`` is set
+    pub fn init( config: &PlannerConfig, control: TC, duration: f32, kind: u32, substates: &[TS] ) -> Self {
+
+        let substates = if config.edge_substates && !substates.is_empty() {
+            substates[..substates.len()-1].to_vec()
+        } else {
+            vec![]
+        };
+
+        Edge { control: control,
+               duration: duration,
+               substates: substates,
+               kind: kind }
+    }
+}
+
+///propagation tree of a planner, borrowed from its storage
+pub struct Tree<'a,TS,TC> where TS: States, TC: Control {
+    pub nodes: &'a [Node<TS>],
+    pub link_parent: &'a HashMap< usize, usize >,
+    pub edges: &'a HashMap< (usize,usize), Edge<TS,TC> >,
+    pub nodes_goal: &'a HashSet<usize>,
+    ///lowest cost node satisfying goal condition
+    pub idx_reached: Option<usize>,
+}
+
+impl <'a,TS,TC> Tree<'a,TS,TC> where TS: States, TC: Control {
+
+    ///config space polyline of edge from ``parent`` to ``child`` through its sub-states
+    pub fn edge_polyline<TObs>( & self, model: &dyn SystemModel<TS,TC,TObs>, parent: usize, child: usize, edge: &Edge<TS,TC> ) -> Vec<TObs> where TObs: States {
+        std::iter::once( &self.nodes[parent].state )
+            .chain( edge.substates.iter() )
+            .chain( std::iter::once( &self.nodes[child].state ) )
+            .map(|x| model.project_state_to_config( x.clone() ) )
+            .collect()
+    }
+
+    ///config space projections of all nodes
+    pub fn nodes_config_space<TObs>( & self, model: &dyn SystemModel<TS,TC,TObs> ) -> Vec<TObs> where TObs: States {
+        self.nodes.iter()
+            .map(|x| model.project_state_to_config( x.state.clone() ) )
+            .collect()
+    }
+
+    ///config space polylines of all edges, with edge kind
+    pub fn edges_config_space<TObs>( & self, model: &dyn SystemModel<TS,TC,TObs> ) -> Vec<(Vec<TObs>,u32)> where TObs: States {
+        self.edges.iter()
+            .map(|((a,b),e)| ( self.edge_polyline( model, *a, *b, e ), e.kind ) )
+            .collect()
+    }
+
+    ///node indices from start to best goal node, empty if goal is not reached
+    pub fn best_path( & self ) -> Vec<usize> {
+
+        let mut idxs = vec![];
+
+        if let Some(idx_goal) = self.idx_reached {
+
+            let mut idx = idx_goal;
+            idxs.push( idx );
+
+            while let Some(parent) = self.link_parent.get( &idx ) {
+                idx = *parent;
+                idxs.push( idx );
+                if idxs.len() > self.nodes.len() {
+                    panic!("looping");
+                }
+            }
+        }
+
+        idxs.reverse();
+        idxs
+    }
+
+    ///config space polylines of edges of the best path, with edge kind
+    pub fn best_path_config_space<TObs>( & self, model: &dyn SystemModel<TS,TC,TObs> ) -> Vec<(Vec<TObs>,u32)> where TObs: States {
+        self.best_path().windows(2)
+            .map(|x| {
+                let e = self.edges.get( &(x[0],x[1]) ).expect("edge not found");
+                ( self.edge_polyline( model, x[0], x[1], e ), e.kind )
+            })
+            .collect()
+    }
+
+    pub fn best_solution( & self ) -> Option<Solution<TS,TC>> {
+
+        let idx_goal = self.idx_reached?;
+
+        let idxs = self.best_path();
+
+        let edges = idxs.windows(2)
+            .map(|x| self.edges.get( &(x[0],x[1]) ).expect("edge not found") )
+            .collect::<Vec<_>>();
+
+        Some( Solution {
+            states: idxs.iter().map(|x| self.nodes[*x].state.clone() ).collect(),
+            controls: edges.iter().map(|e| e.control.clone() ).collect(),
+            durations: edges.iter().map(|e| e.duration ).collect(),
+            substates: edges.iter().map(|e| e.substates.clone() ).collect(),
+            cost: self.nodes[idx_goal].cost,
+        } )
+    }
+
+    ///stats of the tree, every node counted as active, run progress is left to the planner
+    pub fn stats( & self ) -> Stats {
+        Stats {
+            num_nodes: self.nodes.len(),
+            num_nodes_active: self.nodes.len(),
+            num_edges: self.edges.len(),
+            goal_reached: self.idx_reached.is_some(),
+            cost: self.idx_reached.map(|x| self.nodes[x].cost ),
+            num_goal_nodes: self.nodes_goal.len(),
+            ..Stats::default()
+        }
+    }
+}
+
+///checks ``config`` and ``param_tree`` for planner ``name``,
+///motion primitives and steering to goal are accepted if ``extensions`` is set and the model supports them
+pub fn validate<TS,TC,TObs>( name: &str,
+                             param: &Param<TS,TC,TObs>,
+                             param_tree: &ParamTree,
+                             config: &PlannerConfig,
+                             extensions: bool ) -> Result<()> where TS: States, TC: Control, TObs: States {
+
+    if config.motion_primitives {
+        if !extensions {
+            return Err( PlannerError::InvalidParam( format!("motion primitives not supported by planner {}", name ) ) )
+        }
+        if param.model.motion_primitive_xform( param.states_init.clone(), param.states_init.clone() ).is_none() {
+            return Err( PlannerError::InvalidParam( format!("motion primitives not supported by model {:?}", param.model ) ) )
+        }
+    }
+
+    if config.steer_to_goal.is_some() {
+        if !extensions {
+            return Err( PlannerError::InvalidParam( format!("steer_to_goal not supported by planner {}", name ) ) )
+        }
+        if param.model.steer( &param.states_init, &param.states_init, None ).is_none() {
+            return Err( PlannerError::InvalidParam( format!("steering function not supported by model {:?}", param.model ) ) )
+        }
+    }
+
+    if !( config.goal_bias >= 0. && config.goal_bias <= 1. ) {
+        return Err( PlannerError::InvalidParam( format!("goal_bias {} not within [0,1]", config.goal_bias ) ) )
+    }
+
+    if !( param_tree.prop_delta_low <= param_tree.prop_delta_high ) {
+        return Err( PlannerError::InvalidParam( format!("prop_delta_low {} greater than prop_delta_high {}",
+                                                        param_tree.prop_delta_low, param_tree.prop_delta_high ) ) )
+    }
+
+    if config.collision_resolution.map_or( false, |x| !( x > 0. ) ) {
+        return Err( PlannerError::InvalidParam( format!("collision_resolution {:?} not positive", config.collision_resolution ) ) )
+    }
+
+    Ok(())
+}
+
+///rng seeded from seed of ``config``, from entropy if unset
+pub fn rng( config: &PlannerConfig ) -> StdRng {
+    match config.seed {
+        Some(seed) => { StdRng::seed_from_u64( seed ) },
+        _ => { StdRng::from_entropy() },
+    }
+}
+
+///duration resolution of propagation: the collision resolution, or sim_delta if finer and edge sub-states are kept
+pub fn resolution<TS,TC,TObs>( param: &Param<TS,TC,TObs>,
+                               collision: &CollisionChecker<TObs>,
+                               config: &PlannerConfig ) -> Option<f32> where TS: States, TC: Control, TObs: States {
+    match ( collision.resolution, config.edge_substates ) {
+        ( Some(r), true ) => { Some( r.min( param.sim_delta ) ) },
+        ( None, true ) => { Some( param.sim_delta ) },
+        ( r, false ) => { r },
+    }
+}
+
+///propagation with random time delta within [prop_l, prop_h] of sim_delta and random control
+pub fn monte_carlo_propagation<TS,TC,TObs>( param: &Param<TS,TC,TObs>,
+                                            prop_l: f32,
+                                            prop_h: f32,
+                                            rng: &mut StdRng ) -> (f32, TC) where TS: States, TC: Control, TObs: States {
+
+    let val: f32 = rng.sample(Standard);
+
+    let monte_carlo_prop_delta = val.max( prop_l ).min( prop_h ) * param.sim_delta;
+
+    //sampler for control space
+    let control_sample = param.model.sample_control( monte_carlo_prop_delta, rng );
+
+    ( monte_carlo_prop_delta, control_sample )
+}
+
+///return true if ``substates`` after ``config_space_before`` are invalid or collide,
+///counting the rejection in ``stat_iter_invalid`` or ``stat_iter_collision``
+pub fn rejected<TS,TC,TObs>( param: &Param<TS,TC,TObs>,
+                             collision: &CollisionChecker<TObs>,
+                             config_space_before: &TObs,
+                             substates: &[TS],
+                             stat_iter_invalid: & mut u32,
+                             stat_iter_collision: & mut u32 ) -> bool where TS: States, TC: Control, TObs: States {
+
+    if !param.states_valid( substates ) {
+        *stat_iter_invalid += 1;
+        true
+    } else if collision.propagation_collision( &*param.model, config_space_before, substates ) {
+        *stat_iter_collision += 1;
+        true
+    } else {
+        false
+    }
+}
+
+///iterations of a batch of ``iteration`` iterations, unbounded if None,
+///None if ``stop_condition`` is met after ``iter_exec`` iterations and ``time_ms``, which sets ``stop_reason``
+pub fn batch( stop_condition: &StopCondition,
+              stop_reason: & mut Option<StopReason>,
+              iter_exec: u32,
+              time_ms: f64,
+              iteration: Option<u32> ) -> Option<u32> {
+
+    if stop_reason.is_none() {
+        *stop_reason = stop_condition.exhausted( iter_exec, time_ms );
+    }
+
+    if stop_reason.is_some() {
+        return None
+    }
+
+    //unbounded batch runs until stop condition is met
+    Some( iteration.unwrap_or( std::u32::MAX ) )
+}
+
+///return true and set ``stop_reason`` if ``stop_condition`` is met within a batch
+pub fn stopped( stop_condition: &StopCondition,
+                stop_reason: & mut Option<StopReason>,
+                iter_exec: u32,
+                time_ms: f64 ) -> bool {
+
+    match stop_condition.exhausted( iter_exec, time_ms ) {
+        Some(reason) => {
+            info!("planner stopped on iteration: {}, {:?}", iter_exec, reason );
+            *stop_reason = Some(reason);
+            true
+        },
+        _ => { false },
+    }
+}
@@ -0,0 +1,293 @@
+//! Trajectory post-processing: shortcutting and smoothing
+//!
+//! Solutions found by random control sampling zig-zag. Shortcutting connects two states of a solution directly,
+//! with SystemModel::steer if the model provides it, otherwise by re-propagating sampled controls from the first state
+//! and keeping the sub-state closest to the second. Smoothing extends the control of an edge over the following edge.
+//!
+//! Edges after a modified section are re-propagated with their original controls, so the result stays dynamically feasible.
+//! A candidate is accepted if its states are valid, it is collision free at the collision resolution, it ends in the goal region
+//! and it lowers the cost (shortcuts) or does not raise it (smoothing).
+
+use rand::{Rng,RngCore};
+
+use serde::{Serialize,Deserialize};
+
+use crate::states::States;
+use crate::control::Control;
+use crate::planner_param::Param;
+use crate::collision::{self,CollisionChecker};
+use crate::solution::Solution;
+
+///controls sampled per shortcut attempt when the model has no steering function
+const REPROPAGATE_SAMPLES: u32 = 10;
+
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
+pub struct ShortcutStats {
+    pub iterations: u32,
+    ///accepted shortcuts
+    pub shortcuts: u32,
+    ///accepted smoothing steps
+    pub smoothings: u32,
+    pub cost_before: f32,
+    pub cost_after: f32,
+}
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States and Control
+pub struct ShortcutResult<TS,TC> where TS: States, TC: Control {
+    pub solution: Solution<TS,TC>,
+    pub stats: ShortcutStats,
+}
+
+///propagated section of a trajectory
+struct Section<TS> {
+    ///end state of each edge
+    states: Vec<TS>,
+    ///sub-states strictly between edge end points, empty if not kept
+    substates: Vec<Vec<TS>>,
+    costs: Vec<f32>,
+}
+
+///follows ``connection`` edges with their given sub-states from ``start``, then propagates ``edges``,
+///None if a state is invalid or an edge collides
+fn propagate<TS,TC,TObs>( param: &Param<TS,TC,TObs>,
+                          collision: &CollisionChecker<TObs>,
+                          resolution: Option<f32>,
+                          keep_substates: bool,
+                          start: &TS,
+                          connection: &[(TC,f32,Vec<TS>)],
+                          edges: &[(TC,f32)] ) -> Option<Section<TS>> where TS: States, TC: Control, TObs: States {
+
+    let model = &*param.model;
+
+    let mut section = Section { states: vec![], substates: vec![], costs: vec![] };
+
+    let mut s = start.clone();
+
+    let n = connection.len();
+
+    for i in 0..n + edges.len() {
+
+        let (u, t, mut sub) = if i < n {
+            connection[i].clone()
+        } else {
+            let (u,t) = &edges[i-n];
+            ( u.clone(), *t, collision::integrate( model, s.clone(), u.clone(), *t, resolution ) )
+        };
+
+        if !param.states_valid( &sub ) ||
+            collision.propagation_collision( model, &model.project_state_to_config( s.clone() ), &sub ) {
+            return None
+        }
+
+        let end = match sub.pop() {
+            Some(x) => { x },
+            _ => { return None },
+        };
+
+        section.costs.push( param.cost.edge_cost( model, &s, &u, t, &end ) );
+        section.substates.push( if keep_substates { sub } else { vec![] } );
+        section.states.push( end.clone() );
+
+        s = end;
+    }
+
+    Some( section )
+}
+
+///edge from ``start`` with a sampled control and the duration that gets closest to ``target`` within ``duration_max``
+fn repropagate<TS,TC,TObs,R>( param: &Param<TS,TC,TObs>,
+                              start: &TS,
+                              target: &TS,
+                              duration_max: f32,
+                              resolution: Option<f32>,
+                              rng: &mut R ) -> Option<(TC,f32,Vec<TS>)> where TS: States, TC: Control, TObs: States, R: RngCore {
+
+    let model = &*param.model;
+
+    let dt = param.sim_delta;
+    let steps = ( duration_max / dt ).ceil().max( 1. ) as usize;
+
+    let mut best : Option<(f32,TC,f32)> = None;
+
+    for _ in 0..REPROPAGATE_SAMPLES {
+
+        let u = model.sample_control( dt, rng );
+
+        let mut s = start.clone();
+
+        for k in 0..steps {
+            s = model.dynamics( s, u.clone(), dt );
+            let d = model.ss_metric( s.clone(), target.clone() );
+            if best.as_ref().map_or( true, |x| d < x.0 ) {
+                best = Some( ( d, u.clone(), (k+1) as f32 * dt ) );
+            }
+        }
+    }
+
+    best.map(|(_,u,t)| {
+        let sub = collision::integrate( model, start.clone(), u.clone(), t, resolution );
+        (u, t, sub)
+    })
+}
+
+///shortcuts and smooths ``solution`` for ``iterations`` random attempts,
+///edges are collision checked at the resolution of ``collision``, sub-states are kept at sim_delta resolution if ``solution`` has them
+pub fn shortcut<TS,TC,TObs,R>( param: &Param<TS,TC,TObs>,
+                               collision: &CollisionChecker<TObs>,
+                               solution: &Solution<TS,TC>,
+                               iterations: u32,
+                               rng: &mut R ) -> ShortcutResult<TS,TC> where TS: States, TC: Control, TObs: States, R: RngCore {
+
+    let model = &*param.model;
+
+    let keep_substates = !solution.substates.is_empty();
+
+    let resolution = if keep_substates {
+        Some( collision.resolution.map_or( param.sim_delta, |r| r.min( param.sim_delta ) ) )
+    } else {
+        collision.resolution
+    };
+
+    let mut states = solution.states.clone();
+    let mut edges = solution.controls.iter().cloned().zip( solution.durations.iter().cloned() ).collect::<Vec<_>>();
+    let mut substates = if keep_substates { solution.substates.clone() } else { vec![ vec![]; edges.len() ] };
+    let mut costs = edges.iter().enumerate()
+        .map(|(i,(u,t))| param.cost.edge_cost( model, &states[i], u, *t, &states[i+1] ) )
+        .collect::<Vec<_>>();
+
+    let mut stats = ShortcutStats {
+        iterations: iterations,
+        cost_before: solution.cost,
+        cost_after: solution.cost,
+        ..Default::default()
+    };
+
+    for _ in 0..iterations {
+
+        let n = edges.len();
+
+        if n < 2 {
+            break;
+        }
+
+        let is_shortcut = rng.gen_bool( 0.5 );
+
+        //replaces edges from state i to state j with ``connection``
+        let (i, j, connection) = if is_shortcut {
+
+            let i = rng.gen_range( 0, n-1 );
+            let j = rng.gen_range( i+2, n+1 );
+
+            let connection = match model.steer( &states[i], &states[j], resolution ) {
+                Some(x) => { x },
+                _ => {
+                    let duration_max = edges[i..j].iter().map(|x| x.1 ).sum();
+                    match repropagate( param, &states[i], &states[j], duration_max, resolution, rng ) {
+                        Some(x) => { vec![ x ] },
+                        _ => { continue; },
+                    }
+                },
+            };
+
+            (i, j, connection)
+
+        } else {
+            let k = rng.gen_range( 0, n-1 );
+            let (u,t) = ( edges[k].0.clone(), edges[k].1 + edges[k+1].1 );
+            let sub = collision::integrate( model, states[k].clone(), u.clone(), t, resolution );
+            ( k, k+2, vec![ (u, t, sub) ] )
+        };
+
+        let tail = connection.iter().map(|x| ( x.0.clone(), x.1 ) ).chain( edges[j..].iter().cloned() ).collect::<Vec<_>>();
+
+        let section = match propagate( param, collision, resolution, keep_substates, &states[i], &connection, &edges[j..] ) {
+            Some(x) => { x },
+            _ => { continue; },
+        };
+
+        let reached = section.states.last().map_or( false, |x| param.goal_region.contains( model, x, &param.states_goal ) );
+
+        let cost = costs[..i].iter().sum::<f32>() + section.costs.iter().sum::<f32>();
+
+        let improved = if is_shortcut { cost < stats.cost_after } else { cost <= stats.cost_after };
+
+        if reached && improved {
+
+            states.truncate( i+1 );
+            states.extend( section.states );
+            edges.truncate( i );
+            edges.extend( tail );
+            substates.truncate( i );
+            substates.extend( section.substates );
+            costs.truncate( i );
+            costs.extend( section.costs );
+
+            stats.cost_after = cost;
+
+            if is_shortcut {
+                stats.shortcuts += 1;
+            } else {
+                stats.smoothings += 1;
+            }
+        }
+    }
+
+    info!("shortcut: cost {} -> {}, {} shortcuts, {} smoothings", stats.cost_before, stats.cost_after, stats.shortcuts, stats.smoothings );
+
+    ShortcutResult {
+        solution: Solution {
+            states: states,
+            controls: edges.iter().map(|x| x.0.clone() ).collect(),
+            durations: edges.iter().map(|x| x.1 ).collect(),
+            substates: if keep_substates { substates } else { vec![] },
+            cost: stats.cost_after,
+        },
+        stats: stats,
+    }
+}
+
+#[test]
+fn test_shortcut(){
+
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use crate::planner_param::{ParamTree,PlannerConfig};
+    use crate::rrt::{kinorrt::KinoRRT,rrt::RRT};
+    use crate::test_fixture;
+
+    let config = PlannerConfig { seed: Some(11), nn_naive: true, ..PlannerConfig::default() };
+
+    let param = test_fixture::dubins( &config, 20_000 );
+    let obs = test_fixture::obstacles_between();
+
+    //any solution around the box will do, rrt finds one fastest
+    let mut rrt = KinoRRT::init( &param, obs, ParamTree::default(), config, false ).expect("planner init");
+    while rrt.iterate( Some(1000) ) {}
+
+    //the box lies on the straight connection from start to goal, so a shortcut across it must be rejected
+    let model = &*param.model;
+    assert!( rrt.collision.segment_collision( &model.project_state_to_config( param.states_init.clone() ),
+                                              &model.project_state_to_config( param.states_goal.clone() ) ) );
+
+    let sol = rrt.get_best_solution().expect("no solution");
+
+    let r = shortcut( &param, &rrt.collision, &sol, 100, &mut StdRng::seed_from_u64( 0 ) );
+
+    assert!( r.stats.shortcuts + r.stats.smoothings > 0 );
+    assert!( r.stats.cost_after <= r.stats.cost_before );
+    assert_eq!( r.solution.cost, r.stats.cost_after );
+    assert_eq!( r.solution.states.len(), r.solution.controls.len() + 1 );
+    assert_eq!( r.solution.states[0].0, sol.states[0].0 );
+    assert!( param.goal_region.contains( &*param.model, r.solution.states.last().unwrap(), &param.states_goal ) );
+    assert_eq!( rrt.collision.check_solution( &param, &r.solution ), None );
+
+    //shortcuts are dynamically feasible: replaying the controls reproduces the states
+    let replay = collision::integrate_edges( model,
+                                             r.solution.states[0].clone(),
+                                             r.solution.controls.iter().cloned().zip( r.solution.durations.iter().cloned() ).collect(),
+                                             rrt.collision.resolution );
+    for ((_,_,sub),s) in replay.iter().zip( r.solution.states[1..].iter() ) {
+        assert_eq!( sub.last().unwrap().0, s.0 );
+    }
+}
@@ -0,0 +1,135 @@
+//! Solution trajectory
+//!
+//! Full state space sequence of a plan along with the control and duration of each edge, exportable as json or csv.
+//! Edges may carry their intermediate integrated states, in which case exports are polylines through them.
+
+use std::fmt::Write;
+
+use serde::{Serialize,Deserialize};
+
+use crate::states::States;
+use crate::control::Control;
+use crate::error::{PlannerError,Result};
+
+#[derive(Clone,Debug,Serialize,Deserialize)]
+#[serde(bound = "")] //serde bounds are implied by States and Control
+pub struct Solution <TS,TC> where TS: States, TC: Control {
+
+    ///states from start to goal
+    pub states: Vec<TS>,
+
+    ///control applied on edge i, from states[i] to states[i+1]
+    pub controls: Vec<TC>,
+
+    ///duration of edge i
+    pub durations: Vec<f32>,
+
+    ///integrated states strictly between states[i] and states[i+1], empty if sub-states of edges are not kept
+    #[serde(default)]
+    pub substates: Vec<Vec<TS>>,
+
+    ///total cost of the trajectory
+    pub cost: f32,
+}
+
+impl <TS,TC> Solution<TS,TC> where TS: States, TC: Control {
+
+    pub fn to_json( & self ) -> String {
+        serde_json::to_string_pretty( self ).expect("solution serialization failed")
+    }
+
+    ///one row per state: state values, followed by control and duration of the outgoing edge (empty for the last state),
+    ///sub-states of an edge follow its start state with empty control and duration
+    pub fn to_csv( & self ) -> String {
+
+        let mut s = String::new();
+
+        let dim_s = self.states.first().map_or( 0, |x| x.get_num_dims() );
+        let dim_c = self.controls.first().map_or( 0, |x| x.get_num_dims() );
+
+        let header = (0..dim_s).map(|i| format!("s{}",i) )
+            .chain( (0..dim_c).map(|i| format!("u{}",i) ) )
+            .chain( std::iter::once( "duration".to_string() ) )
+            .collect::<Vec<_>>();
+
+        writeln!( s, "{}", header.join(",") ).unwrap();
+
+        for (i,state) in self.states.iter().enumerate() {
+
+            let mut row = state.get_vals().iter().map(|x| x.to_string() ).collect::<Vec<_>>();
+
+            match ( self.controls.get(i), self.durations.get(i) ) {
+                ( Some(u), Some(t) ) => {
+                    row.extend( u.get_vals().iter().map(|x| x.to_string() ) );
+                    row.push( t.to_string() );
+                },
+                _ => {
+                    row.extend( (0..dim_c+1).map(|_| String::new() ) );
+                },
+            }
+
+            writeln!( s, "{}", row.join(",") ).unwrap();
+
+            for sub in self.substates.get(i).map_or( &[][..], |x| &x[..] ) {
+                
+                let row = sub.get_vals().iter().map(|x| x.to_string() )
+                    .chain( (0..dim_c+1).map(|_| String::new() ) )
+                    .collect::<Vec<_>>();
+
+                writeln!( s, "{}", row.join(",") ).unwrap();
+            }
+        }
+
+        s
+    }
+
+    pub fn save_json( & self, path: &str ) -> Result<()> {
+        let s = serde_json::to_string_pretty( self ).map_err(|e| PlannerError::json( path, e ) )?;
+        std::fs::write( path, s ).map_err(|e| PlannerError::io( path, e ) )
+    }
+
+    pub fn save_csv( & self, path: &str ) -> Result<()> {
+        std::fs::write( path, self.to_csv() ).map_err(|e| PlannerError::io( path, e ) )
+    }
+
+    ///saves as csv if ``path`` ends with .csv, json otherwise
+    pub fn save( & self, path: &str ) -> Result<()> {
+        if path.ends_with(".csv") {
+            self.save_csv( path )
+        } else {
+            self.save_json( path )
+        }
+    }
+}
+
+#[test]
+fn test_solution_csv(){
+
+    use crate::states::States3D;
+    use crate::control::Control1D;
+    
+    let sol = Solution {
+        states: vec![ States3D([0., 0., 0.]), States3D([1., 0., 0.5]) ],
+        controls: vec![ Control1D([0.5]) ],
+        durations: vec![ 1. ],
+        substates: vec![],
+        cost: 1.,
+    };
+
+    let s = sol.to_csv();
+    let lines = s.lines().collect::<Vec<_>>();
+    
+    assert_eq!( lines, vec![ "s0,s1,s2,u0,duration", "0,0,0,0.5,1", "1,0,0.5,," ] );
+
+    let sol = Solution { substates: vec![ vec![ States3D([0.5, 0., 0.25]) ] ], ..sol };
+
+    let s = sol.to_csv();
+    let lines = s.lines().collect::<Vec<_>>();
+    
+    assert_eq!( lines, vec![ "s0,s1,s2,u0,duration", "0,0,0,0.5,1", "0.5,0,0.25,,", "1,0,0.5,," ] );
+
+    //write failure is reported instead of aborting
+    let dir = std::env::temp_dir().join("sample_planning_test_solution_missing_dir");
+    let path = dir.join("solution.csv");
+    assert!( sol.save( path.to_str().unwrap() ).is_err() );
+}
@@ -1,13 +1,16 @@
 use std::fmt::Debug;
 
-pub trait States : Clone + Debug + Default {
+use serde::{Serialize,Deserialize};
+use serde::de::DeserializeOwned;
+
+pub trait States : Clone + Debug + Default + Serialize + DeserializeOwned + Send + Sync + 'static {
     fn get_num_dims(&self) -> i32;
     fn get_vals(&self) -> Vec<f32>;
     fn get_vals_3(&self) -> [f32;3];
     fn set_vals(& mut self, vals: &[f32] );
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct States1D(pub f32);
 
 impl States for States1D {
@@ -26,7 +29,7 @@ impl States for States1D {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct States2D(pub[f32;2]);
 
 impl States for States2D {
@@ -41,13 +44,11 @@ impl States for States2D {
     }
     fn set_vals(& mut self, vals: &[f32] ){
         debug_assert!( vals.len() == 2 );
-        for i in 0..2 {
-            self.0[i] = vals[i];
-        }
+        self.0.copy_from_slice( &vals[..2] );
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct States3D(pub[f32;3]);
 
 impl States for States3D {
@@ -62,13 +63,11 @@ impl States for States3D {
     }
     fn set_vals(& mut self, vals: &[f32] ){
         debug_assert!( vals.len() == 3 );
-        for i in 0..3 {
-            self.0[i] = vals[i];
-        }
+        self.0.copy_from_slice( &vals[..3] );
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
 pub struct States4D(pub[f32;4]);
 
 impl States for States4D {
@@ -83,8 +82,6 @@ impl States for States4D {
     }
     fn set_vals(& mut self, vals: &[f32] ){
         debug_assert!( vals.len() == 4 );
-        for i in 0..4 {
-            self.0[i] = vals[i];
-        }
+        self.0.copy_from_slice( &vals[..4] );
     }
 }
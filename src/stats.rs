@@ -1,5 +1,28 @@
+use serde::{Serialize,Deserialize};
+
+use crate::planner_param::StopReason;
+
+///summary of planner progress, see Planner::get_stats
+#[derive(Clone,Debug,Default,Serialize,Deserialize)]
 pub struct Stats {
-    pub num_nodes: i32,
-    pub num_edges: i32,
-    pub memory: i32,
+    pub num_nodes: usize,
+    pub num_nodes_active: usize,
+    pub num_edges: usize,
+    pub num_witnesses: usize,
+    pub num_pruned_nodes: u32,
+    pub iterations: u32,
+    pub iterations_collision: u32,
+    ///iterations with a propagation rejected by state validity
+    pub iterations_invalid: u32,
+    ///accumulated planning time
+    pub duration_ms: f64,
+    pub goal_reached: bool,
+    ///cost of the node reaching goal
+    pub cost: Option<f32>,
+    ///nodes in the tree satisfying goal condition
+    pub num_goal_nodes: usize,
+    ///closest distance to goal region reached so far, 0 once goal is reached
+    pub goal_distance: Option<f32>,
+    ///set once the planner has stopped
+    pub stop_reason: Option<StopReason>,
 }
@@ -0,0 +1,74 @@
+//! System model interface
+//!
+//! Dynamics, samplers and metrics of a system, used by the planner and motion primitive lookup.
+//! Implementors may carry their own parameters (eg: speed, turning limit, workspace bounds, goal tolerance).
+
+use std::fmt::Debug;
+
+use rand::RngCore;
+
+use crate::states::States;
+use crate::control::Control;
+
+pub trait SystemModel<TS,TC,TObs>: Debug + Send + Sync where TS: States, TC: Control, TObs: States {
+
+    ///propagate ``states`` with ``control`` for ``delta`` duration, uses state space of system
+    fn dynamics( &self, states: TS, control: TC, delta: f32 ) -> TS;
+
+    ///projection from state space to configuration space
+    fn project_state_to_config( &self, states: TS ) -> TObs;
+
+    ///sampling in parameter space for a propagation of ``delta`` duration, randomness is drawn from ``rng`` only
+    fn sample_control( &self, delta: f32, rng: &mut dyn RngCore ) -> TC;
+
+    ///sampling in state space, randomness is drawn from ``rng`` only
+    fn sample_state( &self, rng: &mut dyn RngCore ) -> TS;
+
+    ///sampling in a ball of ``radius`` around position of ``goal``, heading is sampled uniformly if ``ignore_heading`` is set,
+    ///defaults to ``goal`` itself
+    fn sample_goal_region( &self, goal: TS, _radius: f32, _ignore_heading: bool, _rng: &mut dyn RngCore ) -> TS {
+        goal
+    }
+
+    ///distance function in state space
+    fn ss_metric( &self, a: TS, b: TS ) -> f32;
+
+    ///estimated closeness in configuration space
+    fn cs_metric( &self, a: TObs, b: TObs ) -> f32;
+
+    ///number of leading state dimensions that are positional, used by goal regions
+    fn position_dims( &self ) -> usize;
+
+    ///index of heading angle in state, used by goal regions
+    fn heading_dim( &self ) -> Option<usize> {
+        None
+    }
+
+    ///validity of a propagated state apart from obstacle collision, eg: workspace bounds, defaults to valid
+    fn state_valid( &self, _state: &TS ) -> bool {
+        true
+    }
+
+    fn ss_add( &self, a: TS, b: TS ) -> TS;
+
+    fn ss_mul( &self, a: TS, b: f32 ) -> TS;
+
+    ///trajectory from ``from`` to ``to`` in free space as edges of (control, duration, sub-states), used for connecting to the goal
+    ///and shortcutting solutions, sub-states are integrated with ``dynamics`` as by collision::integrate for ``resolution``,
+    ///so that replaying the controls reproduces them and the end may differ from ``to`` by integration error,
+    ///None if the model has no steering function or ``to`` is not reachable within its control bounds
+    fn steer( &self, _from: &TS, _to: &TS, _resolution: Option<f32> ) -> Option<Vec<(TC,f32,Vec<TS>)>> {
+        None
+    }
+
+    ///map ``q_end`` to frame of motion primitive lookup where ``q_start`` is at origin,
+    ///optional, but required when running with motion primitives
+    fn motion_primitive_xform( &self, _q_start: TS, _q_end: TS ) -> Option<TS> {
+        None
+    }
+
+    ///inverse of ``motion_primitive_xform``
+    fn motion_primitive_xform_inv( &self, _q_start: TS, _qq_end: TS ) -> Option<TS> {
+        None
+    }
+}
@@ -0,0 +1,40 @@
+//! Fixtures shared by unit tests
+//!
+//! The default dubins car problem goes from (0.5,0.1) to (0.8,0.1), OBSTACLE_BETWEEN blocks the straight line in between.
+
+use std::marker::PhantomData;
+
+use zpatial::mazth::rbox::RecBox;
+
+use crate::states::{States,States3D};
+use crate::control::Control1D;
+use crate::planner_param::{Param,ParamObstacles,ObsVariant,PlannerConfig,StopCondition};
+use crate::dynamics_dubins;
+use crate::goal::GoalRegion;
+
+///center and half extent of a box between start and goal of the default dubins problem
+pub const OBSTACLE_BETWEEN: ( [f64;3], f64 ) = ( [ 0.65, 0.1, 0. ], 0.03 );
+
+///goal tolerance of the default dubins problem in tests, looser than the model's so that planners solve in few iterations
+pub const GOAL_TOLERANCE: f32 = 0.03;
+
+///default dubins car problem with the integrator of ``config``, stopping after ``iterations``
+pub fn dubins( config: &PlannerConfig, iterations: u32 ) -> Param<States3D,Control1D,States3D> {
+    let mut param = dynamics_dubins::load_model( config.integrator() );
+    param.stop_condition = StopCondition::Iterations( iterations );
+    param.goal_region = GoalRegion::Tolerance { position: GOAL_TOLERANCE, heading: None };
+    param
+}
+
+pub fn obstacles_empty<T>() -> ParamObstacles<T> where T: States {
+    ParamObstacles { obstacles: ObsVariant::RBOX(vec![]), states_info: PhantomData }
+}
+
+///boxes of half extent ``size`` around ``centers``
+pub fn obstacles_boxes<T>( centers: &[[f64;3]], size: f64 ) -> ParamObstacles<T> where T: States {
+    ParamObstacles { obstacles: ObsVariant::RBOX( centers.iter().map(|x| RecBox::init( x, size ) ).collect() ), states_info: PhantomData }
+}
+
+pub fn obstacles_between<T>() -> ParamObstacles<T> where T: States {
+    obstacles_boxes( &[ OBSTACLE_BETWEEN.0 ], OBSTACLE_BETWEEN.1 )
+}
@@ -0,0 +1,93 @@
+//! State validity constraints
+//!
+//! Propagated states must satisfy SystemModel::state_valid and the constraints of Param, in addition to being collision free.
+//!
+//! Scenario files may add constraints, eg:
+//! "constraints": [ { "workspace": [ [0.0, 1.0], [0.0, 1.0] ] }, { "limit": { "dim": 2, "low": 0.0, "high": 0.5 } } ]
+
+use std::fmt;
+use std::sync::Arc;
+
+use serde::{Serialize,Deserialize};
+
+use crate::states::States;
+use crate::control::Control;
+use crate::system_model::SystemModel;
+
+#[derive(Clone,Serialize,Deserialize)]
+#[serde(rename_all = "lowercase", bound = "")]
+pub enum StateConstraint<TS> {
+
+    ///(low, high) range of each positional dimension, dimensions beyond the list are not constrained
+    Workspace( Vec<(f32,f32)> ),
+
+    ///range of state dimension ``dim``
+    Limit { dim: usize, low: f32, high: f32 },
+
+    ///arbitrary validity condition of a state
+    #[serde(skip)]
+    Predicate( Arc<dyn Fn(&TS) -> bool + Send + Sync> ),
+}
+
+impl<TS> fmt::Debug for StateConstraint<TS> {
+
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateConstraint::Workspace( x ) => { f.debug_tuple("Workspace").field( x ).finish() },
+            StateConstraint::Limit { dim, low, high } => {
+                f.debug_struct("Limit")
+                    .field("dim", dim )
+                    .field("low", low )
+                    .field("high", high )
+                    .finish()
+            },
+            StateConstraint::Predicate(_) => { write!( f, "Predicate" ) },
+        }
+    }
+}
+
+///true if each of ``vals`` is within the corresponding range of ``bounds``
+pub fn within_bounds( vals: &[f32], bounds: &[(f32,f32)] ) -> bool {
+    vals.iter().zip( bounds.iter() ).all(|(x,(lo,hi))| *x >= *lo && *x <= *hi )
+}
+
+impl<TS> StateConstraint<TS> where TS: States {
+
+    pub fn satisfied<TC,TObs>( & self, model: &dyn SystemModel<TS,TC,TObs>, state: &TS ) -> bool where TC: Control, TObs: States {
+        match self {
+            StateConstraint::Workspace( bounds ) => {
+                let vals = state.get_vals();
+                let n = model.position_dims().min( vals.len() );
+                within_bounds( &vals[..n], bounds )
+            },
+            StateConstraint::Limit { dim, low, high } => {
+                state.get_vals().get( *dim ).map_or( true, |x| *x >= *low && *x <= *high )
+            },
+            StateConstraint::Predicate(f) => { f( state ) },
+        }
+    }
+}
+
+#[test]
+fn test_state_constraint(){
+
+    use crate::states::States3D;
+    use crate::dynamics_dubins::DubinsCar;
+
+    let model = DubinsCar::default();
+
+    assert!( model.state_valid( &States3D([0.5, 0.5, 6.]) ) );
+    assert!( !model.state_valid( &States3D([1.1, 0.5, 0.]) ) );
+
+    let c = StateConstraint::Workspace( vec![ (0., 0.5), (0., 0.5) ] );
+    assert!( c.satisfied( &model, &States3D([0.4, 0.5, 6.]) ) );
+    assert!( !c.satisfied( &model, &States3D([0.4, 0.6, 0.]) ) );
+
+    let c : StateConstraint<States3D> = serde_json::from_str( r#"{ "limit": { "dim": 2, "low": 0.0, "high": 1.0 } }"# ).unwrap();
+    assert!( c.satisfied( &model, &States3D([5., 5., 0.5]) ) );
+    assert!( !c.satisfied( &model, &States3D([0., 0., 1.5]) ) );
+
+    let c = StateConstraint::Predicate( Arc::new(|x: &States3D| x.0[0] < x.0[1] ) );
+    assert!( c.satisfied( &model, &States3D([0.1, 0.2, 0.]) ) );
+    assert!( !c.satisfied( &model, &States3D([0.3, 0.2, 0.]) ) );
+}